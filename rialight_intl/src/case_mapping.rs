@@ -0,0 +1,37 @@
+//! Locale-aware case conversion.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, case_mapping};
+//!
+//! assert_eq!(case_mapping::to_uppercase(&locale!("en"), "istanbul"), "ISTANBUL");
+//! assert_eq!(case_mapping::to_uppercase(&locale!("tr"), "istanbul"), "İSTANBUL");
+//! assert_eq!(case_mapping::to_lowercase(&locale!("tr"), "KIRMIZI"), "kırmızı");
+//! ```
+
+use crate::locale::Locale;
+use icu::casemap::CaseMapper;
+
+/// Converts a string to uppercase according to the casing rules of
+/// the given locale, such as `i` to `İ` in Turkish. Locales without
+/// specific rules use the Unicode default casing.
+pub fn to_uppercase(locale: &Locale, s: &str) -> String {
+    CaseMapper::new().uppercase_to_string(s, &locale.id)
+}
+
+/// Converts a string to lowercase according to the casing rules of
+/// the given locale, such as `I` to `ı` in Turkish. Locales without
+/// specific rules use the Unicode default casing.
+pub fn to_lowercase(locale: &Locale, s: &str) -> String {
+    CaseMapper::new().lowercase_to_string(s, &locale.id)
+}
+
+/// Folds the case of a string for case-insensitive matching, using the
+/// Turkic folding of `I` and `İ` in Turkish and Azerbaijani.
+pub fn fold(locale: &Locale, s: &str) -> String {
+    match locale.id.language.as_str() {
+        "tr" | "az" => CaseMapper::new().fold_turkic_string(s),
+        _ => CaseMapper::new().fold_string(s),
+    }
+}
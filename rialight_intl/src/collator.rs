@@ -0,0 +1,108 @@
+//! Locale-aware string comparison, for sorting.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, collator::{Collator, CollatorOptions}};
+//!
+//! let mut names = ["Zoe", "Ängel", "Adam"];
+//! Collator::new(&locale!("de"), &CollatorOptions::new()).sort(&mut names);
+//! assert_eq!(names, ["Adam", "Ängel", "Zoe"]);
+//! Collator::new(&locale!("sv"), &CollatorOptions::new()).sort(&mut names);
+//! assert_eq!(names, ["Adam", "Zoe", "Ängel"]);
+//! ```
+
+use crate::locale::Locale;
+use icu::collator::{CaseLevel, Numeric, Strength};
+use std::{cell::Cell, cmp::Ordering};
+
+/// Which differences between strings make them compare unequal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CollationSensitivity {
+    /// Only base letters differ; `a`, `á` and `A` are equal.
+    Base,
+    /// Base letters and accents differ; `a` and `A` are equal.
+    Accent,
+    /// Base letters and case differ; `a` and `á` are equal.
+    Case,
+    /// Base letters, accents and case differ.
+    Variant,
+}
+
+/// Options for comparing strings.
+///
+/// By default, strings are compared with `CollationSensitivity::Variant`
+/// and digits are compared as characters.
+#[derive(Clone)]
+pub struct CollatorOptions {
+    m_sensitivity: Cell<CollationSensitivity>,
+    m_numeric: Cell<bool>,
+}
+
+impl Default for CollatorOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CollatorOptions {
+    pub fn new() -> Self {
+        CollatorOptions {
+            m_sensitivity: Cell::new(CollationSensitivity::Variant),
+            m_numeric: Cell::new(false),
+        }
+    }
+
+    /// Which differences make strings compare unequal.
+    /// Default is `CollationSensitivity::Variant`.
+    pub fn sensitivity(&mut self, value: CollationSensitivity) -> &mut Self {
+        self.m_sensitivity.set(value);
+        self
+    }
+
+    /// Whether sequences of digits are compared by their numeric value,
+    /// so that `item2` sorts before `item10`. Default is `false`.
+    pub fn numeric(&mut self, value: bool) -> &mut Self {
+        self.m_numeric.set(value);
+        self
+    }
+}
+
+/// Compares strings according to the rules of a locale.
+pub struct Collator {
+    m_collator: icu::collator::Collator,
+}
+
+impl Collator {
+    /// Creates a collator for the given locale. Locales without specific
+    /// rules use the root collation order.
+    pub fn new(locale: &Locale, options: &CollatorOptions) -> Self {
+        let mut collator_options = icu::collator::CollatorOptions::new();
+        let (strength, case_level) = match options.m_sensitivity.get() {
+            CollationSensitivity::Base => (Strength::Primary, CaseLevel::Off),
+            CollationSensitivity::Accent => (Strength::Secondary, CaseLevel::Off),
+            CollationSensitivity::Case => (Strength::Primary, CaseLevel::On),
+            CollationSensitivity::Variant => (Strength::Tertiary, CaseLevel::Off),
+        };
+        collator_options.strength = Some(strength);
+        collator_options.case_level = Some(case_level);
+        collator_options.numeric = Some(if options.m_numeric.get() { Numeric::On } else { Numeric::Off });
+        let collator = icu::collator::Collator::try_new(&locale.into(), collator_options)
+            .or_else(|_| icu::collator::Collator::try_new(&Default::default(), collator_options))
+            .expect("Root collation data is missing.");
+        Collator { m_collator: collator }
+    }
+
+    /// Compares two strings.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.m_collator.compare(a, b)
+    }
+
+    /// Sorts a slice of strings. The sort is stable.
+    ///
+    /// Sort keys are not available from the underlying ICU collator,
+    /// so each comparison collates both strings.
+    pub fn sort<S: AsRef<str>>(&self, items: &mut [S]) {
+        items.sort_by(|a, b| self.compare(a.as_ref(), b.as_ref()));
+    }
+}
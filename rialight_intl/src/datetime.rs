@@ -0,0 +1,120 @@
+//! Locale-aware date and time formatting.
+//!
+//! Timestamps are formatted in UTC.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, datetime::{self, DateTimeStyle}};
+//! use std::time::{Duration, SystemTime};
+//!
+//! let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(1_710_000_000);
+//! assert_eq!(datetime::format_date(&locale!("en-US"), ts, DateTimeStyle::Short), "3/9/24");
+//! assert_eq!(datetime::format_date(&locale!("de-DE"), ts, DateTimeStyle::Medium), "09.03.2024");
+//! ```
+
+use crate::locale::Locale;
+use icu::calendar::{DateTime, Iso};
+use icu::datetime::{ZonedDateTimeFormatter, options::length};
+use icu::timezone::CustomTimeZone;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Length of a formatted date or time.
+///
+/// Examples for `en-US`:
+///
+/// | Style    | Date                    | Time              |
+/// |----------|-------------------------|-------------------|
+/// | `Short`  | 3/9/24                  | 4:00 PM           |
+/// | `Medium` | Mar 9, 2024             | 4:00:00 PM        |
+/// | `Long`   | March 9, 2024           | 4:00:00 PM GMT    |
+/// | `Full`   | Saturday, March 9, 2024 | 4:00:00 PM GMT    |
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DateTimeStyle {
+    Short,
+    Medium,
+    Long,
+    Full,
+}
+
+impl DateTimeStyle {
+    fn date_length(self) -> length::Date {
+        match self {
+            DateTimeStyle::Short => length::Date::Short,
+            DateTimeStyle::Medium => length::Date::Medium,
+            DateTimeStyle::Long => length::Date::Long,
+            DateTimeStyle::Full => length::Date::Full,
+        }
+    }
+
+    fn time_length(self) -> length::Time {
+        match self {
+            DateTimeStyle::Short => length::Time::Short,
+            DateTimeStyle::Medium => length::Time::Medium,
+            DateTimeStyle::Long => length::Time::Long,
+            DateTimeStyle::Full => length::Time::Full,
+        }
+    }
+}
+
+/// Formats the date and time of a timestamp according to the rules
+/// of the given locale.
+pub fn format(locale: &Locale, ts: SystemTime, style: DateTimeStyle) -> String {
+    format_with_bag(locale, ts, length::Bag::from_date_time_style(style.date_length(), style.time_length()))
+}
+
+/// Formats the date and time of a timestamp according to the rules
+/// of the given locale, with different lengths for the date and the time.
+pub fn format_with_styles(locale: &Locale, ts: SystemTime, date_style: DateTimeStyle, time_style: DateTimeStyle) -> String {
+    format_with_bag(locale, ts, length::Bag::from_date_time_style(date_style.date_length(), time_style.time_length()))
+}
+
+/// Formats the date of a timestamp according to the rules of the given locale.
+pub fn format_date(locale: &Locale, ts: SystemTime, style: DateTimeStyle) -> String {
+    format_with_bag(locale, ts, length::Bag::from_date_style(style.date_length()))
+}
+
+/// Formats the time of a timestamp according to the rules of the given locale.
+pub fn format_time(locale: &Locale, ts: SystemTime, style: DateTimeStyle) -> String {
+    format_with_bag(locale, ts, length::Bag::from_time_style(style.time_length()))
+}
+
+fn format_with_bag(locale: &Locale, ts: SystemTime, bag: length::Bag) -> String {
+    let datetime = to_iso_datetime(ts);
+    ZonedDateTimeFormatter::try_new(&locale.into(), bag.into(), Default::default())
+        .ok()
+        .and_then(|formatter| formatter.format_to_string(&datetime.to_any(), &CustomTimeZone::utc()).ok())
+        .unwrap_or_else(|| {
+            // RFC 3339 when the locale data is unavailable.
+            format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                datetime.date.year().number, datetime.date.month().ordinal, datetime.date.day_of_month().0,
+                datetime.time.hour.number(), datetime.time.minute.number(), datetime.time.second.number())
+        })
+}
+
+/// Converts a timestamp to an ISO date and time in UTC,
+/// truncated to seconds.
+fn to_iso_datetime(ts: SystemTime) -> DateTime<Iso> {
+    let seconds = match ts.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+    };
+    let days = seconds.div_euclid(86_400);
+    let seconds_of_day = seconds.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01, proleptic Gregorian.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    DateTime::try_new_iso_datetime(
+        year as i32, month, day,
+        (seconds_of_day / 3600) as u8, (seconds_of_day / 60 % 60) as u8, (seconds_of_day % 60) as u8,
+    ).unwrap()
+}
@@ -0,0 +1,34 @@
+//! Localized names of locales.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, display_names};
+//!
+//! assert_eq!(display_names::display_name(&locale!("ja")).as_deref(), Some("日本語"));
+//! assert_eq!(display_names::display_name_in(&locale!("fr"), &locale!("en")).as_deref(), Some("French"));
+//! ```
+
+use crate::locale::Locale;
+use icu::experimental::displaynames::{DisplayNamesOptions, LanguageDisplayNames, LocaleDisplayNamesFormatter};
+
+/// Returns the name of a locale in its own language, such as `français`
+/// for `fr` or `日本語` for `ja`, as given by CLDR. Names are not
+/// capitalized beyond what CLDR does.
+///
+/// Returns `None` if the locale data has no name for the locale's language.
+pub fn display_name(locale: &Locale) -> Option<String> {
+    display_name_in(locale, locale)
+}
+
+/// Returns the name of a locale in another locale's language, such as
+/// `French` for `fr` in `en`.
+///
+/// Returns `None` if the locale data has no name for the locale's language.
+pub fn display_name_in(locale: &Locale, in_locale: &Locale) -> Option<String> {
+    let in_locale = &in_locale.into();
+    let languages = LanguageDisplayNames::try_new(in_locale, DisplayNamesOptions::default()).ok()?;
+    languages.of(locale.id.language)?;
+    let formatter = LocaleDisplayNamesFormatter::try_new(in_locale, DisplayNamesOptions::default()).ok()?;
+    Some(formatter.of(locale).into_owned())
+}
@@ -3,14 +3,24 @@
 //! # FTL Syntax
 //!
 //! [See the FTL syntax guide.](https://projectfluent.org/fluent/guide/)
+//!
+//! Braces are written as string literals inside placeables, as in
+//! `json = {"{"} "key": 1 {"}"}`, which resolves to `{ "key": 1 }`.
+//! Arguments are never parsed as FTL, so a string argument containing
+//! braces, such as one created with `FtlValue::literal()`, is displayed
+//! as is.
 
 pub use fluent::FluentArgs as Arguments;
+pub use icu::plurals::PluralCategory;
 
-use icu::locid::Locale;
+use icu::locid::{LanguageIdentifier, Locale};
 use std::{
-    cell::{Cell}, collections::{HashMap, HashSet}, sync::{Arc, RwLock},
+    borrow::Cow, cell::{Cell, RefCell}, collections::{BTreeMap, BTreeSet, HashMap, HashSet}, fmt, future::Future, io::{Cursor, Read}, pin::Pin, str::FromStr,
+    sync::{Arc, LazyLock, Mutex, RwLock, Weak, atomic::{AtomicBool, AtomicU64, Ordering}},
+    time::{Duration, SystemTime},
 };
 use rialight_util::{hashmap, hashset};
+use crate::{HasTextDirection, TextDirection, collator::{Collator, CollatorOptions}, datetime::DateTimeStyle, display_names, list::ListStyle, number::{CurrencyFormatOptions, NumberFormatOptions, Unit, UnitStyle}, relative_time::RelativeTimeUnit};
 
 /// Creates an `Arguments` object from a list of key-value pairs.
 ///
@@ -30,7 +40,7 @@ pub macro arguments {
             #[allow(unused_mut)]
             let mut r_map = ::fluent::FluentArgs::new();
             $(
-                let _ = r_map.set($key.to_string(), Box::new($value));
+                let _ = r_map.set($key.to_string(), $value);
             )*
             r_map
         }
@@ -40,292 +50,3225 @@ pub macro arguments {
             #[allow(unused_mut)]
             let mut r_map = ::fluent::FluentArgs::new();
             $(
-                let _ = r_map.set($key.to_string(), Box::new($value));
+                let _ = r_map.set($key.to_string(), $value);
             )*
             r_map
         }
     }
 }
 
-/// Interface for working with Fluent Translation Lists.
-pub struct Ftl {
-    m_current_locale: RwLock<Option<Locale>>,
-    /// Maps a Locale object to its equivalent path component.
-    /// The string to which the Locale maps depends in how the
-    /// Ftl object was constructed. If the `supported_locales` option
-    /// contains "en-us", then `m_locale_to_path_components.get(&locale!("en-US"))` returns "en-us".
-    /// When FTLs are loaded, this component is appended to the URL or file path;
-    /// for example, `"res/lang/en-us"`.
-    m_locale_to_path_components: Arc<HashMap<Locale, String>>,
-    m_supported_locales: Arc<HashSet<Locale>>,
-    m_default_locale: Locale,
-    m_fallbacks: Arc<HashMap<Locale, Vec<Locale>>>,
-    m_locale_initializers: Arc<RwLock<Vec<fn(Locale, Arc<fluent::FluentBundle<fluent::FluentResource>>)>>>,
-    m_assets: Arc<RwLock<HashMap<Locale, Arc<fluent::FluentBundle<fluent::FluentResource>>>>>,
-    m_assets_source: String,
-    m_assets_files: Vec<String>,
-    m_assets_clean_unused: bool,
-    m_assets_load_method: FtlLoadMethod,
+/// A value given as a message argument.
+///
+/// When used directly in text, strings are displayed as is, numbers are
+/// formatted according to the locale of the message, booleans are displayed
+/// as `true` or `false`, and dates are formatted in UTC in the short style,
+/// as `DATETIME()` does without options. Select expressions match booleans
+/// against the `[true]` and `[false]` variants.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FtlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    DateTime(SystemTime),
 }
 
-fn parse_locale_or_panic(s: &str) -> Locale {
-    Locale::try_from_bytes(s.as_bytes()).expect((format!("{} is a malformed locale.", s)).as_ref())
+impl FtlValue {
+    /// Creates a string argument that is displayed as is, including any
+    /// braces, such as JSON-like text.
+    pub fn literal(s: impl Into<String>) -> Self {
+        FtlValue::String(s.into())
+    }
+
+    fn to_fluent_value(&self) -> fluent::FluentValue<'_> {
+        match self {
+            FtlValue::String(s) => fluent::FluentValue::from(s.as_str()),
+            FtlValue::Integer(n) => fluent::FluentValue::from(*n),
+            FtlValue::Float(n) => fluent::FluentValue::from(*n),
+            FtlValue::Bool(b) => fluent::FluentValue::from(if *b { "true" } else { "false" }),
+            FtlValue::DateTime(ts) => fluent::FluentValue::Custom(Box::new(FluentDateTime(*ts))),
+        }
+    }
+
+    fn into_fluent_value(self) -> fluent::FluentValue<'static> {
+        match self {
+            FtlValue::String(s) => fluent::FluentValue::from(s),
+            FtlValue::Integer(n) => fluent::FluentValue::from(n),
+            FtlValue::Float(n) => fluent::FluentValue::from(n),
+            FtlValue::Bool(b) => fluent::FluentValue::from(if b { "true" } else { "false" }),
+            FtlValue::DateTime(ts) => fluent::FluentValue::Custom(Box::new(FluentDateTime(ts))),
+        }
+    }
+
+    /// Converts a resolved Fluent value. Returns `None` for errors
+    /// and values with no `FtlValue` equivalent.
+    fn from_fluent_value(value: &fluent::FluentValue) -> Option<Self> {
+        match value {
+            fluent::FluentValue::String(s) => Some(FtlValue::String(s.to_string())),
+            fluent::FluentValue::Number(n) => {
+                let integral = n.value.fract() == 0.0 && n.value.abs() < i64::MAX as f64
+                    && n.options.minimum_fraction_digits.unwrap_or(0) == 0;
+                Some(if integral { FtlValue::Integer(n.value as i64) } else { FtlValue::Float(n.value) })
+            },
+            fluent::FluentValue::Custom(custom) => custom.as_any().downcast_ref::<FluentDateTime>().map(|ts| FtlValue::DateTime(ts.0)),
+            _ => None,
+        }
+    }
 }
 
-fn locale_to_unic_langid_impl_langid(locale: &Locale) -> unic_langid_impl::LanguageIdentifier {
-    unic_langid_impl::LanguageIdentifier::from_bytes(locale.id.to_string().as_bytes()).unwrap()
+impl From<&str> for FtlValue {
+    fn from(value: &str) -> Self {
+        FtlValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for FtlValue {
+    fn from(value: String) -> Self {
+        FtlValue::String(value)
+    }
+}
+
+macro_rules! ftl_value_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for FtlValue {
+                fn from(value: $t) -> Self {
+                    FtlValue::Integer(value as i64)
+                }
+            }
+        )*
+    };
+}
+
+ftl_value_from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, usize);
+
+impl From<f32> for FtlValue {
+    fn from(value: f32) -> Self {
+        FtlValue::Float(value as f64)
+    }
+}
+
+impl From<f64> for FtlValue {
+    fn from(value: f64) -> Self {
+        FtlValue::Float(value)
+    }
+}
+
+impl From<bool> for FtlValue {
+    fn from(value: bool) -> Self {
+        FtlValue::Bool(value)
+    }
+}
+
+impl From<SystemTime> for FtlValue {
+    fn from(value: SystemTime) -> Self {
+        FtlValue::DateTime(value)
+    }
+}
+
+/// Date argument, formatted according to the locale of the message.
+#[derive(Debug, PartialEq)]
+struct FluentDateTime(SystemTime);
+
+impl fluent::types::FluentType for FluentDateTime {
+    fn duplicate(&self) -> Box<dyn fluent::types::FluentType + Send> {
+        Box::new(FluentDateTime(self.0))
+    }
+
+    fn as_string(&self, intls: &intl_memoizer::IntlLangMemoizer) -> Cow<'static, str> {
+        intls.with_try_get::<MemoizedLocale, _, _>((), |locale| crate::datetime::format(&locale.0, self.0, DateTimeStyle::Short))
+            .unwrap_or_default().into()
+    }
+
+    fn as_string_threadsafe(&self, intls: &intl_memoizer::concurrent::IntlLangMemoizer) -> Cow<'static, str> {
+        intls.with_try_get::<MemoizedLocale, _, _>((), |locale| crate::datetime::format(&locale.0, self.0, DateTimeStyle::Short))
+            .unwrap_or_default().into()
+    }
+}
+
+/// Locale of a `FluentBundle`, cached by the bundle's memoizer.
+struct MemoizedLocale(Locale);
+
+impl intl_memoizer::Memoizable for MemoizedLocale {
+    type Args = ();
+    type Error = ();
+
+    fn construct(lang: unic_langid_impl::LanguageIdentifier, _: ()) -> Result<Self, ()> {
+        Locale::try_from_bytes(lang.to_string().as_bytes()).map(MemoizedLocale).map_err(|_| ())
+    }
+}
+
+/// Formats a date given to the built-in `DATETIME` function, with
+/// the `dateStyle` and `timeStyle` options, whose values are `short`,
+/// `medium`, `long` or `full`, as in `DATETIME($ts, dateStyle: "long")`.
+/// Without options, the date and time are in the short style.
+fn format_datetime_function(locale: &Locale, positional: &[fluent::FluentValue], named: &Arguments) -> fluent::FluentValue<'static> {
+    let Some(FtlValue::DateTime(ts)) = positional.first().and_then(FtlValue::from_fluent_value) else {
+        return fluent::FluentValue::Error;
+    };
+    let style = |name: &str| match named.get(name) {
+        Some(fluent::FluentValue::String(s)) => match s.as_ref() {
+            "short" => Some(DateTimeStyle::Short),
+            "medium" => Some(DateTimeStyle::Medium),
+            "long" => Some(DateTimeStyle::Long),
+            "full" => Some(DateTimeStyle::Full),
+            _ => None,
+        },
+        _ => None,
+    };
+    fluent::FluentValue::from(match (style("dateStyle"), style("timeStyle")) {
+        (Some(date_style), Some(time_style)) => crate::datetime::format_with_styles(locale, ts, date_style, time_style),
+        (Some(date_style), None) => crate::datetime::format_date(locale, ts, date_style),
+        (None, Some(time_style)) => crate::datetime::format_time(locale, ts, time_style),
+        (None, None) => crate::datetime::format(locale, ts, DateTimeStyle::Short),
+    })
+}
+
+/// Named arguments given to `Ftl::format()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FtlArgs {
+    m_args: Vec<(String, FtlValue)>,
+}
+
+impl FtlArgs {
+    pub fn new() -> Self {
+        Self { m_args: vec![] }
+    }
+
+    /// Sets an argument, replacing any previous argument of the same name.
+    pub fn set(&mut self, name: impl AsRef<str>, value: impl Into<FtlValue>) -> &mut Self {
+        let name = name.as_ref();
+        let value = value.into();
+        if let Some(arg) = self.m_args.iter_mut().find(|(k, _)| k == name) {
+            arg.1 = value;
+        } else {
+            self.m_args.push((name.to_owned(), value));
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FtlValue> {
+        self.m_args.iter().find(|(k, _)| k == name).map(|(_, v)| v)
+    }
+
+    fn from_fluent_args(args: &Arguments) -> Self {
+        let mut r = FtlArgs::new();
+        for (k, v) in args.iter() {
+            if let Some(v) = FtlValue::from_fluent_value(v) {
+                r.set(k, v);
+            }
+        }
+        r
+    }
+
+    fn to_fluent_args(&self) -> Arguments<'_> {
+        let mut r = Arguments::with_capacity(self.m_args.len());
+        for (k, v) in self.m_args.iter() {
+            r.set(k.as_str(), v.to_fluent_value());
+        }
+        r
+    }
+}
+
+/// Creates an `FtlArgs` object from a list of key-value pairs.
+///
+/// ## Example
+///
+/// ```
+/// use rialight::intl;
+///
+/// let a = intl::ftl::ftl_args!{
+///     "name" => "Ada",
+///     "count" => 3,
+/// };
+/// ```
+pub macro ftl_args {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        {
+            #[allow(unused_mut)]
+            let mut r_args = $crate::ftl::FtlArgs::new();
+            $(
+                r_args.set($key, $value);
+            )*
+            r_args
+        }
+    }
+}
+
+/// Decimal formatter for the locale of a `FluentBundle`, cached
+/// by the bundle's memoizer.
+struct DecimalFormatter(icu::decimal::FixedDecimalFormatter);
+
+impl intl_memoizer::Memoizable for DecimalFormatter {
+    type Args = ();
+    type Error = ();
+
+    fn construct(lang: unic_langid_impl::LanguageIdentifier, _: ()) -> Result<Self, ()> {
+        let locale = Locale::try_from_bytes(lang.to_string().as_bytes()).map_err(|_| ())?;
+        let formatter = icu::decimal::FixedDecimalFormatter::try_new(&(&locale).into(), Default::default()).map_err(|_| ())?;
+        Ok(DecimalFormatter(formatter))
+    }
+}
+
+/// Formats numeric placeables according to the locale's number rules.
+fn format_fluent_value(value: &fluent::FluentValue, intls: &intl_memoizer::concurrent::IntlLangMemoizer) -> Option<String> {
+    match value {
+        fluent::FluentValue::Number(n) => {
+            let decimal = fixed_decimal::FixedDecimal::from_str(&n.as_string()).ok()?;
+            intls.with_try_get::<DecimalFormatter, _, _>((), |f| f.0.format_to_string(&decimal)).ok()
+        },
+        _ => None,
+    }
+}
+
+fn plural_category_keyword(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::Zero => "zero",
+        PluralCategory::One => "one",
+        PluralCategory::Two => "two",
+        PluralCategory::Few => "few",
+        PluralCategory::Many => "many",
+        PluralCategory::Other => "other",
+    }
+}
+
+/// Converts a Fluent number, or a string containing a number, to a decimal.
+fn fluent_value_to_decimal(value: &fluent::FluentValue) -> Option<fixed_decimal::FixedDecimal> {
+    match value {
+        fluent::FluentValue::Number(n) => fixed_decimal::FixedDecimal::from_str(&n.as_string()).ok(),
+        fluent::FluentValue::String(s) => fixed_decimal::FixedDecimal::from_str(s.trim()).ok(),
+        _ => None,
+    }
+}
+
+fn cardinal_category(locale: &Locale, n: &fixed_decimal::FixedDecimal) -> PluralCategory {
+    icu::plurals::PluralRules::try_new_cardinal(&locale.into())
+        .map(|rules| rules.category_for(n))
+        .unwrap_or(PluralCategory::Other)
+}
+
+fn ordinal_category(locale: &Locale, n: &fixed_decimal::FixedDecimal) -> PluralCategory {
+    icu::plurals::PluralRules::try_new_ordinal(&locale.into())
+        .map(|rules| rules.category_for(n))
+        .unwrap_or(PluralCategory::Other)
+}
+
+/// Error returned when formatting a message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FtlError {
+    /// No locale has been loaded.
+    NoLocaleLoaded,
+    /// The locale is not one of the supported locales.
+    UnsupportedLocale(String),
+    /// The message is not defined by the current locale
+    /// nor by its fallbacks, or it has no value.
+    MessageNotFound(String),
+    /// The message references a variable that was not given.
+    MissingVariable {
+        id: String,
+        name: String,
+    },
+    /// The message was found, but errors occurred while formatting it.
+    Format {
+        id: String,
+        errors: Vec<String>,
+    },
+    /// A resource of a locale could not be read.
+    ResourceNotFound {
+        locale: String,
+        path: String,
+    },
+    /// A resource of a locale defines messages or terms that are already
+    /// defined and the `strict_duplicates` option is enabled.
+    InvalidResource {
+        locale: String,
+        path: String,
+    },
+    /// A resource of a locale could not be fetched through HTTP,
+    /// after any retries.
+    Http {
+        url: String,
+        locale: String,
+        message: String,
+    },
+    /// The zip archive of the assets, or one of its entries, could not
+    /// be read, such as when it is corrupt.
+    ZipArchive {
+        locale: String,
+        path: String,
+        message: String,
+    },
+    /// A custom function has the name of a built-in function.
+    ReservedFunction(String),
+    /// Resources have syntax errors and the `strict` option is enabled.
+    Parse(Vec<FtlParseError>),
+    /// The operation is not supported by the load method of the assets.
+    UnsupportedLoadMethod,
+    /// The locale is the current locale, one of its fallbacks or the
+    /// default locale, and cannot be unloaded.
+    LocaleInUse(String),
+}
+
+impl fmt::Display for FtlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FtlError::NoLocaleLoaded => write!(f, "no locale has been loaded"),
+            FtlError::UnsupportedLocale(locale) => write!(f, "unsupported locale: {}", locale),
+            FtlError::MessageNotFound(id) => write!(f, "message `{}` not found", id),
+            FtlError::MissingVariable { id, name } => write!(f, "message `{}` references missing variable `${}`", id, name),
+            FtlError::Format { id, errors } => write!(f, "failed to format message `{}`: {}", id, errors.join("; ")),
+            FtlError::ResourceNotFound { locale, path } => write!(f, "failed to load resource at {} for locale {}", path, locale),
+            FtlError::InvalidResource { locale, path } => write!(f, "resource at {} for locale {} has errors", path, locale),
+            FtlError::Http { url, locale, message } => write!(f, "failed to fetch {} for locale {}: {}", url, locale, message),
+            FtlError::ZipArchive { locale, path, message } => write!(f, "failed to read zip archive {} for locale {}: {}", path, locale, message),
+            FtlError::ReservedFunction(name) => write!(f, "function {} is built-in", name),
+            FtlError::UnsupportedLoadMethod => write!(f, "operation not supported by the load method"),
+            FtlError::LocaleInUse(locale) => write!(f, "locale {} is in use", locale),
+            FtlError::Parse(errors) => write!(f, "syntax errors: {}", errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for FtlError {}
+
+/// A syntax error in a resource, found while loading a locale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FtlParseError {
+    /// The locale, in the form in which it appears in the `supported_locales` option.
+    pub locale: String,
+    /// Path of the resource, such as `res/lang/en/common.ftl`.
+    pub file: String,
+    /// Line of the error, starting from 1.
+    pub line: usize,
+    /// Column of the error, in characters, starting from 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl FtlParseError {
+    fn new(locale: &str, file: &str, source: &str, error: &fluent_syntax::parser::ParserError) -> Self {
+        let mut offset = error.pos.start.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self {
+            locale: locale.to_owned(),
+            file: file.to_owned(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: error.kind.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FtlParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.message)
+    }
+}
+
+/// A part of a message formatted by `Ftl::format_segments()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FtlSegment {
+    /// Literal text of the message.
+    Text(String),
+    /// The formatted value of a placeable, such as `{ $key }`, without
+    /// isolation marks. `variable` is the name of the variable if the
+    /// placeable is a variable reference, such as `key` in `{ $key }`.
+    Placeable {
+        value: String,
+        variable: Option<String>,
+    },
+}
+
+/// A message formatted by `Ftl::format_shared()`. Dereferences to `str`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FtlText {
+    /// The value of a plain-text message, shared with the loaded locale,
+    /// so that it is not allocated on every call.
+    Shared(Arc<str>),
+    /// A formatted message.
+    Owned(String),
+}
+
+impl std::ops::Deref for FtlText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            FtlText::Shared(text) => text,
+            FtlText::Owned(text) => text,
+        }
+    }
+}
+
+impl fmt::Display for FtlText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl PartialEq<str> for FtlText {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl PartialEq<&str> for FtlText {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+impl PartialEq<String> for FtlText {
+    fn eq(&self, other: &String) -> bool {
+        **self == **other
+    }
+}
+
+impl From<FtlText> for String {
+    fn from(text: FtlText) -> String {
+        match text {
+            FtlText::Shared(text) => text.to_string(),
+            FtlText::Owned(text) => text,
+        }
+    }
+}
+
+/// An inconsistency between the translations of a message in the loaded
+/// locales, given by `Ftl::validate()`. Variables and select arms are
+/// compared against a reference locale: the default locale, or the first
+/// locale, in alphabetical order, that defines the message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FtlValidationIssue {
+    /// A message defined by another loaded locale is not defined by the locale.
+    MissingMessage {
+        id: String,
+        locale: String,
+    },
+    /// The message references different variables in the locale
+    /// than in the reference locale.
+    MismatchedVariables {
+        id: String,
+        locale: String,
+        reference_locale: String,
+        variables: Vec<String>,
+        reference_variables: Vec<String>,
+    },
+    /// The select expressions of the message have different arms in the
+    /// locale than in the reference locale. Plural arms, which depend
+    /// on the locale, are not compared.
+    MismatchedSelectArms {
+        id: String,
+        locale: String,
+        reference_locale: String,
+        arms: Vec<String>,
+        reference_arms: Vec<String>,
+    },
+}
+
+impl fmt::Display for FtlValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FtlValidationIssue::MissingMessage { id, locale } => write!(f, "message {} is missing in locale {}", id, locale),
+            FtlValidationIssue::MismatchedVariables { id, locale, reference_locale, variables, reference_variables } =>
+                write!(f, "message {} references [{}] in locale {}, but [{}] in locale {}", id, variables.join(", "), locale, reference_variables.join(", "), reference_locale),
+            FtlValidationIssue::MismatchedSelectArms { id, locale, reference_locale, arms, reference_arms } =>
+                write!(f, "message {} selects [{}] in locale {}, but [{}] in locale {}", id, arms.join(", "), locale, reference_arms.join(", "), reference_locale),
+        }
+    }
+}
+
+/// Variables and non-plural select arms of a message.
+#[derive(Default)]
+struct MessageSignature {
+    m_variables: BTreeSet<String>,
+    m_arms: BTreeSet<String>,
+}
+
+impl MessageSignature {
+    fn add_pattern(&mut self, pattern: &fluent_syntax::ast::Pattern<&str>) {
+        for element in pattern.elements.iter() {
+            if let fluent_syntax::ast::PatternElement::Placeable { expression } = element {
+                self.add_expression(expression);
+            }
+        }
+    }
+
+    fn add_expression(&mut self, expression: &fluent_syntax::ast::Expression<&str>) {
+        match expression {
+            fluent_syntax::ast::Expression::Select { selector, variants } => {
+                self.add_inline_expression(selector);
+                let keys: Vec<&str> = variants.iter().map(|variant| match &variant.key {
+                    fluent_syntax::ast::VariantKey::Identifier { name } => *name,
+                    fluent_syntax::ast::VariantKey::NumberLiteral { value } => *value,
+                }).filter(|key| *key != MISSING_DEFAULT_KEY).collect();
+                let plural = keys.iter().all(|key| ["zero", "one", "two", "few", "many", "other"].contains(key) || f64::from_str(key).is_ok());
+                if !plural {
+                    self.m_arms.extend(keys.iter().map(|key| key.to_string()));
+                }
+                for variant in variants.iter() {
+                    self.add_pattern(&variant.value);
+                }
+            },
+            fluent_syntax::ast::Expression::Inline(expression) => self.add_inline_expression(expression),
+        }
+    }
+
+    fn add_inline_expression(&mut self, expression: &fluent_syntax::ast::InlineExpression<&str>) {
+        match expression {
+            fluent_syntax::ast::InlineExpression::VariableReference { id } => {
+                self.m_variables.insert(id.name.to_owned());
+            },
+            fluent_syntax::ast::InlineExpression::FunctionReference { arguments, .. } => self.add_arguments(arguments),
+            fluent_syntax::ast::InlineExpression::TermReference { arguments: Some(arguments), .. } => self.add_arguments(arguments),
+            fluent_syntax::ast::InlineExpression::Placeable { expression } => self.add_expression(expression),
+            _ => {},
+        }
+    }
+
+    fn add_arguments(&mut self, arguments: &fluent_syntax::ast::CallArguments<&str>) {
+        for expression in arguments.positional.iter() {
+            self.add_inline_expression(expression);
+        }
+        for argument in arguments.named.iter() {
+            self.add_inline_expression(&argument.value);
+        }
+    }
+}
+
+type FtlFunction = dyn Fn(&[FtlValue], &FtlArgs) -> FtlValue + Send + Sync;
+
+/// Functions that messages can call, other than the custom ones.
+const BUILTIN_FUNCTIONS: [&str; 3] = ["NUMBER", "DATETIME", "ORDINAL"];
+
+/// Key of the default variant added to select expressions that have none.
+const MISSING_DEFAULT_KEY: &str = "rialight-missing-default";
+
+/// Adds a default variant to each select expression without one, which
+/// the parser rejects along with its message. The added variant is a copy
+/// of the first variant under the key `MISSING_DEFAULT_KEY`, so that
+/// formatting can tell when no other variant matches.
+fn add_missing_default_variants(source: &str) -> String {
+    // Braces and brackets are matched outside of string literals and
+    // comments, which are blanked out at the same positions.
+    let masked = mask_literals_and_comments(source);
+    let bytes = masked.as_bytes();
+    // Open placeables, as whether they are select expressions, whether
+    // they have a default variant, and the positions where their first
+    // and second variants start.
+    let mut placeables: Vec<(bool, bool, Option<usize>, Option<usize>)> = vec![];
+    // Default variants to insert, by position, in ascending order.
+    let mut insertions: Vec<(usize, String)> = vec![];
+    let mut line_start = false;
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'{' => placeables.push((false, false, None, None)),
+            b'}' => if let Some((true, false, Some(first), second)) = placeables.pop() {
+                let variant = missing_default_variant(source, &masked, &insertions, first, second.unwrap_or(i), i);
+                insertions.push((i, variant));
+            },
+            b'>' if i > 0 && bytes[i - 1] == b'-' => if let Some(placeable) = placeables.last_mut() {
+                placeable.0 = true;
+            },
+            // Variants start on their own lines.
+            b'[' | b'*' if line_start => if let Some(placeable) = placeables.last_mut().filter(|placeable| placeable.0) {
+                placeable.1 |= b == b'*';
+                if placeable.2.is_none() {
+                    placeable.2 = Some(i);
+                } else if placeable.3.is_none() {
+                    placeable.3 = Some(i);
+                }
+            },
+            _ => {},
+        }
+        line_start = match b {
+            b'\n' => true,
+            b' ' | b'\t' | b'\r' => line_start,
+            _ => false,
+        };
+    }
+    let mut source = source.to_owned();
+    for (position, variant) in insertions.iter().rev() {
+        source.insert_str(*position, variant);
+    }
+    source
+}
+
+/// Returns the default variant to insert at the closing brace of a select
+/// expression without one, as a copy of its first variant, which starts at
+/// `first` and ends at `end`. The copy includes the default variants added
+/// to the select expressions nested in the first variant.
+fn missing_default_variant(source: &str, masked: &str, insertions: &[(usize, String)], first: usize, end: usize, close: usize) -> String {
+    let key_end = masked[first..end].find(']').map_or(end, |i| first + i + 1);
+    let mut pattern = source[key_end..end].trim_end().to_owned();
+    for (position, variant) in insertions.iter().rev().filter(|(position, _)| (key_end..end).contains(position)) {
+        pattern.insert_str(position - key_end, variant);
+    }
+    let line = &source[source[..close].rfind('\n').map_or(0, |i| i + 1)..close];
+    let (prefix, indent) = if line.trim().is_empty() { ("", line) } else { ("\n ", " ") };
+    format!("{prefix}*[{MISSING_DEFAULT_KEY}]{pattern}\n{indent}")
+}
+
+/// Replaces the string literals and comments of a source with spaces,
+/// keeping line breaks. Quotes are only string delimiters inside
+/// placeables, outside of the variants of select expressions.
+fn mask_literals_and_comments(source: &str) -> String {
+    // Whether each open placeable is in its variants.
+    let mut placeables: Vec<bool> = vec![];
+    let (mut literal, mut escape, mut comment, mut line_start) = (false, false, false, true);
+    let mut masked = Vec::with_capacity(source.len());
+    let bytes = source.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        let mask = if literal {
+            match b {
+                _ if escape => escape = false,
+                b'\\' => escape = true,
+                b'"' | b'\n' => literal = false,
+                _ => {},
+            }
+            b != b'\n'
+        } else if comment {
+            comment = b != b'\n';
+            comment
+        } else {
+            match b {
+                b'#' if line_start && placeables.is_empty() => comment = true,
+                b'"' if placeables.last() == Some(&false) => literal = true,
+                b'>' if placeables.last() == Some(&false) && i > 0 && bytes[i - 1] == b'-' => *placeables.last_mut().unwrap() = true,
+                b'{' => placeables.push(false),
+                b'}' => { placeables.pop(); },
+                _ => {},
+            }
+            comment || literal
+        };
+        line_start = b == b'\n';
+        masked.push(if mask { b' ' } else { b });
+    }
+    // Only whole characters are masked.
+    String::from_utf8(masked).unwrap()
+}
+
+/// Value of the selector of a select expression. A number has its
+/// value, its decimal and whether it selects ordinal categories.
+enum SelectorValue {
+    Number(f64, fixed_decimal::FixedDecimal, bool),
+    String(String),
+}
+
+impl SelectorValue {
+    /// Returns whether the value matches a variant key, as the resolver
+    /// matches it, but with the ICU plural rules of the locale.
+    fn matches(&self, locale: &Locale, key: &fluent_syntax::ast::VariantKey<&str>) -> bool {
+        match (self, key) {
+            (SelectorValue::Number(n, _, _), fluent_syntax::ast::VariantKey::NumberLiteral { value }) => f64::from_str(value).is_ok_and(|value| value == *n),
+            (SelectorValue::Number(_, n, ordinal), fluent_syntax::ast::VariantKey::Identifier { name }) => {
+                let category = if *ordinal { ordinal_category(locale, n) } else { cardinal_category(locale, n) };
+                *name == plural_category_keyword(category)
+            },
+            (SelectorValue::String(s), fluent_syntax::ast::VariantKey::Identifier { name }) => s == name,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SelectorValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelectorValue::Number(_, n, _) => write!(f, "{}", n),
+            SelectorValue::String(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Evaluates the selector of a select expression. Returns `None` if
+/// it cannot be evaluated, such as for a missing variable, leaving
+/// the select expression to the resolver.
+fn selector_value(assets: &FtlBundle, selector: &fluent_syntax::ast::InlineExpression<&str>, args: Option<&Arguments>) -> Option<SelectorValue> {
+    let number = |value: &fluent::FluentValue, ordinal: bool| match value {
+        fluent::FluentValue::Number(n) => Some(SelectorValue::Number(n.value, fluent_value_to_decimal(value)?, ordinal)),
+        _ => None,
+    };
+    match selector {
+        fluent_syntax::ast::InlineExpression::VariableReference { id } => return match args?.get(id.name)? {
+            fluent::FluentValue::String(s) => Some(SelectorValue::String(s.to_string())),
+            value => number(value, false),
+        },
+        fluent_syntax::ast::InlineExpression::NumberLiteral { value } => return number(&fluent::FluentValue::try_number(value), false),
+        // `NUMBER($n, type: "ordinal")` selects ordinal categories.
+        fluent_syntax::ast::InlineExpression::FunctionReference { id, arguments } if id.name == "NUMBER" => {
+            let ordinal = arguments.named.iter().any(|argument| argument.name.name == "type"
+                && matches!(argument.value, fluent_syntax::ast::InlineExpression::StringLiteral { value: "ordinal" }));
+            if let Some(fluent_syntax::ast::InlineExpression::VariableReference { id }) = arguments.positional.first() {
+                return args?.get(id.name).and_then(|value| number(value, ordinal));
+            }
+        },
+        _ => {},
+    }
+    // Other selectors, such as messages and functions, match as strings.
+    let pattern = fluent_syntax::ast::Pattern {
+        elements: vec![fluent_syntax::ast::PatternElement::Placeable {
+            expression: fluent_syntax::ast::Expression::Inline(selector.clone()),
+        }],
+    };
+    let mut errors = vec![];
+    let value = assets.format_pattern(&pattern, args, &mut errors).into_owned();
+    errors.is_empty().then_some(SelectorValue::String(value))
+}
+
+/// Chooses the variant of each select expression that formatting a
+/// pattern of the given locale reaches, leaving it as the only variant,
+/// so that the added default variants are detected at resolve time.
+/// The selector values that only match an added default variant are
+/// pushed to `unmatched`.
+fn resolve_selects(assets: &FtlBundle, locale: &Locale, pattern: &mut fluent_syntax::ast::Pattern<&str>, args: Option<&Arguments>, unmatched: &mut Vec<String>) {
+    for element in pattern.elements.iter_mut() {
+        if let fluent_syntax::ast::PatternElement::Placeable { expression } = element {
+            resolve_select(assets, locale, expression, args, unmatched);
+        }
+    }
+}
+
+fn resolve_select(assets: &FtlBundle, locale: &Locale, expression: &mut fluent_syntax::ast::Expression<&str>, args: Option<&Arguments>, unmatched: &mut Vec<String>) {
+    let (selector, variants) = match expression {
+        fluent_syntax::ast::Expression::Select { selector, variants } => (selector, variants),
+        fluent_syntax::ast::Expression::Inline(fluent_syntax::ast::InlineExpression::Placeable { expression }) => {
+            return resolve_select(assets, locale, expression, args, unmatched);
+        },
+        _ => return,
+    };
+    let Some(value) = selector_value(assets, selector, args) else {
+        return;
+    };
+    let Some(chosen) = variants.iter().position(|variant| value.matches(locale, &variant.key)).or_else(|| variants.iter().position(|variant| variant.default)) else {
+        return;
+    };
+    let mut variant = variants.swap_remove(chosen);
+    if variant.key == (fluent_syntax::ast::VariantKey::Identifier { name: MISSING_DEFAULT_KEY }) {
+        unmatched.push(value.to_string());
+    }
+    resolve_selects(assets, locale, &mut variant.value, args, unmatched);
+    variant.default = true;
+    *selector = fluent_syntax::ast::InlineExpression::StringLiteral { value: "" };
+    *variants = vec![variant];
+}
+
+type LocaleObservers = RwLock<Vec<(u64, Arc<dyn Fn(&str) + Send + Sync>)>>;
+
+/// Handle returned by `Ftl::on_locale_change()`. The callback
+/// is unsubscribed when the handle is dropped.
+#[must_use = "the callback is unsubscribed when the handle is dropped"]
+pub struct SubscriptionHandle {
+    m_observers: Weak<LocaleObservers>,
+    m_id: u64,
 }
 
-fn add_ftl_bundle_resource(file_name: String, source: String, bundle: &mut fluent::FluentBundle<fluent::FluentResource>) -> bool {
-    match fluent::FluentResource::try_new(source) {
-        Ok(res) => {
-            if let Err(error_list) = bundle.add_resource(res) {
-                for e in error_list {
-                    println!("Error at {}.ftl: {}", file_name, e.to_string());
-                }
-                return false;
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        if let Some(observers) = self.m_observers.upgrade() {
+            observers.write().unwrap().retain(|(id, _)| *id != self.m_id);
+        }
+    }
+}
+
+/// Resources of a locale, as file names and sources.
+type LocaleSources = Vec<(String, String)>;
+
+/// The values of the plain-text messages of a locale, by message ID.
+type PlainMessages = HashMap<String, Arc<str>>;
+
+/// A zip archive read from memory.
+type ZipArchive = zip::ZipArchive<Cursor<Cow<'static, [u8]>>>;
+
+/// A zip archive opened by `Ftl`, with the modification time of its file.
+type OpenedZipArchive = (Option<SystemTime>, Arc<Mutex<ZipArchive>>);
+
+/// Handle returned by `Ftl::watch()`. Watching stops when
+/// the handle is dropped.
+#[must_use = "watching stops when the handle is dropped"]
+pub struct WatchHandle {
+    m_stopped: Arc<AtomicBool>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.m_stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Polls the resources of the loaded locales on the file system,
+/// from the thread started by `Ftl::watch()`.
+struct FtlWatcher {
+    m_source: String,
+    m_locale_to_path_components: Arc<HashMap<Locale, String>>,
+    m_sources: Arc<RwLock<HashMap<Locale, LocaleSources>>>,
+    m_stale_locales: Arc<RwLock<HashSet<Locale>>>,
+    m_added_sources: Arc<RwLock<HashMap<Locale, LocaleSources>>>,
+    m_locale_observers: Arc<LocaleObservers>,
+    /// Sources with syntax errors that have been reported, by path.
+    m_rejected: HashMap<String, String>,
+    /// Changed sources waiting to be unchanged for a poll, by path.
+    m_pending: HashMap<String, String>,
+}
+
+impl FtlWatcher {
+    fn poll(&mut self) {
+        let loaded = self.m_sources.read().unwrap().clone();
+        for (locale, mut sources) in loaded {
+            let Some(locale_path_comp) = self.m_locale_to_path_components.get(&locale) else {
+                continue;
+            };
+            let added_sources = self.m_added_sources.read().unwrap().get(&locale).cloned().unwrap_or_default();
+            let mut changed = false;
+            for (file_name, source) in sources.iter_mut() {
+                // Resources given through `Ftl::add_source()` are not watched.
+                if added_sources.iter().any(|(f, _)| f == file_name) {
+                    continue;
+                }
+                let res_path = format!("{}/{}/{}.ftl", self.m_source, locale_path_comp, file_name);
+                // A file that cannot be read, such as while it is being
+                // saved, is tried again at the next poll.
+                let Some(new_source) = rialight_filesystem::File::new(res_path.clone()).read_utf8().ok() else {
+                    continue;
+                };
+                if new_source == *source || self.m_rejected.get(&res_path) == Some(&new_source) {
+                    self.m_pending.remove(&res_path);
+                    continue;
+                }
+                // A file may be read while it is being written, so changes
+                // are applied once the file is unchanged for a poll.
+                if self.m_pending.get(&res_path) != Some(&new_source) {
+                    self.m_pending.insert(res_path, new_source);
+                    continue;
+                }
+                self.m_pending.remove(&res_path);
+                match fluent::FluentResource::try_new(new_source.clone()) {
+                    Ok(_) => {
+                        self.m_rejected.remove(&res_path);
+                        *source = new_source;
+                        changed = true;
+                    },
+                    Err((_, errors)) => {
+                        for e in errors.iter() {
+                            println!("Syntax error at {}", FtlParseError::new(locale_path_comp, &res_path, &new_source, e));
+                        }
+                        self.m_rejected.insert(res_path, new_source);
+                    },
+                }
+            }
+            if !changed {
+                continue;
+            }
+            self.m_sources.write().unwrap().insert(locale.clone(), sources);
+            self.m_stale_locales.write().unwrap().insert(locale);
+            let observers: Vec<_> = self.m_locale_observers.read().unwrap().iter().map(|(_, c)| c.clone()).collect();
+            for c in observers {
+                c(locale_path_comp);
+            }
+        }
+    }
+}
+
+/// Appends text to the last segment if it is text, otherwise
+/// as a new segment.
+fn push_text_segment(segments: &mut Vec<FtlSegment>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match segments.last_mut() {
+        Some(FtlSegment::Text(last)) => last.push_str(text),
+        _ => segments.push(FtlSegment::Text(text.to_owned())),
+    }
+}
+
+/// Errors that occurred while formatting a message.
+#[derive(Default)]
+struct FormatErrors {
+    m_errors: Vec<fluent::FluentError>,
+    /// The selector values that matched no variant of a select
+    /// expression without a default variant.
+    m_unmatched: Vec<String>,
+}
+
+/// Converts the errors that occurred while formatting a message.
+fn check_format_errors(id: &str, errors: &FormatErrors) -> Result<(), FtlError> {
+    let FormatErrors { m_errors: errors, m_unmatched: unmatched } = errors;
+    for e in errors.iter() {
+        if let fluent::FluentError::ResolverError(fluent::resolver::ResolverError::Reference(fluent::resolver::errors::ReferenceKind::Variable { id: name })) = e {
+            return Err(FtlError::MissingVariable {
+                id: id.to_owned(),
+                name: name.clone(),
+            });
+        }
+    }
+    if !errors.is_empty() || !unmatched.is_empty() {
+        return Err(FtlError::Format {
+            id: id.to_owned(),
+            errors: errors.iter().map(|e| e.to_string()).chain(unmatched.iter().map(|value| match value.as_str() {
+                "" => "no variant matches the selector and there is no default variant".to_owned(),
+                value => format!("no variant matches the selector value \"{}\" and there is no default variant", value),
+            })).collect(),
+        });
+    }
+    Ok(())
+}
+
+fn write_error(id: &str, error: fmt::Error) -> FtlError {
+    FtlError::Format {
+        id: id.to_owned(),
+        errors: vec![error.to_string()],
+    }
+}
+
+/// Sets the source of a resource of a locale, replacing
+/// the resource with the same file name, if any.
+fn set_locale_source(sources: &mut LocaleSources, file_name: &str, source: &str) {
+    match sources.iter_mut().find(|(f, _)| f == file_name) {
+        Some((_, s)) => *s = source.to_owned(),
+        None => sources.push((file_name.to_owned(), source.to_owned())),
+    }
+}
+
+/// A locale read or fetched by `Ftl::load_single_locale()`.
+struct LoadedLocale {
+    m_bundle: Arc<FtlBundle>,
+    m_message_ids: Vec<String>,
+    m_plain_messages: PlainMessages,
+    m_sources: LocaleSources,
+    m_parse_errors: Vec<FtlParseError>,
+}
+
+/// What `Ftl::get_message()` and `Ftl::format()` return for a message
+/// that is not defined by the current locale nor by its fallbacks,
+/// given to `FtlOptions::on_missing()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MissingBehavior {
+    /// Returns the message ID, so that missing messages stand out.
+    ReturnId,
+    /// Returns an empty string.
+    ReturnEmpty,
+    /// `get_message()` returns `None` and `format()` fails
+    /// with `FtlError::MessageNotFound`.
+    Error,
+}
+
+/// How `Ftl::set_locale()` resolved the requested locale.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocaleResolution {
+    /// The requested locale is supported.
+    Exact,
+    /// The requested locale is not supported, but a related locale is,
+    /// such as `en` for `en-AU`. Contains that locale, in the form in
+    /// which it appears in the `supported_locales` option.
+    FallbackTo(String),
+    /// Neither the requested locale nor a related locale is supported,
+    /// or the requested locale is malformed, so the default locale
+    /// is used.
+    DefaultUsed,
+}
+
+/// Style of pseudolocalization, given to `Ftl::enable_pseudolocalization()`.
+///
+/// Both styles wrap messages in brackets and pad them by about 40%, so that
+/// hardcoded strings and layouts that do not fit longer translations stand
+/// out. Only the text of messages is transformed; arguments, such as
+/// `{ $name }`, are kept intact.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PseudoStyle {
+    /// Replaces Latin letters by accented letters, as in `[Ẇḗŀƈǿḿḗ~~~]`.
+    Accented,
+    /// Replaces Latin letters by upside-down letters within a
+    /// right-to-left override, simulating a right-to-left locale.
+    Bidi,
+}
+
+impl PseudoStyle {
+    fn transform(self) -> fn(&str) -> std::borrow::Cow<str> {
+        match self {
+            PseudoStyle::Accented => |s| pseudo_replace(s, "ȦƁƇḒḖƑƓĦĪĴĶĿḾȠǾƤɊŘŞŦŬṼẆẊẎẐ", "ȧƀƈḓḗƒɠħīĵķŀḿƞǿƥɋřşŧŭṽẇẋẏẑ").into(),
+            PseudoStyle::Bidi => |s| format!("\u{202e}{}\u{202c}", pseudo_replace(s, "∀ԐↃᗡƎℲ⅁HIſӼ⅂WNOԀÒᴚS⊥∩ɅMX⅄Z", "ɐqɔpǝɟƃɥıɾʞʅɯuodbɹsʇnʌʍxʎz")).into(),
+        }
+    }
+}
+
+/// Replaces ASCII letters by the letters at the same positions
+/// of the given alphabets.
+fn pseudo_replace(s: &str, upper: &str, lower: &str) -> String {
+    let upper: Vec<char> = upper.chars().collect();
+    let lower: Vec<char> = lower.chars().collect();
+    s.chars().map(|ch| match ch {
+        'A'..='Z' => upper[(ch as u8 - b'A') as usize],
+        'a'..='z' => lower[(ch as u8 - b'a') as usize],
+        _ => ch,
+    }).collect()
+}
+
+/// Wraps a pseudolocalized message in brackets and pads it
+/// by about 40% of its length.
+fn pseudo_wrap(s: String) -> String {
+    let length = s.chars().filter(|ch| !matches!(ch, '\u{2068}' | '\u{2069}' | '\u{202e}' | '\u{202c}')).count();
+    format!("[{}{}]", s, "~".repeat((length * 2).div_ceil(5)))
+}
+
+/// Interface for working with Fluent Translation Lists.
+pub struct Ftl {
+    m_current_locale: RwLock<Option<Locale>>,
+    /// Locale given by `Ftl::set_formatting_locale()`.
+    m_formatting_locale: RwLock<Option<Locale>>,
+    /// Maps a Locale object to its equivalent path component.
+    /// The string to which the Locale maps depends in how the
+    /// Ftl object was constructed. If the `supported_locales` option
+    /// contains "en-us", then `m_locale_to_path_components.get(&locale!("en-US"))` returns "en-us".
+    /// When FTLs are loaded, this component is appended to the URL or file path;
+    /// for example, `"res/lang/en-us"`.
+    m_locale_to_path_components: Arc<HashMap<Locale, String>>,
+    m_supported_locales: Arc<HashSet<Locale>>,
+    m_default_locale: Locale,
+    m_missing_behavior: MissingBehavior,
+    m_fallback_on_format_error: bool,
+    m_first_variant_as_default: bool,
+    m_lazy: bool,
+    m_use_isolating: bool,
+    m_trim_message_whitespace: bool,
+    m_track_usage: bool,
+    m_global_args: Arc<RwLock<FtlArgs>>,
+    /// IDs of the messages requested, if the `track_usage` option is enabled.
+    m_used_ids: Arc<RwLock<HashSet<String>>>,
+    m_fallbacks: Arc<HashMap<Locale, Vec<Locale>>>,
+    m_locale_initializers: Arc<RwLock<Vec<fn(Locale, Arc<FtlBundle>)>>>,
+    m_functions: Arc<RwLock<HashMap<String, Arc<FtlFunction>>>>,
+    m_locale_observers: Arc<LocaleObservers>,
+    m_next_locale_observer_id: Arc<AtomicU64>,
+    m_assets: Arc<RwLock<HashMap<Locale, Arc<FtlBundle>>>>,
+    /// Maps a loaded locale to the IDs of the messages that it defines.
+    m_message_ids: Arc<RwLock<HashMap<Locale, Vec<String>>>>,
+    /// Maps a loaded locale to its plain-text messages, for `Ftl::format_shared()`.
+    m_plain_messages: Arc<RwLock<HashMap<Locale, PlainMessages>>>,
+    /// Maps a loaded locale to its resources, from which its bundle is rebuilt.
+    m_sources: Arc<RwLock<HashMap<Locale, LocaleSources>>>,
+    m_pseudo_style: Arc<RwLock<Option<PseudoStyle>>>,
+    /// Locales whose sources were changed by `Ftl::watch()`
+    /// and whose bundles are yet to be rebuilt.
+    m_stale_locales: Arc<RwLock<HashSet<Locale>>>,
+    /// Sources given through `Ftl::add_source()`.
+    m_added_sources: Arc<RwLock<HashMap<Locale, LocaleSources>>>,
+    m_assets_source: String,
+    m_assets_files: Vec<String>,
+    /// Maps a locale path component and a file name to an embedded source.
+    m_assets_embedded: Arc<HashMap<(String, String), &'static str>>,
+    m_assets_zip_archive: Option<FtlZipArchive>,
+    /// The archive of `m_assets_zip_archive` opened by a previous load.
+    m_opened_zip_archive: Arc<Mutex<Option<OpenedZipArchive>>>,
+    m_assets_resolver: Option<Arc<dyn AssetResolver>>,
+    m_assets_clean_unused: bool,
+    m_assets_strict: bool,
+    m_assets_strict_duplicates: bool,
+    m_assets_load_method: FtlLoadMethod,
+    m_assets_http_max_retries: u32,
+    m_assets_http_retry_backoff: Duration,
+    m_assets_watch_interval: Duration,
+    m_http_client: reqwest::Client,
+}
+
+/// Returns the parent of a locale, which is the locale without its region
+/// and variants or, if it has neither, without its script; for example,
+/// `en` for `en-GB` and `zh-Hant` for `zh-Hant-TW`.
+fn parent_locale(locale: &Locale) -> Option<Locale> {
+    let mut id = locale.id.clone();
+    if id.region.is_some() || !id.variants.is_empty() {
+        id.region = None;
+        id.variants = Default::default();
+    } else if id.script.is_some() {
+        id.script = None;
+    } else {
+        return None;
+    }
+    Some(Locale::from(id))
+}
+
+fn parse_locale_or_panic(s: &str) -> Locale {
+    Locale::try_from_bytes(s.as_bytes()).expect((format!("{} is a malformed locale.", s)).as_ref())
+}
+
+/// Candidates for matching a requested language against the supported
+/// locales, from the most specific to the least specific, excluding the
+/// language alone; for example, `zh-Hant-TW` and `zh-Hant`.
+fn locale_match_candidates(id: &LanguageIdentifier, output: &mut Vec<LanguageIdentifier>) {
+    let mut id = id.clone();
+    let push = |id: &LanguageIdentifier, output: &mut Vec<LanguageIdentifier>| {
+        if (id.script.is_some() || id.region.is_some()) && !output.contains(id) {
+            output.push(id.clone());
+        }
+    };
+    push(&id, output);
+    id.variants = Default::default();
+    push(&id, output);
+    id.region = None;
+    push(&id, output);
+}
+
+/// Negotiates the requested locales, in order of preference, against the
+/// supported locales. For each requested locale, the subtags are removed
+/// from the most specific to the least specific until a supported locale
+/// matches, also trying the likely script of the locale; for example,
+/// `zh-TW` tries `zh-TW`, `zh-Hant-TW`, `zh-Hant` and `zh`. At last, any
+/// supported locale of the same language matches.
+fn negotiate_locale(requested: &[Locale], supported: &HashSet<Locale>) -> Option<Locale> {
+    let mut supported: Vec<&Locale> = supported.iter().collect();
+    supported.sort_by_key(|locale| locale.to_string());
+    let expander = icu::locid_transform::LocaleExpander::new();
+    for locale in requested {
+        let mut candidates = vec![];
+        locale_match_candidates(&locale.id, &mut candidates);
+        let mut maximized = locale.id.clone();
+        expander.maximize(&mut maximized);
+        locale_match_candidates(&maximized, &mut candidates);
+        candidates.push(LanguageIdentifier::from(locale.id.language));
+        for candidate in candidates.iter() {
+            if let Some(s) = supported.iter().find(|s| s.id == *candidate) {
+                return Some((*s).clone());
+            }
+        }
+        if let Some(s) = supported.iter().find(|s| s.id.language == locale.id.language) {
+            return Some((*s).clone());
+        }
+    }
+    None
+}
+
+/// Parses an `Accept-Language` header, such as `pt-BR,pt;q=0.9,en;q=0.8`,
+/// and negotiates its languages against the supported locales. Returns the
+/// best supported locale, in the form in which it is given, or `default`
+/// if no supported locale matches.
+///
+/// Languages are tried in descending order of quality, keeping the header
+/// order for equal qualities. Malformed entries, the `*` wildcard and
+/// languages of zero quality are skipped.
+///
+/// ```
+/// use rialight::intl::ftl::negotiate_accept_language;
+///
+/// assert_eq!(negotiate_accept_language("pt-BR,pt;q=0.9,en;q=0.8", &["en", "pt"], "en"), "pt");
+/// ```
+pub fn negotiate_accept_language(header: &str, supported: &[&str], default: &str) -> String {
+    let mut requested: Vec<(f32, Locale)> = vec![];
+    for entry in header.split(',') {
+        let mut parts = entry.split(';');
+        let tag = parts.next().unwrap_or("").trim();
+        let mut quality = Some(1.0);
+        for param in parts {
+            if let Some((k, v)) = param.split_once('=') {
+                if k.trim().eq_ignore_ascii_case("q") {
+                    quality = v.trim().parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q));
+                }
+            }
+        }
+        let Some(quality) = quality else {
+            continue;
+        };
+        if quality == 0.0 || tag == "*" {
+            continue;
+        }
+        if let Ok(locale) = Locale::try_from_bytes(tag.as_bytes()) {
+            requested.push((quality, locale));
+        }
+    }
+    // Stable sort, so that equal qualities keep the header order.
+    requested.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let requested: Vec<Locale> = requested.into_iter().map(|(_, locale)| locale).collect();
+
+    let mut supported_locales = HashMap::<Locale, &str>::new();
+    for s in supported.iter() {
+        if let Ok(locale) = Locale::try_from_bytes(s.as_bytes()) {
+            supported_locales.entry(locale).or_insert(s);
+        }
+    }
+    let supported_set: HashSet<Locale> = supported_locales.keys().cloned().collect();
+    match negotiate_locale(&requested, &supported_set) {
+        Some(locale) => supported_locales[&locale].to_owned(),
+        None => default.to_owned(),
+    }
+}
+
+fn locale_to_unic_langid_impl_langid(locale: &Locale) -> unic_langid_impl::LanguageIdentifier {
+    unic_langid_impl::LanguageIdentifier::from_bytes(locale.id.to_string().as_bytes()).unwrap()
+}
+
+/// The bundle of a locale. Its resources are shared with the bundles
+/// of other `Ftl` objects that load the same sources.
+pub type FtlBundle = fluent::concurrent::FluentBundle<Arc<fluent::FluentResource>>;
+
+/// A parsed resource in `RESOURCE_CACHE`.
+struct CachedResource {
+    m_source: String,
+    /// Resource parsed from the source, with default variants added
+    /// to the select expressions that have none.
+    m_resource: Arc<fluent::FluentResource>,
+    m_errors: Vec<fluent_syntax::parser::ParserError>,
+    /// Modification time of the file, for `FtlLoadMethod::FileSystem`.
+    m_modified: Option<SystemTime>,
+    /// Entity tag of the response, for `FtlLoadMethod::Http`.
+    m_etag: Option<String>,
+}
+
+thread_local! {
+    /// Locales set by `Ftl::with_locale()` on the current thread, innermost
+    /// last, with the `Ftl::locale_override_key()` of the objects that they
+    /// apply to.
+    static LOCALE_OVERRIDES: RefCell<Vec<(usize, Locale)>> = const { RefCell::new(vec![]) };
+}
+
+/// Removes the innermost locale set by `Ftl::with_locale()` when dropped,
+/// including when the closure panics.
+struct LocaleOverrideGuard;
+
+impl Drop for LocaleOverrideGuard {
+    fn drop(&mut self) {
+        LOCALE_OVERRIDES.with(|overrides| overrides.borrow_mut().pop());
+    }
+}
+
+/// Parsed resources of every `Ftl` object, by path or URL.
+static RESOURCE_CACHE: LazyLock<Mutex<HashMap<String, CachedResource>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Parses a resource, reusing the cached resource of the path if it has
+/// the same source. The given modification time and entity tag replace
+/// the ones of the cached resource.
+fn parse_cached_resource(res_path: &str, source: &str, modified: Option<SystemTime>, etag: Option<String>) -> (Arc<fluent::FluentResource>, Vec<fluent_syntax::parser::ParserError>) {
+    let mut cache = RESOURCE_CACHE.lock().unwrap();
+    if let Some(cached) = cache.get_mut(res_path).filter(|cached| cached.m_source == source) {
+        if modified.is_some() {
+            cached.m_modified = modified;
+        }
+        if etag.is_some() {
+            cached.m_etag = etag;
+        }
+        return (cached.m_resource.clone(), cached.m_errors.clone());
+    }
+    // A resource with syntax errors still contains its valid entries.
+    let (res, errors) = match fluent::FluentResource::try_new(source.to_owned()) {
+        Ok(res) => (res, vec![]),
+        Err((res, errors)) => (res, errors),
+    };
+    // The syntax errors of select expressions without a default variant
+    // are still reported.
+    let res = if errors.iter().any(|e| e.kind == fluent_syntax::parser::ErrorKind::MissingDefaultVariant) {
+        fluent::FluentResource::try_new(add_missing_default_variants(source)).unwrap_or_else(|(res, _)| res)
+    } else {
+        res
+    };
+    let res = Arc::new(res);
+    cache.insert(res_path.to_owned(), CachedResource {
+        m_source: source.to_owned(),
+        m_resource: res.clone(),
+        m_errors: errors.clone(),
+        m_modified: modified,
+        m_etag: etag,
+    });
+    (res, errors)
+}
+
+/// The limit of the decompressed size of a resource, so that small
+/// compressed inputs cannot exhaust memory.
+const MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Reads the output of a decoder. Returns `None` if the input is invalid
+/// or the output exceeds `MAX_DECOMPRESSED_SIZE`.
+fn read_decoded(decoder: impl Read) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    decoder.take(MAX_DECOMPRESSED_SIZE + 1).read_to_end(&mut output).ok()?;
+    (output.len() as u64 <= MAX_DECOMPRESSED_SIZE).then_some(output)
+}
+
+/// Decompresses gzip data, such as a `.ftl.gz` file.
+fn gunzip(data: &[u8]) -> Option<Vec<u8>> {
+    read_decoded(flate2::read::MultiGzDecoder::new(data))
+}
+
+/// Decompresses a response body with the given content encoding.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown", feature = "web")))]
+fn decode_content(encoding: &str, body: &[u8]) -> Result<String, String> {
+    let body = match encoding {
+        "gzip" | "x-gzip" => gunzip(body),
+        // Some servers send raw DEFLATE data instead of zlib data.
+        "deflate" => read_decoded(flate2::read::ZlibDecoder::new(body)).or_else(|| read_decoded(flate2::read::DeflateDecoder::new(body))),
+        _ => return Err(format!("unsupported content encoding: {}", encoding)),
+    };
+    body.and_then(|body| String::from_utf8(body).ok()).ok_or_else(|| format!("invalid {} data", encoding))
+}
+
+/// Returns the source of a cached resource if the validator
+/// accepts it.
+fn cached_source(res_path: &str, validator: impl FnOnce(&CachedResource) -> bool) -> Option<String> {
+    RESOURCE_CACHE.lock().unwrap().get(res_path).filter(|cached| validator(cached)).map(|cached| cached.m_source.clone())
+}
+
+/// Resolves an `app://` URL or a relative path against the page.
+#[cfg(all(target_family = "wasm", target_os = "unknown", feature = "web"))]
+fn resolve_browser_url(window: &web_sys::Window, url: &str) -> Result<String, wasm_bindgen::JsValue> {
+    let location = window.location();
+    if let Some(path) = url.strip_prefix("app://") {
+        return Ok(format!("{}/{}", location.origin()?, path));
+    }
+    Ok(web_sys::Url::new_with_base(url, &location.href()?)?.href())
+}
+
+#[cfg(all(target_family = "wasm", target_os = "unknown", feature = "web"))]
+async fn browser_sleep(window: &web_sys::Window, duration: Duration) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, duration.as_millis() as i32);
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// Adds a resource to a bundle, collecting the IDs of its messages.
+/// Messages and terms that are already defined are overridden, unless
+/// `strict` is `true`, in which case `false` is returned if the resource
+/// conflicts with the bundle.
+fn add_ftl_bundle_resource(file_name: &str, res: Arc<fluent::FluentResource>, bundle: &mut FtlBundle, message_ids: &mut Vec<String>, strict: bool) -> bool {
+    for entry in res.entries() {
+        if let fluent_syntax::ast::Entry::Message(message) = entry {
+            message_ids.push(message.id.name.to_owned());
+        }
+    }
+    if !strict {
+        bundle.add_resource_overriding(res);
+        return true;
+    }
+    if let Err(error_list) = bundle.add_resource(res) {
+        for e in error_list {
+            println!("Error at {}.ftl: {}", file_name, e);
+        }
+        return false;
+    }
+    true
+}
+
+impl Ftl {
+    /// Constructs a `Ftl` object.
+    pub fn new(options: &mut FtlOptions) -> Self {
+        let mut locale_to_path_components = HashMap::<Locale, String>::new();
+        let mut supported_locales = HashSet::<Locale>::new();
+        for unparsed_locale in options.m_supported_locales.get_mut().unwrap().iter() {
+            let parsed_locale = parse_locale_or_panic(unparsed_locale);
+            locale_to_path_components.insert(parsed_locale.clone(), unparsed_locale.clone());
+            supported_locales.insert(parsed_locale);
+        }
+        let mut fallbacks = HashMap::<Locale, Vec<Locale>>::new();
+        for (k, v) in options.m_fallbacks.get_mut().unwrap().iter() {
+            fallbacks.insert(parse_locale_or_panic(k), v.iter().map(|s| parse_locale_or_panic(s)).collect());
+        }
+        let default_locale = options.m_default_locale.get_mut().unwrap().clone();
+        let mut http_client = reqwest::Client::builder();
+        if let Some(timeout) = options.m_assets.get_mut().unwrap().m_http_timeout.get() {
+            http_client = http_client.timeout(timeout);
+        }
+        let mut http_headers = reqwest::header::HeaderMap::new();
+        for (k, v) in options.m_assets.get_mut().unwrap().m_http_base_headers.get_mut().unwrap().iter() {
+            let name = reqwest::header::HeaderName::from_bytes(k.as_bytes()).unwrap_or_else(|_| panic!("{} is a malformed HTTP header name.", k));
+            let value = reqwest::header::HeaderValue::from_str(v).unwrap_or_else(|_| panic!("{} is a malformed HTTP header value.", v));
+            http_headers.append(name, value);
+        }
+        let accept_encoding = options.m_assets.get_mut().unwrap().m_http_accept_encoding.get_mut().unwrap().join(", ");
+        if !accept_encoding.is_empty() {
+            http_headers.insert(reqwest::header::ACCEPT_ENCODING, reqwest::header::HeaderValue::from_str(&accept_encoding).expect("Invalid Accept-Encoding."));
+        }
+        let http_client = http_client.default_headers(http_headers).build().expect("Failed to initialize the HTTP client.");
+        Self {
+            m_current_locale: RwLock::new(None),
+            m_formatting_locale: RwLock::new(None),
+            m_locale_to_path_components: Arc::new(locale_to_path_components),
+            m_supported_locales: Arc::new(supported_locales),
+            m_default_locale: parse_locale_or_panic(&default_locale),
+            m_missing_behavior: options.m_on_missing.get(),
+            m_fallback_on_format_error: options.m_fallback_on_format_error.get(),
+            m_first_variant_as_default: options.m_first_variant_as_default.get(),
+            m_lazy: options.m_lazy.get(),
+            m_use_isolating: options.m_use_isolating.get(),
+            m_trim_message_whitespace: options.m_trim_message_whitespace.get(),
+            m_track_usage: options.m_track_usage.get(),
+            m_global_args: Arc::new(RwLock::new(FtlArgs::new())),
+            m_used_ids: Arc::new(RwLock::new(HashSet::new())),
+            m_fallbacks: Arc::new(fallbacks),
+            m_locale_initializers: Arc::new(RwLock::new(vec![])),
+            m_functions: Arc::new(RwLock::new(hashmap![])),
+            m_locale_observers: Arc::new(RwLock::new(vec![])),
+            m_next_locale_observer_id: Arc::new(AtomicU64::new(0)),
+            m_assets: Arc::new(RwLock::new(HashMap::new())),
+            m_message_ids: Arc::new(RwLock::new(HashMap::new())),
+            m_plain_messages: Arc::new(RwLock::new(HashMap::new())),
+            m_sources: Arc::new(RwLock::new(HashMap::new())),
+            m_pseudo_style: Arc::new(RwLock::new(None)),
+            m_stale_locales: Arc::new(RwLock::new(HashSet::new())),
+            m_added_sources: Arc::new(RwLock::new(HashMap::new())),
+            m_assets_source: options.m_assets.get_mut().unwrap().m_source.get_mut().unwrap().clone(),
+            m_assets_files: options.m_assets.get_mut().unwrap().m_files.get_mut().unwrap().iter().map(|s| s.clone()).collect(),
+            m_assets_embedded: Arc::new(options.m_assets.get_mut().unwrap().m_embedded.get_mut().unwrap().clone()),
+            m_assets_zip_archive: options.m_assets.get_mut().unwrap().m_zip_archive.get_mut().unwrap().clone(),
+            m_opened_zip_archive: Arc::new(Mutex::new(None)),
+            m_assets_resolver: options.m_assets.get_mut().unwrap().m_resolver.get_mut().unwrap().clone(),
+            m_assets_clean_unused: options.m_assets.get_mut().unwrap().m_clean_unused.get(),
+            m_assets_strict: options.m_assets.get_mut().unwrap().m_strict.get(),
+            m_assets_strict_duplicates: options.m_assets.get_mut().unwrap().m_strict_duplicates.get(),
+            m_assets_load_method: options.m_assets.get_mut().unwrap().m_load_method.get(),
+            m_assets_http_max_retries: options.m_assets.get_mut().unwrap().m_http_max_retries.get(),
+            m_assets_http_retry_backoff: options.m_assets.get_mut().unwrap().m_http_retry_backoff.get(),
+            m_assets_watch_interval: options.m_assets.get_mut().unwrap().m_watch_interval.get(),
+            m_http_client: http_client,
+        }
+    }
+
+    /// Returns a set of supported locales, reflecting
+    /// the ones that were specified when constructing the `Ftl` object.
+    pub fn supported_locales(&self) -> HashSet<Locale> {
+        self.m_supported_locales.as_ref().clone()
+    }
+
+    /// Returns `true` if the locale is one of the supported locales
+    /// that were specified when constructing the `Ftl` object,
+    /// otherwise `false`.
+    pub fn supports_locale(&self, arg: &Locale) -> bool {
+        self.m_supported_locales.contains(arg)
+    }
+
+    /// Returns `true` if a supported locale is loaded, otherwise `false`.
+    pub fn is_loaded(&self, locale: &str) -> bool {
+        Locale::try_from_bytes(locale.as_bytes()).is_ok_and(|locale| self.m_assets.read().unwrap().contains_key(&locale))
+    }
+
+    /// Returns the current locale, in the form in which it appears in the
+    /// `supported_locales` option. If no locale has been set or loaded yet,
+    /// returns the default locale.
+    pub fn current_locale(&self) -> String {
+        let locale = self.current_locale_id().unwrap_or_else(|| self.m_default_locale.clone());
+        self.locale_to_string(&locale)
+    }
+
+    /// Sets the current locale against which `get_message()` and `format()`
+    /// resolve messages. The requested locale is negotiated against the
+    /// supported locales, falling back to the default locale if it is not
+    /// supported. Returns how the requested locale was resolved, so that
+    /// missing translations can be noticed; `current_locale()` returns
+    /// the negotiated locale.
+    ///
+    /// The negotiated locale is expected to have been loaded; otherwise
+    /// messages are not found until it is loaded. `switch_locale()`
+    /// loads it first.
+    pub fn set_locale(&self, locale: &str) -> LocaleResolution {
+        let requested = Locale::try_from_bytes(locale.as_bytes()).ok();
+        let negotiated = requested.clone().and_then(|locale| negotiate_locale(&[locale], &self.m_supported_locales));
+        let resolution = match &negotiated {
+            Some(negotiated) if Some(negotiated) == requested.as_ref() => LocaleResolution::Exact,
+            Some(negotiated) => LocaleResolution::FallbackTo(self.locale_to_string(negotiated)),
+            None => LocaleResolution::DefaultUsed,
+        };
+        self.change_current_locale(negotiated.unwrap_or_else(|| self.m_default_locale.clone()));
+        resolution
+    }
+
+    /// Calls `f` with the current locale overridden on the current thread,
+    /// such as for rendering a request in the locale of its client. The
+    /// requested locale is negotiated as `set_locale()` does and is expected
+    /// to have been loaded. The current locale of other threads, and the one
+    /// restored once `f` returns, is unaffected; overrides may be nested.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # fn f(ftl: &Ftl) {
+    /// let greeting = ftl.with_locale("de", || ftl.get_message("hello-world"));
+    /// # }
+    /// ```
+    pub fn with_locale<R>(&self, locale: &str, f: impl FnOnce() -> R) -> R {
+        let locale = self.negotiate_or_default(locale);
+        LOCALE_OVERRIDES.with(|overrides| overrides.borrow_mut().push((self.locale_override_key(), locale)));
+        let _guard = LocaleOverrideGuard;
+        f()
+    }
+
+    /// Sets the locale in which `format_number()`, `format_currency()`,
+    /// `format_unit()`, `format_datetime()`, `format_date()` and
+    /// `format_time()` format, such as the regional locale of the user,
+    /// while messages are still resolved in the current locale. The locale
+    /// need not be supported. A malformed tag is ignored.
+    ///
+    /// Numbers interpolated into messages are still formatted
+    /// in the locale of the message.
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, number::NumberFormatOptions};
+    /// # fn f(ftl: &Ftl) {
+    /// ftl.set_formatting_locale("de-DE");
+    /// // "1.234,56", even if messages are in English
+    /// let total = ftl.format_number(1234.56, &NumberFormatOptions::new());
+    /// # }
+    /// ```
+    pub fn set_formatting_locale(&self, locale: &str) {
+        if let Ok(locale) = Locale::try_from_bytes(locale.as_bytes()) {
+            *self.m_formatting_locale.write().unwrap() = Some(locale);
+        }
+    }
+
+    /// Makes numbers and dates be formatted in the current locale again,
+    /// undoing `set_formatting_locale()`.
+    pub fn reset_formatting_locale(&self) {
+        *self.m_formatting_locale.write().unwrap() = None;
+    }
+
+    /// Returns the locale in which numbers and dates are formatted, which is
+    /// the one given to `set_formatting_locale()` or else the current locale.
+    pub fn formatting_locale(&self) -> String {
+        match self.m_formatting_locale.read().unwrap().as_ref() {
+            Some(locale) => locale.to_string(),
+            None => self.current_locale(),
+        }
+    }
+
+    fn formatting_locale_id(&self) -> Locale {
+        self.m_formatting_locale.read().unwrap().clone().unwrap_or_else(|| self.current_or_default_locale_id())
+    }
+
+    fn negotiate_or_default(&self, locale: &str) -> Locale {
+        Locale::try_from_bytes(locale.as_bytes()).ok()
+            .and_then(|locale| negotiate_locale(&[locale], &self.m_supported_locales))
+            .unwrap_or_else(|| self.m_default_locale.clone())
+    }
+
+    /// Returns the text direction of the current locale, or of the default
+    /// locale if no locale has been set yet, so that layouts may be mirrored
+    /// for right-to-left scripts.
+    pub fn text_direction(&self) -> TextDirection {
+        self.current_or_default_locale_id().text_direction()
+    }
+
+    /// Returns the name of a locale in its own language, such as
+    /// `français` for `fr`, for listing locales in a language picker.
+    /// Returns `None` if the locale is malformed or has no name
+    /// in the locale data.
+    pub fn display_name(&self, locale: &str) -> Option<String> {
+        display_names::display_name(&Locale::try_from_bytes(locale.as_bytes()).ok()?)
+    }
+
+    /// Returns the name of a locale in another locale's language,
+    /// such as `French` for `fr` in `en`.
+    pub fn display_name_in(&self, locale: &str, in_locale: &str) -> Option<String> {
+        let locale = Locale::try_from_bytes(locale.as_bytes()).ok()?;
+        let in_locale = Locale::try_from_bytes(in_locale.as_bytes()).ok()?;
+        display_names::display_name_in(&locale, &in_locale)
+    }
+
+    /// Returns the CLDR ordinal plural category of a number in the current
+    /// locale, or in the default locale if no locale has been set yet.
+    /// For example, in English, 1 is `One` (1st), 2 is `Two` (2nd),
+    /// 3 is `Few` (3rd) and 4 is `Other` (4th).
+    ///
+    /// Messages may select on the same categories through the `ORDINAL()`
+    /// function:
+    ///
+    /// ```ftl
+    /// place = { ORDINAL($n) ->
+    ///     [one] {$n}st
+    ///     [two] {$n}nd
+    ///     [few] {$n}rd
+    ///    *[other] {$n}th
+    /// }
+    /// ```
+    pub fn ordinal_category(&self, n: i64) -> PluralCategory {
+        ordinal_category(&self.current_or_default_locale_id(), &fixed_decimal::FixedDecimal::from(n))
+    }
+
+    /// Returns the CLDR cardinal plural category of a number in the current
+    /// locale, or in the default locale if no locale has been set yet, such
+    /// as `One` for 1 and `Other` for 2 in English. A string containing a
+    /// number keeps its visible fraction digits, so `"1.0"` is `Other` in
+    /// English whereas `1` is `One`. Other strings are `Other`. These are
+    /// the categories that select expressions over numbers match.
+    pub fn plural_category(&self, n: impl Into<FtlValue>) -> PluralCategory {
+        let Some(n) = fluent_value_to_decimal(&n.into().to_fluent_value()) else {
+            return PluralCategory::Other;
+        };
+        cardinal_category(&self.current_or_default_locale_id(), &n)
+    }
+
+    /// Formats a number outside of any message according to the formatting
+    /// locale, which is the current locale, or the default locale if no
+    /// locale has been set yet, unless `set_formatting_locale()` is used.
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, number::NumberFormatOptions};
+    /// # fn f(ftl: &Ftl) {
+    /// // "1,234,567.50" in English
+    /// let score = ftl.format_number(1234567.5, NumberFormatOptions::new().minimum_fraction_digits(2));
+    /// # }
+    /// ```
+    pub fn format_number(&self, n: f64, options: &NumberFormatOptions) -> String {
+        crate::number::format(&self.formatting_locale_id(), n, options)
+    }
+
+    /// Formats a currency amount according to the formatting locale
+    /// (see `format_number()`).
+    /// `currency` is an ISO 4217 code, such as `USD`. Currency names are
+    /// only included for English (see `number::currency_display_for()`).
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, number::CurrencyFormatOptions};
+    /// # fn f(ftl: &Ftl) {
+    /// // "$1,234.50" in en-US
+    /// let price = ftl.format_currency(1234.5, "USD", &CurrencyFormatOptions::new());
+    /// # }
+    /// ```
+    pub fn format_currency(&self, amount: f64, currency: &str, options: &CurrencyFormatOptions) -> String {
+        crate::number::format_currency(&self.formatting_locale_id(), amount, currency, options)
+    }
+
+    /// Formats a quantity in a measurement unit according to the
+    /// formatting locale (see `format_number()`). Unit names are only
+    /// included for English (see `number::unit_style_for()`).
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, number::{Unit, UnitStyle}};
+    /// # fn f(ftl: &Ftl) {
+    /// // "120 km/h" in English
+    /// let speed = ftl.format_unit(120.0, Unit::KilometerPerHour, UnitStyle::Short);
+    /// # }
+    /// ```
+    pub fn format_unit(&self, value: f64, unit: Unit, style: UnitStyle) -> String {
+        crate::number::format_unit(&self.formatting_locale_id(), value, unit, style, &NumberFormatOptions::new())
+    }
+
+    /// Formats the date and time of a timestamp, in UTC, according to the
+    /// formatting locale (see `format_number()`).
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, datetime::DateTimeStyle};
+    /// # use std::time::SystemTime;
+    /// # fn f(ftl: &Ftl) {
+    /// // "3/9/24, 4:00 PM" in en-US
+    /// let posted_at = ftl.format_datetime(SystemTime::now(), DateTimeStyle::Short);
+    /// # }
+    /// ```
+    pub fn format_datetime(&self, ts: SystemTime, style: DateTimeStyle) -> String {
+        crate::datetime::format(&self.formatting_locale_id(), ts, style)
+    }
+
+    /// Formats the date of a timestamp, in UTC, according to the
+    /// formatting locale (see `format_number()`).
+    pub fn format_date(&self, ts: SystemTime, style: DateTimeStyle) -> String {
+        crate::datetime::format_date(&self.formatting_locale_id(), ts, style)
+    }
+
+    /// Formats the time of a timestamp, in UTC, according to the
+    /// formatting locale (see `format_number()`).
+    pub fn format_time(&self, ts: SystemTime, style: DateTimeStyle) -> String {
+        crate::datetime::format_time(&self.formatting_locale_id(), ts, style)
+    }
+
+    /// Formats a relative time according to the current locale, or the
+    /// default locale if no locale has been set yet. A negative value
+    /// is in the past.
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, relative_time::RelativeTimeUnit};
+    /// # fn f(ftl: &Ftl) {
+    /// // "3 minutes ago" in English
+    /// let last_seen = ftl.format_relative_time(-3, RelativeTimeUnit::Minute);
+    /// # }
+    /// ```
+    pub fn format_relative_time(&self, value: i64, unit: RelativeTimeUnit) -> String {
+        crate::relative_time::format(&self.current_or_default_locale_id(), value, unit)
+    }
+
+    /// Joins a list according to the current locale, or the default
+    /// locale if no locale has been set yet.
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, list::ListStyle};
+    /// # fn f(ftl: &Ftl) {
+    /// // "X, Y, and Z" in English
+    /// let unlocked = ftl.format_list(&["X", "Y", "Z"], ListStyle::Conjunction);
+    /// # }
+    /// ```
+    pub fn format_list(&self, items: &[&str], style: ListStyle) -> String {
+        crate::list::format(&self.current_or_default_locale_id(), items, style)
+    }
+
+    /// Returns a collator for sorting strings according to the current
+    /// locale, or the default locale if no locale has been set yet.
+    ///
+    /// ```
+    /// # use rialight::intl::{ftl::*, collator::CollatorOptions};
+    /// # fn f(ftl: &Ftl, friends: &mut [String]) {
+    /// ftl.collator(&CollatorOptions::new()).sort(friends);
+    /// # }
+    /// ```
+    pub fn collator(&self, options: &CollatorOptions) -> Collator {
+        Collator::new(&self.current_or_default_locale_id(), options)
+    }
+
+    /// Compares two strings according to the current locale, or the
+    /// default locale if no locale has been set yet, with the default
+    /// collator options.
+    pub fn compare(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        self.collator(&CollatorOptions::new()).compare(a, b)
+    }
+
+    /// Converts a string to uppercase according to the casing rules of
+    /// the current locale, or the default locale if no locale has been
+    /// set yet.
+    pub fn to_locale_uppercase(&self, s: &str) -> String {
+        crate::case_mapping::to_uppercase(&self.current_or_default_locale_id(), s)
+    }
+
+    /// Converts a string to lowercase according to the casing rules of
+    /// the current locale, or the default locale if no locale has been
+    /// set yet.
+    pub fn to_locale_lowercase(&self, s: &str) -> String {
+        crate::case_mapping::to_lowercase(&self.current_or_default_locale_id(), s)
+    }
+
+    /// Folds the case of a string for case-insensitive matching according
+    /// to the current locale, or the default locale if no locale has been
+    /// set yet.
+    pub fn fold_case(&self, s: &str) -> String {
+        crate::case_mapping::fold(&self.current_or_default_locale_id(), s)
+    }
+
+    /// Negotiates BCP-47 language tags, in order of preference, against
+    /// the supported locales. Returns the best supported locale, in the form
+    /// in which it appears in the `supported_locales` option, or `None` if
+    /// no supported locale matches. Malformed tags are ignored.
+    ///
+    /// A requested locale matches a supported locale after removing its
+    /// subtags from the most specific to the least specific; for example,
+    /// `zh-Hant-TW` tries `zh-Hant-TW`, `zh-Hant` and then `zh`, and `en-US`
+    /// matches `en`. The likely script of the locale is also considered, so
+    /// that `zh-TW` matches `zh-Hant` before `zh`.
+    pub fn negotiate(&self, requested: &[&str]) -> Option<String> {
+        let requested: Vec<Locale> = requested.iter().filter_map(|s| Locale::try_from_bytes(s.as_bytes()).ok()).collect();
+        negotiate_locale(&requested, &self.m_supported_locales).map(|locale| self.locale_to_string(&locale))
+    }
+
+    /// Adds a callback function that is called with the new locale
+    /// whenever the current locale changes, either through `set_locale()`
+    /// or `load()`, and with each locale reloaded by `watch()`.
+    /// The callback is unsubscribed when the returned handle is dropped.
+    pub fn on_locale_change(&self, callback: impl Fn(&str) + Send + Sync + 'static) -> SubscriptionHandle {
+        let id = self.m_next_locale_observer_id.fetch_add(1, Ordering::Relaxed);
+        self.m_locale_observers.write().unwrap().push((id, Arc::new(callback)));
+        SubscriptionHandle {
+            m_observers: Arc::downgrade(&self.m_locale_observers),
+            m_id: id,
+        }
+    }
+
+    fn change_current_locale(&self, locale: Locale) {
+        let previous = self.m_current_locale.write().unwrap().replace(locale.clone());
+        if previous.as_ref() == Some(&locale) {
+            return;
+        }
+        // Collect the callbacks first, so that they may subscribe
+        // or unsubscribe other callbacks.
+        let observers: Vec<_> = self.m_locale_observers.read().unwrap().iter().map(|(_, c)| c.clone()).collect();
+        let locale = self.locale_to_string(&locale);
+        for c in observers {
+            c(&locale);
+        }
+    }
+
+    /// Identifies the `Ftl` object and its clones in `LOCALE_OVERRIDES`,
+    /// as the address of their shared bundles, which outlive the override.
+    fn locale_override_key(&self) -> usize {
+        Arc::as_ptr(&self.m_assets) as usize
+    }
+
+    fn current_locale_id(&self) -> Option<Locale> {
+        let key = self.locale_override_key();
+        let overridden = LOCALE_OVERRIDES.with(|overrides| {
+            overrides.borrow().iter().rev().find(|(k, _)| *k == key).map(|(_, locale)| locale.clone())
+        });
+        overridden.or_else(|| self.m_current_locale.read().unwrap().clone())
+    }
+
+    fn current_or_default_locale_id(&self) -> Locale {
+        self.current_locale_id().unwrap_or_else(|| self.m_default_locale.clone())
+    }
+
+    fn locale_to_string(&self, locale: &Locale) -> String {
+        self.m_locale_to_path_components.get(locale).cloned().unwrap_or_else(|| locale.to_string())
+    }
+
+    /// Returns the currently loaded locale followed by its fallbacks or empty if no locale is loaded.
+    pub fn locale_and_fallbacks(&self) -> HashSet<Locale> {
+        if let Some(c) = self.current_locale_id() {
+            let mut r: HashSet<Locale> = hashset![c.clone()];
+            self.enumerate_fallbacks(c.clone(), &mut r);
+            return r;
+        }
+        hashset![]
+    }
+
+    /// Returns the currently loaded fallbacks.
+    pub fn fallbacks(&self) -> HashSet<Locale> {
+        if let Some(c) = self.current_locale_id() {
+            let mut r: HashSet<Locale> = hashset![];
+            self.enumerate_fallbacks(c.clone(), &mut r);
+            return r;
+        }
+        hashset![]
+    }
+
+    /// Adds a callback function to initialize the `FluentBundle` object of a locale.
+    /// The callback is called when the locale is loaded.
+    pub fn initialize_locale(&self, callback: fn(Locale, Arc<FtlBundle>)) {
+        self.m_locale_initializers.write().unwrap().push(callback);
+    }
+
+    /// Enables pseudolocalization of every message, for testing the user
+    /// interface. Loaded locales are rebuilt from their resources, which
+    /// are not read or fetched again.
+    pub fn enable_pseudolocalization(&self, style: PseudoStyle) {
+        *self.m_pseudo_style.write().unwrap() = Some(style);
+        self.rebuild_bundles();
+    }
+
+    /// Disables pseudolocalization, rebuilding the loaded locales.
+    pub fn disable_pseudolocalization(&self) {
+        *self.m_pseudo_style.write().unwrap() = None;
+        self.rebuild_bundles();
+    }
+
+    /// Returns the style of pseudolocalization, if enabled.
+    pub fn pseudolocalization(&self) -> Option<PseudoStyle> {
+        *self.m_pseudo_style.read().unwrap()
+    }
+
+    fn rebuild_bundles(&self) {
+        let sources = self.m_sources.read().unwrap().clone();
+        let mut assets = self.m_assets.write().unwrap();
+        for (locale, sources) in sources {
+            // The resources were valid when the locale was loaded.
+            if let Ok(loaded) = self.build_bundle(&locale, sources) {
+                assets.insert(locale, loaded.m_bundle);
+            }
+        }
+    }
+
+    /// Watches the resources of the loaded locales on the file system,
+    /// for `FtlLoadMethod::FileSystem`, so that edits appear without
+    /// restarting the application. Changed resources are parsed by a
+    /// background thread and then the callbacks added through
+    /// `on_locale_change()` are called with the changed locale, in the
+    /// form in which it appears in the `supported_locales` option.
+    /// The bundle of the locale is rebuilt the next time that a message
+    /// is resolved.
+    ///
+    /// Syntax errors in a changed resource are printed and the resource
+    /// is not reloaded until they are fixed, keeping the previous one.
+    /// Watching stops when the returned handle is dropped. Fails with
+    /// `FtlError::UnsupportedLoadMethod` with other load methods or
+    /// with a custom resolver.
+    pub fn watch(&self) -> Result<WatchHandle, FtlError> {
+        if self.m_assets_load_method != FtlLoadMethod::FileSystem || self.m_assets_resolver.is_some() {
+            return Err(FtlError::UnsupportedLoadMethod);
+        }
+        let stopped = Arc::new(AtomicBool::new(false));
+        let mut watcher = FtlWatcher {
+            m_source: self.m_assets_source.clone(),
+            m_locale_to_path_components: self.m_locale_to_path_components.clone(),
+            m_sources: self.m_sources.clone(),
+            m_stale_locales: self.m_stale_locales.clone(),
+            m_added_sources: self.m_added_sources.clone(),
+            m_locale_observers: self.m_locale_observers.clone(),
+            m_rejected: hashmap![],
+            m_pending: hashmap![],
+        };
+        let interval = self.m_assets_watch_interval;
+        let watcher_stopped = stopped.clone();
+        std::thread::spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                if watcher_stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                watcher.poll();
+            }
+        });
+        Ok(WatchHandle { m_stopped: stopped })
+    }
+
+    /// Rebuilds the bundles of the locales changed by `watch()`.
+    fn reload_stale_bundles(&self) {
+        let stale: Vec<Locale> = self.m_stale_locales.write().unwrap().drain().collect();
+        for locale in stale {
+            let Some(sources) = self.m_sources.read().unwrap().get(&locale).cloned() else {
+                continue;
+            };
+            match self.build_bundle(&locale, sources) {
+                Ok(loaded) => {
+                    self.m_assets.write().unwrap().insert(locale.clone(), loaded.m_bundle);
+                    self.m_message_ids.write().unwrap().insert(locale.clone(), loaded.m_message_ids);
+                    self.m_plain_messages.write().unwrap().insert(locale, loaded.m_plain_messages);
+                },
+                Err(error) => println!("{}", error),
+            }
+        }
+    }
+
+    /// Clears the parsed resources that are shared by every `Ftl` object,
+    /// so that the next loads read, fetch and parse every resource again.
+    ///
+    /// Resources are cached by path or URL and reused while their source
+    /// is unchanged. Files are not read again while their modification time
+    /// is unchanged, and resources fetched through HTTP are revalidated
+    /// through their `ETag` header, if any. Resources that no bundle uses
+    /// anymore are evicted when a locale is unloaded.
+    pub fn clear_cache() {
+        RESOURCE_CACHE.lock().unwrap().clear();
+    }
+
+    /// Adds a function that messages can call, such as
+    /// `{ ASSET_NAME($id) }`, in every locale. The function receives the
+    /// positional and named arguments of the call; if any positional
+    /// argument fails to resolve, the call fails without calling it.
+    ///
+    /// Functions apply to locales loaded after they are added, so they
+    /// should be added before loading. Adding a function with the name
+    /// of a previously added function replaces it, and adding a function
+    /// with the name of a built-in function, such as `NUMBER`, `DATETIME`
+    /// or `ORDINAL`, fails with `FtlError::ReservedFunction`.
+    ///
+    /// ```ignore
+    /// ftl.add_function("ASSET_NAME", |positional, _named| {
+    ///     match positional.first() {
+    ///         Some(FtlValue::String(id)) => FtlValue::String(asset_name(id)),
+    ///         _ => FtlValue::String("?".to_owned()),
+    ///     }
+    /// })?;
+    /// ```
+    pub fn add_function(&self, name: &str, function: impl Fn(&[FtlValue], &FtlArgs) -> FtlValue + Send + Sync + 'static) -> Result<(), FtlError> {
+        if BUILTIN_FUNCTIONS.contains(&name) {
+            return Err(FtlError::ReservedFunction(name.to_owned()));
+        }
+        self.m_functions.write().unwrap().insert(name.to_owned(), Arc::new(function));
+        Ok(())
+    }
+
+    /// Attempts to load a locale and its fallbacks.
+    /// If the locale argument is specified, it is loaded.
+    /// Otherwise, if there is a default locale, it is loaded, and if not,
+    /// the method panics.
+    ///
+    /// If any resource fails to load, the method returns `false`, otherwise `true`.
+    pub async fn load(&self, mut new_locale: Option<Locale>) -> bool {
+        if new_locale.is_none() {
+            new_locale = Some(self.m_default_locale.clone());
+        }
+        let new_locale = new_locale.unwrap();
+        if !self.supports_locale(&new_locale) {
+            panic!("Unsupported locale: {}", new_locale);
+        }
+        let mut to_load: HashSet<Locale> = hashset![new_locale.clone()];
+        self.enumerate_fallbacks(new_locale.clone(), &mut to_load);
+
+        let new_assets = match self.load_bundles(to_load).await {
+            Ok(new_assets) => new_assets,
+            Err(error) => {
+                println!("{}", error);
+                return false;
+            },
+        };
+        if self.m_assets_clean_unused {
+            self.m_assets.write().unwrap().clear();
+            self.m_message_ids.write().unwrap().clear();
+            self.m_plain_messages.write().unwrap().clear();
+            self.m_sources.write().unwrap().clear();
+        }
+        for e in self.insert_bundles(new_assets) {
+            println!("Syntax error at {}", e);
+        }
+        self.change_current_locale(new_locale.clone());
+        for c in self.m_locale_initializers.read().unwrap().iter() {
+            c(new_locale.clone(), self.m_assets.read().unwrap()[&new_locale.clone()].clone());
+        }
+
+        true
+    }
+
+    /// Loads a supported locale and its fallbacks, without changing the
+    /// current locale nor cleaning previously loaded locales. The returned
+    /// future completes once every resource has been read or fetched.
+    ///
+    /// Returns the syntax errors of the resources, whose valid entries are
+    /// still loaded, unless the `strict` option is enabled, in which case
+    /// syntax errors fail with `FtlError::Parse`.
+    pub async fn load_locale(&self, locale: &str) -> Result<Vec<FtlParseError>, FtlError> {
+        let locale = Locale::try_from_bytes(locale.as_bytes()).ok()
+            .filter(|locale| self.supports_locale(locale))
+            .ok_or_else(|| FtlError::UnsupportedLocale(locale.to_owned()))?;
+        let mut to_load: HashSet<Locale> = hashset![locale.clone()];
+        self.enumerate_fallbacks(locale.clone(), &mut to_load);
+        let new_assets = self.load_bundles(to_load).await?;
+        let parse_errors = self.insert_bundles(new_assets);
+        for c in self.m_locale_initializers.read().unwrap().iter() {
+            c(locale.clone(), self.m_assets.read().unwrap()[&locale].clone());
+        }
+        Ok(parse_errors)
+    }
+
+    /// Loads every supported locale, without changing the current locale.
+    /// Fails with the first error, in which case no locale is loaded.
+    ///
+    /// With the `lazy` option, only the default locale, the current locale
+    /// and their fallbacks are loaded, and other locales are loaded when
+    /// selected through `switch_locale()`.
+    ///
+    /// Returns the syntax errors of the resources, as `load_locale()` does.
+    pub async fn load_all(&self) -> Result<Vec<FtlParseError>, FtlError> {
+        self.load_all_with_progress(|_, _| {}).await
+    }
+
+    /// Loads locales as `load_all()` does, calling `on_progress` with the
+    /// number of locales loaded so far and the number of locales to load
+    /// each time a locale finishes loading, such as for a progress bar.
+    ///
+    /// `on_progress` is not required to be `Send`; the returned future
+    /// is not `Send` either way, so it runs on a single-threaded executor
+    /// or a local task set.
+    pub async fn load_all_with_progress(&self, mut on_progress: impl FnMut(usize, usize)) -> Result<Vec<FtlParseError>, FtlError> {
+        let to_load: HashSet<Locale> = if self.m_lazy {
+            let mut to_load = hashset![self.m_default_locale.clone()];
+            self.enumerate_fallbacks(self.m_default_locale.clone(), &mut to_load);
+            if let Some(locale) = self.current_locale_id() {
+                self.enumerate_fallbacks(locale.clone(), &mut to_load);
+                to_load.insert(locale);
+            }
+            to_load
+        } else {
+            self.m_supported_locales.as_ref().clone()
+        };
+        let total = to_load.len();
+        let mut new_assets = hashmap![];
+        for locale in to_load.iter() {
+            new_assets.insert(locale.clone(), self.load_single_locale(locale).await?);
+            on_progress(new_assets.len(), total);
+        }
+        let parse_errors = self.insert_bundles(new_assets);
+        for locale in to_load {
+            for c in self.m_locale_initializers.read().unwrap().iter() {
+                c(locale.clone(), self.m_assets.read().unwrap()[&locale].clone());
+            }
+        }
+        Ok(parse_errors)
+    }
+
+    /// Loads every supported locale, regardless of the `lazy` option and
+    /// without changing the current locale. Unlike `load_all()`, a locale
+    /// that fails to load does not prevent the others from being loaded;
+    /// its error is returned along with the locale, in the form in which
+    /// it appears in the `supported_locales` option.
+    ///
+    /// Syntax errors are printed, unless the `strict` option is enabled,
+    /// in which case they fail the locale with `FtlError::Parse`. Fails
+    /// only if the default locale cannot be loaded.
+    pub async fn preload_all(&self) -> Result<Vec<(String, FtlError)>, FtlError> {
+        let mut failures = vec![];
+        let mut new_assets = hashmap![];
+        for locale in self.m_supported_locales.iter() {
+            match self.load_single_locale(locale).await {
+                Ok(loaded) => {
+                    new_assets.insert(locale.clone(), loaded);
+                },
+                Err(error) if *locale == self.m_default_locale => return Err(error),
+                Err(error) => failures.push((self.locale_to_string(locale), error)),
+            }
+        }
+        let loaded: Vec<Locale> = new_assets.keys().cloned().collect();
+        for e in self.insert_bundles(new_assets) {
+            println!("Syntax error at {}", e);
+        }
+        for locale in loaded {
+            for c in self.m_locale_initializers.read().unwrap().iter() {
+                c(locale.clone(), self.m_assets.read().unwrap()[&locale].clone());
+            }
+        }
+        failures.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(failures)
+    }
+
+    /// Sets the current locale as `set_locale()` does, first loading the
+    /// negotiated locale and its fallbacks that are not loaded yet, without
+    /// cleaning previously loaded locales. Returns the negotiated locale,
+    /// in the form in which it appears in the `supported_locales` option.
+    ///
+    /// Syntax errors are printed, unless the `strict` option is enabled,
+    /// in which case they fail with `FtlError::Parse`. On failure, the
+    /// current locale is not changed.
+    pub async fn switch_locale(&self, locale: &str) -> Result<String, FtlError> {
+        let negotiated = self.negotiate_or_default(locale);
+        let to_load: HashSet<Locale> = {
+            let assets = self.m_assets.read().unwrap();
+            self.locale_chain(&negotiated).into_iter().filter(|locale| !assets.contains_key(locale)).collect()
+        };
+        if !to_load.is_empty() {
+            let new_assets = self.load_bundles(to_load.clone()).await?;
+            for e in self.insert_bundles(new_assets) {
+                println!("Syntax error at {}", e);
+            }
+            for locale in to_load {
+                for c in self.m_locale_initializers.read().unwrap().iter() {
+                    c(locale.clone(), self.m_assets.read().unwrap()[&locale].clone());
+                }
+            }
+        }
+        self.change_current_locale(negotiated.clone());
+        Ok(self.locale_to_string(&negotiated))
+    }
+
+    /// Unloads a supported locale, freeing its bundle and resources. The
+    /// locale is loaded again when selected through `switch_locale()` or
+    /// `load()`. Unloading a locale that is not loaded does nothing.
+    ///
+    /// Cached resources that are no longer used by the bundle of any
+    /// `Ftl` object are evicted from the cache (see `clear_cache()`).
+    ///
+    /// Fails with `FtlError::LocaleInUse` for the current locale, its
+    /// fallbacks and the default locale.
+    pub fn unload_locale(&self, locale: &str) -> Result<(), FtlError> {
+        let locale_id = Locale::try_from_bytes(locale.as_bytes()).ok()
+            .filter(|locale| self.supports_locale(locale))
+            .ok_or_else(|| FtlError::UnsupportedLocale(locale.to_owned()))?;
+        let in_use = match self.current_locale_id() {
+            Some(current) => self.locale_chain(&current),
+            None => vec![self.m_default_locale.clone()],
+        };
+        if in_use.contains(&locale_id) || locale_id == self.m_default_locale {
+            return Err(FtlError::LocaleInUse(self.locale_to_string(&locale_id)));
+        }
+        self.m_assets.write().unwrap().remove(&locale_id);
+        self.m_message_ids.write().unwrap().remove(&locale_id);
+        self.m_plain_messages.write().unwrap().remove(&locale_id);
+        self.m_sources.write().unwrap().remove(&locale_id);
+        self.m_stale_locales.write().unwrap().remove(&locale_id);
+        RESOURCE_CACHE.lock().unwrap().retain(|_, cached| Arc::strong_count(&cached.m_resource) > 1);
+        Ok(())
+    }
+
+    /// Adds a resource to a supported locale from its source, for
+    /// generated messages or tests. A resource with the file name of
+    /// a previously added or loaded resource replaces it.
+    ///
+    /// If the locale is loaded, its bundle is rebuilt; otherwise, the
+    /// resource is included when the locale is loaded. With
+    /// `FtlLoadMethod::Memory`, locales consist only of added resources.
+    ///
+    /// Returns the syntax errors of the resource, as `load_locale()` does.
+    pub fn add_source(&self, locale: &str, file: &str, source: impl Into<String>) -> Result<Vec<FtlParseError>, FtlError> {
+        let locale_id = Locale::try_from_bytes(locale.as_bytes()).ok()
+            .filter(|locale| self.supports_locale(locale))
+            .ok_or_else(|| FtlError::UnsupportedLocale(locale.to_owned()))?;
+        let source = source.into();
+        let locale_path_comp = self.locale_to_string(&locale_id);
+        let res_path = format!("{}/{}/{}.ftl", self.m_assets_source, locale_path_comp, file);
+        let loaded_sources = self.m_sources.read().unwrap().get(&locale_id).cloned();
+        let parse_errors = if let Some(mut sources) = loaded_sources {
+            set_locale_source(&mut sources, file, &source);
+            let loaded = self.build_bundle(&locale_id, sources)?;
+            self.m_assets.write().unwrap().insert(locale_id.clone(), loaded.m_bundle);
+            self.m_message_ids.write().unwrap().insert(locale_id.clone(), loaded.m_message_ids);
+            self.m_plain_messages.write().unwrap().insert(locale_id.clone(), loaded.m_plain_messages);
+            self.m_sources.write().unwrap().insert(locale_id.clone(), loaded.m_sources);
+            loaded.m_parse_errors.into_iter().filter(|e| e.file == res_path).collect()
+        } else {
+            let parse_errors: Vec<FtlParseError> = match fluent::FluentResource::try_new(source.clone()) {
+                Ok(_) => vec![],
+                Err((_, errors)) => errors.iter().map(|e| FtlParseError::new(&locale_path_comp, &res_path, &source, e)).collect(),
+            };
+            if self.m_assets_strict && !parse_errors.is_empty() {
+                return Err(FtlError::Parse(parse_errors));
+            }
+            parse_errors
+        };
+        let mut added_sources = self.m_added_sources.write().unwrap();
+        set_locale_source(added_sources.entry(locale_id).or_default(), file, &source);
+        Ok(parse_errors)
+    }
+
+    async fn load_bundles(&self, locales: HashSet<Locale>) -> Result<HashMap<Locale, LoadedLocale>, FtlError> {
+        let mut r: HashMap<Locale, LoadedLocale> = hashmap![];
+        for locale in locales {
+            let bundle = self.load_single_locale(&locale).await?;
+            r.insert(locale, bundle);
+        }
+        Ok(r)
+    }
+
+    /// Stores loaded locales, returning their syntax errors.
+    fn insert_bundles(&self, new_assets: HashMap<Locale, LoadedLocale>) -> Vec<FtlParseError> {
+        let mut assets = self.m_assets.write().unwrap();
+        let mut message_ids = self.m_message_ids.write().unwrap();
+        let mut plain_messages = self.m_plain_messages.write().unwrap();
+        let mut sources = self.m_sources.write().unwrap();
+        let mut parse_errors = vec![];
+        for (locale, loaded) in new_assets {
+            assets.insert(locale.clone(), loaded.m_bundle);
+            message_ids.insert(locale.clone(), loaded.m_message_ids);
+            plain_messages.insert(locale.clone(), loaded.m_plain_messages);
+            sources.insert(locale, loaded.m_sources);
+            parse_errors.extend(loaded.m_parse_errors);
+        }
+        parse_errors.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+        parse_errors
+    }
+
+    /// Reads or fetches the resources of a locale and builds its bundle.
+    async fn load_single_locale(&self, locale: &Locale) -> Result<LoadedLocale, FtlError> {
+        let locale_path_comp = self.m_locale_to_path_components.get(locale);
+        if locale_path_comp.is_none() {
+            panic!("Fallback is not supported a locale: {}", locale);
+        }
+        let locale_path_comp = locale_path_comp.unwrap();
+        let zip_archive = if self.m_assets_load_method == FtlLoadMethod::ZipArchive {
+            Some(self.open_zip_archive(locale_path_comp)?)
+        } else {
+            None
+        };
+        let mut sources = vec![];
+        for file_name in self.m_assets_files.iter() {
+            let res_path = format!("{}/{}/{}.ftl", self.m_assets_source, locale_path_comp, file_name);
+            let source = match self.m_assets_load_method {
+                FtlLoadMethod::FileSystem if self.m_assets_resolver.is_some() => self.read_with_resolver(&res_path, locale_path_comp).await?,
+                FtlLoadMethod::FileSystem => {
+                    // A resource may be stored compressed as `.ftl.gz`.
+                    let file = rialight_filesystem::File::new(res_path.clone());
+                    let gz_file = rialight_filesystem::File::new(format!("{}.gz", res_path));
+                    let compressed = !file.exists() && gz_file.exists();
+                    let file = if compressed { gz_file } else { file };
+                    // Files that were not modified since they were cached are not read again.
+                    let modified = file.modification_date().ok().map(SystemTime::from);
+                    if let Some(source) = modified.and_then(|modified| cached_source(&res_path, |cached| cached.m_modified == Some(modified))) {
+                        source
+                    } else {
+                        let source = file.read_bytes();
+                        let source = source.ok()
+                            .and_then(|source| if compressed { gunzip(&source) } else { Some(source) })
+                            .and_then(|source| String::from_utf8(source).ok());
+                        if source.is_none() {
+                            return Err(FtlError::ResourceNotFound {
+                                locale: locale_path_comp.clone(),
+                                path: res_path,
+                            });
+                        }
+                        let source = source.unwrap();
+                        parse_cached_resource(&res_path, &source, modified, None);
+                        source
+                    }
+                },
+                FtlLoadMethod::Http => self.fetch_http(&res_path, locale_path_comp).await?,
+                FtlLoadMethod::Embedded => {
+                    let source = self.m_assets_embedded.get(&(locale_path_comp.clone(), file_name.clone()));
+                    if source.is_none() {
+                        return Err(FtlError::ResourceNotFound {
+                            locale: locale_path_comp.clone(),
+                            path: format!("{}/{}.ftl", locale_path_comp, file_name),
+                        });
+                    }
+                    source.unwrap().to_string()
+                },
+                FtlLoadMethod::ZipArchive => {
+                    let entry_name = format!("{}/{}.ftl", locale_path_comp, file_name);
+                    self.read_zip_entry(zip_archive.as_ref().unwrap(), &entry_name, locale_path_comp)?
+                },
+                FtlLoadMethod::Memory => continue,
+            };
+            sources.push((file_name.clone(), source));
+        }
+        if let Some(added_sources) = self.m_added_sources.read().unwrap().get(locale) {
+            for (file_name, source) in added_sources.iter() {
+                set_locale_source(&mut sources, file_name, source);
+            }
+        }
+        self.build_bundle(locale, sources)
+    }
+
+    /// Reads a resource through the `resolver` option, or the resource
+    /// compressed as `.ftl.gz` if it cannot be read.
+    async fn read_with_resolver(&self, res_path: &str, locale: &str) -> Result<String, FtlError> {
+        let resolver = self.m_assets_resolver.as_ref().unwrap();
+        let source = match resolver.read(res_path).await {
+            Ok(source) => Some(source),
+            Err(_) => resolver.read(&format!("{}.gz", res_path)).await.ok().and_then(|source| gunzip(&source)),
+        };
+        source.and_then(|source| String::from_utf8(source).ok()).ok_or_else(|| FtlError::ResourceNotFound {
+            locale: locale.to_owned(),
+            path: res_path.to_owned(),
+        })
+    }
+
+    /// Opens the archive given by the `zip_archive` option, reusing the
+    /// archive opened by a previous load unless its file was modified.
+    fn open_zip_archive(&self, locale: &str) -> Result<Arc<Mutex<ZipArchive>>, FtlError> {
+        let modified = match self.m_assets_zip_archive.as_ref() {
+            Some(FtlZipArchive::Path(path)) => rialight_filesystem::File::new(path.clone()).modification_date().ok().map(SystemTime::from),
+            _ => None,
+        };
+        let mut opened = self.m_opened_zip_archive.lock().unwrap();
+        if let Some((_, archive)) = opened.as_ref().filter(|(opened_modified, _)| *opened_modified == modified) {
+            return Ok(archive.clone());
+        }
+        let data = self.read_zip_archive().ok_or_else(|| FtlError::ResourceNotFound {
+            locale: locale.to_owned(),
+            path: self.zip_archive_path(),
+        })?;
+        let archive = ZipArchive::new(Cursor::new(data)).map_err(|error| FtlError::ZipArchive {
+            locale: locale.to_owned(),
+            path: self.zip_archive_path(),
+            message: error.to_string(),
+        })?;
+        let archive = Arc::new(Mutex::new(archive));
+        *opened = Some((modified, archive.clone()));
+        Ok(archive)
+    }
+
+    /// Reads a resource from the archive opened by `open_zip_archive()`.
+    fn read_zip_entry(&self, archive: &Mutex<ZipArchive>, name: &str, locale: &str) -> Result<String, FtlError> {
+        let error = |message: String| FtlError::ZipArchive {
+            locale: locale.to_owned(),
+            path: self.zip_archive_path(),
+            message: format!("{}: {}", name, message),
+        };
+        let mut archive = archive.lock().unwrap();
+        let entry = match archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(zip::result::ZipError::FileNotFound) => return Err(FtlError::ResourceNotFound {
+                locale: locale.to_owned(),
+                path: name.to_owned(),
+            }),
+            Err(e) => return Err(error(e.to_string())),
+        };
+        // The entry's checksum is verified once it is read to the end.
+        let mut source = String::new();
+        entry.take(MAX_DECOMPRESSED_SIZE + 1).read_to_string(&mut source).map_err(|e| error(e.to_string()))?;
+        if source.len() as u64 > MAX_DECOMPRESSED_SIZE {
+            return Err(error("decompressed size exceeds the limit".to_owned()));
+        }
+        Ok(source)
+    }
+
+    /// Reads the archive given by the `zip_archive` option.
+    fn read_zip_archive(&self) -> Option<Cow<'static, [u8]>> {
+        match self.m_assets_zip_archive.as_ref() {
+            Some(FtlZipArchive::Path(path)) => rialight_filesystem::File::new(path.clone()).read_bytes().ok().map(Cow::Owned),
+            Some(FtlZipArchive::Bytes(bytes)) => Some(Cow::Borrowed(*bytes)),
+            None => None,
+        }
+    }
+
+    /// Returns the path of the archive given by the `zip_archive` option,
+    /// for errors.
+    fn zip_archive_path(&self) -> String {
+        match self.m_assets_zip_archive.as_ref() {
+            Some(FtlZipArchive::Path(path)) => path.clone(),
+            _ => "<zip archive>".to_owned(),
+        }
+    }
+
+    /// Builds the bundle of a locale from its resources, given as
+    /// file names and sources.
+    fn build_bundle(&self, locale: &Locale, sources: LocaleSources) -> Result<LoadedLocale, FtlError> {
+        let mut r = fluent::concurrent::FluentBundle::new_concurrent(vec![locale_to_unic_langid_impl_langid(locale)]);
+        r.set_formatter(Some(format_fluent_value));
+        r.set_use_isolating(self.m_use_isolating);
+        if let Some(style) = *self.m_pseudo_style.read().unwrap() {
+            r.set_transform(Some(style.transform()));
+        }
+        let ordinal_locale = locale.clone();
+        r.add_function("ORDINAL", move |positional, _| {
+            match positional.first().and_then(fluent_value_to_decimal) {
+                Some(n) => fluent::FluentValue::from(plural_category_keyword(ordinal_category(&ordinal_locale, &n))),
+                None => fluent::FluentValue::Error,
+            }
+        }).unwrap();
+        let datetime_locale = locale.clone();
+        r.add_function("DATETIME", move |positional, named| format_datetime_function(&datetime_locale, positional, named)).unwrap();
+        for (name, function) in self.m_functions.read().unwrap().iter() {
+            let function = function.clone();
+            r.add_function(name, move |positional, named| {
+                let positional: Option<Vec<FtlValue>> = positional.iter().map(FtlValue::from_fluent_value).collect();
+                match positional {
+                    Some(positional) => function(&positional, &FtlArgs::from_fluent_args(named)).into_fluent_value(),
+                    None => fluent::FluentValue::Error,
+                }
+            }).unwrap();
+        }
+        let locale_path_comp = self.locale_to_string(locale);
+        let mut message_ids = vec![];
+        let mut plain_messages = PlainMessages::new();
+        let mut parse_errors = vec![];
+        for (file_name, source) in sources.iter() {
+            let res_path = format!("{}/{}/{}.ftl", self.m_assets_source, locale_path_comp, file_name);
+            let (res, errors) = parse_cached_resource(&res_path, source, None, None);
+            parse_errors.extend(errors.iter().map(|e| FtlParseError::new(&locale_path_comp, &res_path, source, e)));
+            self.collect_plain_messages(&res, &mut plain_messages);
+            if !add_ftl_bundle_resource(file_name, res, &mut r, &mut message_ids, self.m_assets_strict_duplicates) {
+                return Err(FtlError::InvalidResource {
+                    path: res_path,
+                    locale: locale_path_comp,
+                });
+            }
+        }
+        if self.m_assets_strict && !parse_errors.is_empty() {
+            return Err(FtlError::Parse(parse_errors));
+        }
+        message_ids.sort();
+        message_ids.dedup();
+        Ok(LoadedLocale {
+            m_bundle: Arc::new(r),
+            m_message_ids: message_ids,
+            m_plain_messages: plain_messages,
+            m_sources: sources,
+            m_parse_errors: parse_errors,
+        })
+    }
+
+    /// Collects the values of the messages of a resource that are plain
+    /// text. Messages that are not replace the ones of previous resources.
+    fn collect_plain_messages(&self, res: &fluent::FluentResource, plain_messages: &mut PlainMessages) {
+        for entry in res.entries() {
+            let fluent_syntax::ast::Entry::Message(message) = entry else {
+                continue;
+            };
+            match message.value.as_ref().map(|pattern| pattern.elements.as_slice()) {
+                Some([fluent_syntax::ast::PatternElement::TextElement { value }]) => {
+                    let value = if self.m_trim_message_whitespace { value.trim_ascii() } else { *value };
+                    plain_messages.insert(message.id.name.to_owned(), Arc::from(value));
+                },
+                _ => {
+                    plain_messages.remove(message.id.name);
+                },
+            }
+        }
+    }
+
+    /// Fetches a resource through HTTP, retrying with exponential
+    /// backoff on connection errors, timeouts and server errors.
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown", feature = "web")))]
+    async fn fetch_http(&self, url: &str, locale: &str) -> Result<String, FtlError> {
+        let error = |message: String| FtlError::Http {
+            url: url.to_owned(),
+            locale: locale.to_owned(),
+            message,
+        };
+        let parsed_url = reqwest::Url::parse(url).map_err(|e| error(e.to_string()))?;
+        let mut backoff = self.m_assets_http_retry_backoff;
+        let mut attempt = 0;
+        // A cached resource is revalidated through its entity tag.
+        let etag = RESOURCE_CACHE.lock().unwrap().get(url).and_then(|cached| cached.m_etag.clone());
+        loop {
+            let mut request = self.m_http_client.get(parsed_url.clone());
+            if let Some(etag) = etag.as_ref() {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
             }
-        },
-        Err((_, error_list)) => {
-            for e in error_list {
-                println!("Syntax error at {}.ftl: {}", file_name, e);
+            let result = match request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    let cached = if status == reqwest::StatusCode::NOT_MODIFIED {
+                        cached_source(url, |cached| cached.m_etag == etag)
+                    } else {
+                        None
+                    };
+                    if let Some(source) = cached {
+                        Ok(source)
+                    } else if status.is_success() {
+                        let response_etag = response.headers().get(reqwest::header::ETAG).and_then(|etag| etag.to_str().ok()).map(|etag| etag.to_owned());
+                        let encoding = response.headers().get(reqwest::header::CONTENT_ENCODING).and_then(|encoding| encoding.to_str().ok()).map(|encoding| encoding.trim().to_ascii_lowercase());
+                        let source = match encoding.as_deref() {
+                            None | Some("identity") => response.text().await.map_err(|e| (true, e.to_string())),
+                            Some(encoding) => match response.bytes().await {
+                                Ok(body) => decode_content(encoding, &body).map_err(|message| (false, message)),
+                                Err(e) => Err((true, e.to_string())),
+                            },
+                        };
+                        if let (Ok(source), Some(response_etag)) = (&source, response_etag) {
+                            parse_cached_resource(url, source, None, Some(response_etag));
+                        }
+                        source
+                    } else {
+                        let retry = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                        Err((retry, format!("HTTP status {}", status)))
+                    }
+                },
+                Err(e) => Err((true, e.to_string())),
+            };
+            match result {
+                Ok(source) => return Ok(source),
+                Err((retry, message)) => {
+                    if !retry || attempt >= self.m_assets_http_max_retries {
+                        return Err(error(message));
+                    }
+                },
             }
-            return false;
-        },
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            attempt += 1;
+        }
     }
-    true
-}
 
-impl Ftl {
-    /// Constructs a `Ftl` object.
-    pub fn new(options: &mut FtlOptions) -> Self {
-        let mut locale_to_path_components = HashMap::<Locale, String>::new();
-        let mut supported_locales = HashSet::<Locale>::new();
-        for unparsed_locale in options.m_supported_locales.get_mut().unwrap().iter() {
-            let parsed_locale = parse_locale_or_panic(unparsed_locale);
-            locale_to_path_components.insert(parsed_locale.clone(), unparsed_locale.clone());
-            supported_locales.insert(parsed_locale);
-        }
-        let mut fallbacks = HashMap::<Locale, Vec<Locale>>::new();
-        for (k, v) in options.m_fallbacks.get_mut().unwrap().iter() {
-            fallbacks.insert(parse_locale_or_panic(k), v.iter().map(|s| parse_locale_or_panic(s)).collect());
-        }
-        let default_locale = options.m_default_locale.get_mut().unwrap().clone();
-        Self {
-            m_current_locale: RwLock::new(None),
-            m_locale_to_path_components: Arc::new(locale_to_path_components),
-            m_supported_locales: Arc::new(supported_locales),
-            m_default_locale: parse_locale_or_panic(&default_locale),
-            m_fallbacks: Arc::new(fallbacks),
-            m_locale_initializers: Arc::new(RwLock::new(vec![])),
-            m_assets: Arc::new(RwLock::new(HashMap::new())),
-            m_assets_source: options.m_assets.get_mut().unwrap().m_source.get_mut().unwrap().clone(),
-            m_assets_files: options.m_assets.get_mut().unwrap().m_files.get_mut().unwrap().iter().map(|s| s.clone()).collect(),
-            m_assets_clean_unused: options.m_assets.get_mut().unwrap().m_clean_unused.get(),
-            m_assets_load_method: options.m_assets.get_mut().unwrap().m_load_method.get(),
+    /// Fetches a resource through the browser's `fetch` API, retrying as
+    /// the native loader does. `app://` URLs and relative paths are resolved
+    /// against the page. Cached resources are revalidated by the browser,
+    /// and the `http_timeout` and `http_base_headers` options are ignored.
+    #[cfg(all(target_family = "wasm", target_os = "unknown", feature = "web"))]
+    async fn fetch_http(&self, url: &str, locale: &str) -> Result<String, FtlError> {
+        use wasm_bindgen::JsCast;
+        use wasm_bindgen_futures::JsFuture;
+        let error = |message: String| FtlError::Http {
+            url: url.to_owned(),
+            locale: locale.to_owned(),
+            message,
+        };
+        let js_error = |value: wasm_bindgen::JsValue| format!("{:?}", value);
+        let window = web_sys::window().ok_or_else(|| error("no window".to_owned()))?;
+        let resolved_url = resolve_browser_url(&window, url).map_err(|e| error(js_error(e)))?;
+        let mut backoff = self.m_assets_http_retry_backoff;
+        let mut attempt = 0;
+        loop {
+            let result = match JsFuture::from(window.fetch_with_str(&resolved_url)).await {
+                Ok(response) => {
+                    let response: web_sys::Response = response.unchecked_into();
+                    if response.ok() {
+                        match response.text() {
+                            Ok(text) => JsFuture::from(text).await
+                                .map(|text| text.as_string().unwrap_or_default())
+                                .map_err(|e| (true, js_error(e))),
+                            Err(e) => Err((true, js_error(e))),
+                        }
+                    } else {
+                        let status = response.status();
+                        Err((status >= 500 || status == 429, format!("HTTP status {}", status)))
+                    }
+                },
+                Err(e) => Err((true, js_error(e))),
+            };
+            match result {
+                Ok(source) => return Ok(source),
+                Err((retry, message)) => {
+                    if !retry || attempt >= self.m_assets_http_max_retries {
+                        return Err(error(message));
+                    }
+                },
+            }
+            browser_sleep(&window, backoff).await;
+            backoff *= 2;
+            attempt += 1;
         }
     }
 
-    /// Returns a set of supported locales, reflecting
-    /// the ones that were specified when constructing the `Ftl` object.
-    pub fn supported_locales(&self) -> HashSet<Locale> {
-        self.m_supported_locales.as_ref().clone()
+    fn enumerate_fallbacks(&self, locale: Locale, output: &mut HashSet<Locale>) {
+        output.extend(self.locale_chain(&locale).into_iter().skip(1));
     }
 
-    /// Returns `true` if the locale is one of the supported locales
-    /// that were specified when constructing the `Ftl` object,
-    /// otherwise `false`.
-    pub fn supports_locale(&self, arg: &Locale) -> bool {
-        self.m_supported_locales.contains(arg)
+    /// Returns a supported locale followed by its fallbacks, in the order
+    /// in which messages are resolved, in the form in which the locales
+    /// appear in the `supported_locales` option. The fallbacks of each
+    /// locale, or its supported parent locale if it has no fallbacks, are
+    /// tried depth-first and the default locale is tried last.
+    /// A locale that appears again in the chain, as in `a → b → a`,
+    /// is skipped.
+    ///
+    /// Returns an empty list if the locale is malformed or unsupported.
+    pub fn fallback_chain(&self, locale: &str) -> Vec<String> {
+        let Some(locale) = Locale::try_from_bytes(locale.as_bytes()).ok().filter(|locale| self.supports_locale(locale)) else {
+            return vec![];
+        };
+        self.locale_chain(&locale).iter().map(|locale| self.locale_to_string(locale)).collect()
     }
 
-    /// Returns the currently loaded locale.
-    pub fn current_locale(&self) -> Option<Locale> {
-        self.m_current_locale.read().unwrap().clone()
+    fn locale_chain(&self, locale: &Locale) -> Vec<Locale> {
+        let mut chain = vec![];
+        self.push_locale_chain(locale, &mut chain);
+        if self.supports_locale(&self.m_default_locale) && !chain.contains(&self.m_default_locale) {
+            chain.push(self.m_default_locale.clone());
+        }
+        chain
     }
 
-    /// Returns the currently loaded locale followed by its fallbacks or empty if no locale is loaded.
-    pub fn locale_and_fallbacks(&self) -> HashSet<Locale> {
-        if let Some(c) = self.current_locale() {
-            let mut r: HashSet<Locale> = hashset![c.clone()];
-            self.enumerate_fallbacks(c.clone(), &mut r);
-            return r;
+    fn push_locale_chain(&self, locale: &Locale, chain: &mut Vec<Locale>) {
+        if chain.contains(locale) {
+            return;
+        }
+        chain.push(locale.clone());
+        match self.m_fallbacks.get(locale) {
+            Some(fallbacks) => for fl in fallbacks.iter() {
+                self.push_locale_chain(fl, chain);
+            },
+            None => if let Some(parent) = parent_locale(locale).filter(|parent| self.supports_locale(parent)) {
+                self.push_locale_chain(&parent, chain);
+            },
         }
-        hashset![]
     }
 
-    /// Returns the currently loaded fallbacks.
-    pub fn fallbacks(&self) -> HashSet<Locale> {
-        if let Some(c) = self.current_locale() {
-            let mut r: HashSet<Locale> = hashset![];
-            self.enumerate_fallbacks(c.clone(), &mut r);
-            return r;
+    /// Sets an argument that is given to every message, such as the name of
+    /// the player or the version of the application, replacing any previous
+    /// global argument of the same name. Arguments given to `format()` and
+    /// similar methods override global arguments of the same name.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # fn f(ftl: &Ftl) {
+    /// ftl.set_global_arg("appVersion", "1.2.4");
+    /// # }
+    /// ```
+    pub fn set_global_arg(&self, name: &str, value: impl Into<FtlValue>) {
+        self.m_global_args.write().unwrap().set(name, value);
+    }
+
+    /// Returns the global arguments overridden by the given arguments.
+    fn with_global_args<'a>(&self, args: &'a FtlArgs) -> Cow<'a, FtlArgs> {
+        let global_args = self.m_global_args.read().unwrap();
+        if global_args.m_args.is_empty() {
+            return Cow::Borrowed(args);
         }
-        hashset![]
+        let mut r = global_args.clone();
+        for (k, v) in args.m_args.iter() {
+            r.set(k, v.clone());
+        }
+        Cow::Owned(r)
     }
 
-    /// Adds a callback function to initialize the `FluentBundle` object of a locale.
-    /// The callback is called when the locale is loaded.
-    pub fn initialize_locale(&self, callback: fn(Locale, Arc<fluent::FluentBundle<fluent::FluentResource>>)) {
-        self.m_locale_initializers.write().unwrap().push(callback);
+    /// Returns the value of a message without arguments other than the
+    /// global ones, resolved in the current locale or in its fallbacks. Returns `None` if no locale is
+    /// loaded. A message that does not exist is handled according to the
+    /// `on_missing` option.
+    ///
+    /// Terms, whose IDs start with `-`, are private to the FTL resources:
+    /// messages may reference them, but they are not returned by this
+    /// method nor by `format()`.
+    pub fn get_message(&self, id: &str) -> Option<String> {
+        self.reload_stale_bundles();
+        self.record_usage(id);
+        let locale = self.current_locale_id()?;
+        let mut errors = FormatErrors::default();
+        let args = self.m_global_args.read().unwrap().clone();
+        let args = args.to_fluent_args();
+        let Some(r) = self.format_by_locale(id, None, locale, Some(&args), &mut errors) else {
+            return self.missing_message(id);
+        };
+        Some(self.finish_message(r))
     }
 
-    /// Attempts to load a locale and its fallbacks.
-    /// If the locale argument is specified, it is loaded.
-    /// Otherwise, if there is a default locale, it is loaded, and if not,
-    /// the method panics.
+    /// Formats a message with the given arguments, resolved in the
+    /// current locale or in its fallbacks. A message that does not
+    /// exist is handled according to the `on_missing` option.
     ///
-    /// If any resource fails to load, the method returns `false`, otherwise `true`.
-    pub async fn load(&self, mut new_locale: Option<Locale>) -> bool {
-        if new_locale.is_none() {
-            new_locale = Some(self.m_default_locale.clone());
+    /// Numeric arguments are formatted according to the number
+    /// rules of the locale that defines the message, and select
+    /// expressions over numbers match the CLDR cardinal plural
+    /// categories of that locale (`zero`, `one`, `two`, `few`,
+    /// `many` and `other`), given by the ICU plural rules, or its
+    /// ordinal categories for `NUMBER($n, type: "ordinal")`.
+    ///
+    /// ```ignore
+    /// let welcome = ftl.format("welcome", &intl::ftl::ftl_args!{ "name" => "Ada" })?;
+    /// ```
+    pub fn format(&self, id: &str, args: &FtlArgs) -> Result<String, FtlError> {
+        self.reload_stale_bundles();
+        self.record_usage(id);
+        let locale = self.current_locale_id().ok_or(FtlError::NoLocaleLoaded)?;
+        let mut errors = FormatErrors::default();
+        let args = self.with_global_args(args);
+        let args = args.to_fluent_args();
+        let Some(r) = self.format_by_locale(id, None, locale.clone(), Some(&args), &mut errors) else {
+            return self.missing_message(id).ok_or_else(|| FtlError::MessageNotFound(id.to_owned()));
+        };
+        if let Err(error) = check_format_errors(id, &errors) {
+            if !self.m_fallback_on_format_error {
+                return Err(error);
+            }
+            return self.format_error_fallback(id, locale, &args, error);
         }
-        let new_locale = new_locale.unwrap();
-        if !self.supports_locale(&new_locale) {
-            panic!("Unsupported locale: {}", new_locale);
+        Ok(self.finish_message(r))
+    }
+
+    /// Formats a message that failed to format in the given locale in the
+    /// default locale, for the `fallback_on_format_error` option.
+    fn format_error_fallback(&self, id: &str, locale: Locale, args: &Arguments, error: FtlError) -> Result<String, FtlError> {
+        let mut error = error;
+        if locale != self.m_default_locale {
+            println!("{} in {}; falling back to {}", error, self.locale_to_string(&locale), self.locale_to_string(&self.m_default_locale));
+            let mut errors = FormatErrors::default();
+            if let Some(r) = self.format_by_locale(id, None, self.m_default_locale.clone(), Some(args), &mut errors) {
+                match check_format_errors(id, &errors) {
+                    Ok(()) => return Ok(self.finish_message(r)),
+                    Err(e) => error = e,
+                }
+            }
         }
-        let mut to_load: HashSet<Locale> = hashset![new_locale.clone()];
-        self.enumerate_fallbacks(new_locale.clone(), &mut to_load);
+        println!("{}; treating the message as missing", error);
+        self.missing_message(id).ok_or(error)
+    }
 
-        let mut new_assets: HashMap<Locale, Arc<fluent::FluentBundle<fluent::FluentResource>>> = hashmap![];
-        for locale in to_load {
-            let res = self.load_single_locale(&locale).await;
-            if res.is_none() {
-                return false;
+    /// Formats a message as `format()` does, sharing the message with
+    /// the loaded locale if its value is plain text without placeables,
+    /// so that static labels are not allocated on every call. Otherwise,
+    /// and with pseudolocalization, returns the formatted message.
+    ///
+    /// The message is shared through an `Arc` rather than borrowed as a
+    /// `Cow<'_, str>`, because the loaded locales are behind a lock and
+    /// may be rebuilt by `add_source()` or `watch()` while it is in use.
+    pub fn format_shared(&self, id: &str, args: &FtlArgs) -> Result<FtlText, FtlError> {
+        if self.pseudolocalization().is_none() {
+            self.reload_stale_bundles();
+            if let Some(text) = self.static_message(id) {
+                self.record_usage(id);
+                return Ok(FtlText::Shared(text));
             }
-            new_assets.insert(locale.clone(), res.unwrap());
         }
-        if self.m_assets_clean_unused {
-            self.m_assets.write().unwrap().clear();
+        self.format(id, args).map(FtlText::Owned)
+    }
+
+    /// Returns the value of a message that is plain text, resolved in the
+    /// current locale or in its fallbacks.
+    fn static_message(&self, id: &str) -> Option<Arc<str>> {
+        let locale = self.current_locale_id()?;
+        let all_assets = self.m_assets.read().unwrap();
+        let plain_messages = self.m_plain_messages.read().unwrap();
+        for locale in self.locale_chain(&locale) {
+            let Some(bundle) = all_assets.get(&locale) else {
+                continue;
+            };
+            if !bundle.has_message(id) {
+                continue;
+            }
+            return plain_messages.get(&locale)?.get(id).cloned();
         }
+        None
+    }
 
-        for (locale, bundle) in new_assets {
-            self.m_assets.write().unwrap().insert(locale, bundle.clone());
+    /// Formats a message as `format()` does, writing it into `out`
+    /// instead of allocating a `String`, so that a buffer may be reused
+    /// across calls. On failure, `out` may contain part of the message.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # fn f(ftl: &Ftl) -> Result<(), FtlError> {
+    /// let mut buffer = String::new();
+    /// for name in ["Ada", "Grace"] {
+    ///     buffer.clear();
+    ///     ftl.format_into(&mut buffer, "welcome", &ftl_args!{ "name" => name })?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format_into(&self, out: &mut impl fmt::Write, id: &str, args: &FtlArgs) -> Result<(), FtlError> {
+        // Pseudolocalization pads the whole message.
+        if self.pseudolocalization().is_some() {
+            let r = self.format(id, args)?;
+            return out.write_str(&r).map_err(|e| write_error(id, e));
         }
-        *self.m_current_locale.write().unwrap() = Some(new_locale.clone());
-        for c in self.m_locale_initializers.read().unwrap().iter() {
-            c(new_locale.clone(), self.m_assets.read().unwrap()[&new_locale.clone()].clone());
+        self.reload_stale_bundles();
+        self.record_usage(id);
+        let locale = self.current_locale_id().ok_or(FtlError::NoLocaleLoaded)?;
+        let mut errors = FormatErrors::default();
+        let args = self.with_global_args(args);
+        let args = args.to_fluent_args();
+        match self.write_by_locale(out, id, None, locale, Some(&args), &mut errors) {
+            Some(r) => r.map_err(|e| write_error(id, e))?,
+            None => {
+                let r = self.missing_message(id).ok_or_else(|| FtlError::MessageNotFound(id.to_owned()))?;
+                return out.write_str(&r).map_err(|e| write_error(id, e));
+            },
         }
+        check_format_errors(id, &errors)
+    }
 
-        true
+    fn record_usage(&self, id: &str) {
+        if self.m_track_usage && !self.m_used_ids.read().unwrap().contains(id) {
+            self.m_used_ids.write().unwrap().insert(id.to_owned());
+        }
     }
 
-    async fn load_single_locale(&self, locale: &Locale) -> Option<Arc<fluent::FluentBundle<fluent::FluentResource>>> {
-        let mut r = fluent::FluentBundle::new(vec![locale_to_unic_langid_impl_langid(locale)]);
-        match self.m_assets_load_method {
-            FtlLoadMethod::FileSystem => {
-                for file_name in self.m_assets_files.iter() {
-                    let locale_path_comp = self.m_locale_to_path_components.get(locale);
-                    if locale_path_comp.is_none() {
-                        panic!("Fallback is not supported a locale: {}", locale.to_string());
-                    }
-                    let res_path = format!("{}/{}/{}.ftl", self.m_assets_source, locale_path_comp.unwrap(), file_name);
-                    let source = rialight_filesystem::File::new(res_path.clone()).read_bytes();
-                    if source.is_err() {
-                        println!("Failed to load resource at {}.", res_path);
-                        return None;
-                    }
-                    let source = String::from_utf8(source.unwrap()).unwrap();
-                    if !add_ftl_bundle_resource(file_name.clone(), source, &mut r) {
-                        return None;
-                    }
-                }
-            },
-            FtlLoadMethod::Http => {
-                for file_name in self.m_assets_files.iter() {
-                    let locale_path_comp = self.m_locale_to_path_components.get(locale);
-                    if locale_path_comp.is_none() {
-                        panic!("Fallback is not supported a locale: {}", locale.to_string());
-                    }
-                    let res_path = format!("{}/{}/{}.ftl", self.m_assets_source, locale_path_comp.unwrap(), file_name);
-                    let source = reqwest::get(reqwest::Url::parse(res_path.clone().as_ref()).unwrap()).await;
-                    if source.is_err() {
-                        println!("Failed to load resource at {}.", res_path);
-                        return None;
-                    }
-                    let source = source.unwrap().text().await;
-                    if source.is_err() {
-                        println!("Failed to load resource at {}.", res_path);
-                        return None;
-                    }
-                    let source = source.unwrap();
-                    if !add_ftl_bundle_resource(file_name.clone(), source, &mut r) {
-                        return None;
-                    }
-                }
-            },
+    /// Returns the IDs of the messages of the default locale that have not
+    /// been requested through `get_message()`, `format()`, `format_into()`
+    /// or `get_attribute()`, sorted, so that unused translations may be
+    /// removed. Returns an empty list if the `track_usage` option is
+    /// disabled or if the default locale is not loaded.
+    pub fn unused_keys(&self) -> Vec<String> {
+        if !self.m_track_usage {
+            return vec![];
         }
-        Some(Arc::new(r))
+        self.reload_stale_bundles();
+        let used_ids = self.m_used_ids.read().unwrap();
+        let mut r: Vec<String> = self.m_message_ids.read().unwrap().get(&self.m_default_locale)
+            .into_iter()
+            .flatten()
+            .filter(|id| !used_ids.contains(*id))
+            .cloned()
+            .collect();
+        r.sort();
+        r.dedup();
+        r
     }
 
-    fn enumerate_fallbacks(&self, locale: Locale, output: &mut HashSet<Locale>) {
-        for list in self.m_fallbacks.get(&locale).iter() {
-            for item in list.iter() {
-                output.insert(item.clone());
-                self.enumerate_fallbacks(item.clone(), output);
+    /// Formats a message as `format()` does, returning its literal text and
+    /// its placeables as separate segments, in order, so that substitutions
+    /// may be styled differently from the surrounding text. Adjacent text is
+    /// merged into a single segment. A select expression, such as
+    /// `{ $n -> ... }`, is a single placeable, without a variable name.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # fn f(ftl: &Ftl) -> Result<(), FtlError> {
+    /// // jump = Press { $key } to jump
+    /// let segments = ftl.format_segments("jump", &ftl_args!{ "key" => "Space" })?;
+    /// assert_eq!(segments[1], FtlSegment::Placeable {
+    ///     value: "Space".to_owned(),
+    ///     variable: Some("key".to_owned()),
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format_segments(&self, id: &str, args: &FtlArgs) -> Result<Vec<FtlSegment>, FtlError> {
+        self.reload_stale_bundles();
+        self.record_usage(id);
+        let locale = self.current_locale_id().ok_or(FtlError::NoLocaleLoaded)?;
+        let (locale, assets) = {
+            let all_assets = self.m_assets.read().unwrap();
+            self.locale_chain(&locale).into_iter().filter_map(|locale| Some((locale.clone(), all_assets.get(&locale)?.clone()))).find(|(_, assets)| assets.has_message(id))
+        }.unzip();
+        let args = self.with_global_args(args);
+        let args = args.to_fluent_args();
+        let mut errors = FormatErrors::default();
+        let Some(pattern) = assets.as_ref().and_then(|assets| assets.get_message(id)).and_then(|message| message.value()).map(|pattern| {
+            self.prepare_pattern(assets.as_ref().unwrap(), locale.as_ref().unwrap(), pattern, Some(&args), &mut errors.m_unmatched)
+        }) else {
+            return match self.missing_message(id) {
+                Some(r) if r.is_empty() => Ok(vec![]),
+                Some(r) => Ok(vec![FtlSegment::Text(r)]),
+                None => Err(FtlError::MessageNotFound(id.to_owned())),
+            };
+        };
+        let assets = assets.as_ref().unwrap();
+        let mut segments = vec![];
+        for element in pattern.elements.iter() {
+            // A pattern of one element is formatted without isolation marks.
+            let element_pattern = fluent_syntax::ast::Pattern { elements: vec![element.clone()] };
+            let value = assets.format_pattern(&element_pattern, Some(&args), &mut errors.m_errors).into_owned();
+            match element {
+                fluent_syntax::ast::PatternElement::TextElement { .. } => push_text_segment(&mut segments, &value),
+                fluent_syntax::ast::PatternElement::Placeable { expression } => {
+                    let variable = match expression {
+                        fluent_syntax::ast::Expression::Inline(fluent_syntax::ast::InlineExpression::VariableReference { id }) => Some(id.name.to_owned()),
+                        _ => None,
+                    };
+                    segments.push(FtlSegment::Placeable { value, variable });
+                },
+            }
+        }
+        check_format_errors(id, &errors)?;
+        if self.pseudolocalization().is_some() {
+            let message: String = segments.iter().map(|segment| match segment {
+                FtlSegment::Text(value) | FtlSegment::Placeable { value, .. } => value.as_str(),
+            }).collect();
+            let length = message.len();
+            let wrapped = pseudo_wrap(message);
+            match segments.first_mut() {
+                Some(FtlSegment::Text(text)) => text.insert(0, '['),
+                _ => segments.insert(0, FtlSegment::Text("[".to_owned())),
             }
+            push_text_segment(&mut segments, &wrapped[length + 1..]);
         }
+        Ok(segments)
     }
 
-    pub fn get_message(&self, id: &str, args: Option<&Arguments>, errors: &mut Vec<fluent::FluentError>) -> Option<String> {
-        self.get_message_by_locale(id, self.m_current_locale.read().unwrap().clone()?, args, errors)
+    /// Returns the value of a missing message according to the
+    /// `on_missing` option, or `None` for `MissingBehavior::Error`.
+    fn missing_message(&self, id: &str) -> Option<String> {
+        match self.m_missing_behavior {
+            MissingBehavior::ReturnId => Some(id.to_owned()),
+            MissingBehavior::ReturnEmpty => Some(String::new()),
+            MissingBehavior::Error => None,
+        }
     }
 
-    fn get_message_by_locale(&self, id: &str, locale: Locale, args: Option<&Arguments>, errors: &mut Vec<fluent::FluentError>) -> Option<String> {
-        if let Some(assets) = self.m_assets.read().unwrap().get(&locale) {
-            if let Some(message) = assets.get_message(id) {
-                return Some(self.format_pattern(message.value()?, args, errors));
-            }
+    /// Applies pseudolocalization, if enabled, to a formatted message.
+    fn finish_message(&self, message: String) -> String {
+        if self.pseudolocalization().is_some() {
+            pseudo_wrap(message)
+        } else {
+            message
         }
+    }
+
+    /// Returns the value of an attribute of a message, such as `tooltip`
+    /// in the following message, formatted with the given arguments and
+    /// resolved in the current locale or in its fallbacks. Returns `None`
+    /// if no locale is loaded or if no locale in the fallback chain defines
+    /// the attribute, regardless of the `on_missing` option.
+    ///
+    /// ```ftl
+    /// login = Log in
+    ///     .tooltip = Click to sign in
+    /// ```
+    pub fn get_attribute(&self, id: &str, attribute: &str, args: &FtlArgs) -> Option<String> {
+        self.reload_stale_bundles();
+        self.record_usage(id);
+        let locale = self.current_locale_id()?;
+        let mut errors = FormatErrors::default();
+        let args = self.with_global_args(args);
+        let args = args.to_fluent_args();
+        self.format_by_locale(id, Some(attribute), locale, Some(&args), &mut errors).map(|r| self.finish_message(r))
+    }
+
+    /// Formats the value of a message, or of an attribute of the message,
+    /// in the first locale of the fallback chain that defines it.
+    fn format_by_locale(&self, id: &str, attribute: Option<&str>, locale: Locale, args: Option<&Arguments>, errors: &mut FormatErrors) -> Option<String> {
+        let mut r = String::new();
+        self.write_by_locale(&mut r, id, attribute, locale, args, errors)?.ok()?;
+        Some(r)
+    }
 
-        let fallbacks = self.m_fallbacks.get(&locale);
-        if fallbacks.is_some() {
-            for fl in fallbacks.unwrap().iter() {
-                let r = self.get_message_by_locale(id, fl.clone(), args, errors);
-                if r.is_some() {
-                    return r;
+    /// Writes the value of a message, or of an attribute of the message,
+    /// as `format_by_locale()` does. Returns `None` if no locale of the
+    /// fallback chain defines it.
+    fn write_by_locale(&self, out: &mut impl fmt::Write, id: &str, attribute: Option<&str>, locale: Locale, args: Option<&Arguments>, errors: &mut FormatErrors) -> Option<fmt::Result> {
+        let all_assets = self.m_assets.read().unwrap();
+        for locale in self.locale_chain(&locale) {
+            let Some(assets) = all_assets.get(&locale) else {
+                continue;
+            };
+            if let Some(message) = assets.get_message(id) {
+                match attribute {
+                    None => return Some(assets.write_pattern(out, &self.prepare_pattern(assets, &locale, message.value()?, args, &mut errors.m_unmatched), args, &mut errors.m_errors)),
+                    Some(attribute) => if let Some(attribute) = message.get_attribute(attribute) {
+                        return Some(assets.write_pattern(out, &self.prepare_pattern(assets, &locale, attribute.value(), args, &mut errors.m_unmatched), args, &mut errors.m_errors));
+                    },
                 }
             }
         }
         None
     }
 
+    /// Prepares a pattern of the given locale for formatting with the given
+    /// arguments, choosing the variants of its select expressions. The
+    /// selector values that matched no variant are pushed to `unmatched`,
+    /// unless the `first_variant_as_default` option is enabled.
+    fn prepare_pattern<'a>(&self, assets: &FtlBundle, locale: &Locale, pattern: &'a fluent_syntax::ast::Pattern<&'a str>, args: Option<&Arguments>, unmatched: &mut Vec<String>) -> Cow<'a, fluent_syntax::ast::Pattern<&'a str>> {
+        let mut pattern = self.trim_pattern(pattern);
+        if pattern.elements.iter().any(|element| matches!(element, fluent_syntax::ast::PatternElement::Placeable { .. })) {
+            let mut r_unmatched = vec![];
+            resolve_selects(assets, locale, pattern.to_mut(), args, &mut r_unmatched);
+            if !self.m_first_variant_as_default {
+                unmatched.append(&mut r_unmatched);
+            }
+        }
+        pattern
+    }
+
+    /// Removes the ASCII whitespace at the start and end of the text
+    /// of a pattern, for the `trim_message_whitespace` option.
+    fn trim_pattern<'a>(&self, pattern: &'a fluent_syntax::ast::Pattern<&'a str>) -> Cow<'a, fluent_syntax::ast::Pattern<&'a str>> {
+        if !self.m_trim_message_whitespace {
+            return Cow::Borrowed(pattern);
+        }
+        let mut pattern = pattern.clone();
+        // Text elements may be emptied up to the first placeable.
+        for element in pattern.elements.iter_mut() {
+            let fluent_syntax::ast::PatternElement::TextElement { value } = element else {
+                break;
+            };
+            *value = value.trim_ascii_start();
+            if !value.is_empty() {
+                break;
+            }
+        }
+        for element in pattern.elements.iter_mut().rev() {
+            let fluent_syntax::ast::PatternElement::TextElement { value } = element else {
+                break;
+            };
+            *value = value.trim_ascii_end();
+            if !value.is_empty() {
+                break;
+            }
+        }
+        Cow::Owned(pattern)
+    }
+
+    /// Panics in debug builds if any of the given messages is not defined
+    /// by the default locale, such as after loading, so that misspelled
+    /// IDs are caught before they reach players. Does nothing in
+    /// release builds.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # async fn f(ftl: &Ftl) {
+    /// ftl.load_all().await.unwrap();
+    /// ftl.assert_ids(&["welcome", "goodbye"]);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn assert_ids(&self, ids: &[&str]) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        self.reload_stale_bundles();
+        let missing: Vec<&str> = ids.iter().copied().filter(|id| !self.has_message_by_locale(id, self.m_default_locale.clone())).collect();
+        if !missing.is_empty() {
+            panic!("messages not defined by the default locale {}: {}", self.locale_to_string(&self.m_default_locale), missing.join(", "));
+        }
+    }
+
+    /// Returns `true` if a message is defined by the current locale
+    /// or by its fallbacks, without formatting it, otherwise `false`.
     pub fn has_message(&self, id: &str) -> bool {
-        let locale = self.m_current_locale.read().unwrap().clone();
+        self.reload_stale_bundles();
+        let locale = self.current_locale_id();
         if locale.is_none() {
             return false;
         }
         self.has_message_by_locale(id, locale.unwrap())
     }
 
-    fn has_message_by_locale(&self, id: &str, locale: Locale) -> bool {
-        let assets = self.m_assets.read().unwrap();
-        let assets = assets.get(&locale);
-        if assets.is_some() {
-            if assets.unwrap().has_message(id) {
-                return true;
+    /// Compares the messages of the loaded locales, reporting the messages
+    /// that some locales do not define and the messages that reference
+    /// different variables or select over different arms in different
+    /// locales, such as `{ $name }` in a locale and `{ $naam }` in another.
+    /// The issues are sorted by message ID and locale.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # async fn f(ftl: &Ftl) {
+    /// ftl.load_all().await.unwrap();
+    /// for issue in ftl.validate() {
+    ///     println!("{}", issue);
+    /// }
+    /// # }
+    /// ```
+    pub fn validate(&self) -> Vec<FtlValidationIssue> {
+        self.reload_stale_bundles();
+        let messages = self.message_signatures();
+        let locales: BTreeSet<String> = self.m_sources.read().unwrap().keys().map(|locale| self.locale_to_string(locale)).collect();
+        let default_locale = self.locale_to_string(&self.m_default_locale);
+        let mut issues = vec![];
+        for (id, signatures) in messages.iter() {
+            let reference_locale = if signatures.contains_key(&default_locale) { &default_locale } else { signatures.keys().next().unwrap() };
+            let reference = &signatures[reference_locale];
+            for locale in locales.iter() {
+                let Some(signature) = signatures.get(locale) else {
+                    issues.push(FtlValidationIssue::MissingMessage {
+                        id: id.clone(),
+                        locale: locale.clone(),
+                    });
+                    continue;
+                };
+                if signature.m_variables != reference.m_variables {
+                    issues.push(FtlValidationIssue::MismatchedVariables {
+                        id: id.clone(),
+                        locale: locale.clone(),
+                        reference_locale: reference_locale.clone(),
+                        variables: signature.m_variables.iter().cloned().collect(),
+                        reference_variables: reference.m_variables.iter().cloned().collect(),
+                    });
+                }
+                if signature.m_arms != reference.m_arms {
+                    issues.push(FtlValidationIssue::MismatchedSelectArms {
+                        id: id.clone(),
+                        locale: locale.clone(),
+                        reference_locale: reference_locale.clone(),
+                        arms: signature.m_arms.iter().cloned().collect(),
+                        reference_arms: reference.m_arms.iter().cloned().collect(),
+                    });
+                }
             }
         }
+        issues
+    }
 
-        let fallbacks = self.m_fallbacks.get(&locale);
-        if fallbacks.is_some() {
-            for fl in fallbacks.unwrap().iter() {
-                let r = self.has_message_by_locale(id, fl.clone());
-                if r {
-                    return true;
+    /// Returns the messages defined by the loaded locales, sorted by ID,
+    /// each with the sorted names of the variables that it references in
+    /// any loaded locale, including in its attributes.
+    ///
+    /// ```
+    /// # use rialight::intl::ftl::*;
+    /// # async fn f(ftl: &Ftl) {
+    /// ftl.load_all().await.unwrap();
+    /// for (id, variables) in ftl.extract_schema() {
+    ///     println!("{}: {}", id, variables.join(", "));
+    /// }
+    /// # }
+    /// ```
+    pub fn extract_schema(&self) -> Vec<(String, Vec<String>)> {
+        self.reload_stale_bundles();
+        self.message_signatures().into_iter().map(|(id, signatures)| {
+            let variables: BTreeSet<String> = signatures.into_values().flat_map(|signature| signature.m_variables).collect();
+            (id, variables.into_iter().collect())
+        }).collect()
+    }
+
+    /// Returns the signature of each message in each loaded locale.
+    fn message_signatures(&self) -> BTreeMap<String, BTreeMap<String, MessageSignature>> {
+        let loaded = self.m_sources.read().unwrap().clone();
+        let mut messages: BTreeMap<String, BTreeMap<String, MessageSignature>> = BTreeMap::new();
+        for (locale, sources) in loaded.iter() {
+            let locale = self.locale_to_string(locale);
+            for (file_name, source) in sources.iter() {
+                let res_path = format!("{}/{}/{}.ftl", self.m_assets_source, locale, file_name);
+                let (res, _) = parse_cached_resource(&res_path, source, None, None);
+                for entry in res.entries() {
+                    let fluent_syntax::ast::Entry::Message(message) = entry else {
+                        continue;
+                    };
+                    let mut signature = MessageSignature::default();
+                    if let Some(value) = message.value.as_ref() {
+                        signature.add_pattern(value);
+                    }
+                    for attribute in message.attributes.iter() {
+                        signature.add_pattern(&attribute.value);
+                    }
+                    messages.entry(message.id.name.to_owned()).or_default().insert(locale.clone(), signature);
                 }
             }
         }
-        false
+        messages
+    }
+
+    /// Returns the IDs of the messages defined by the current locale
+    /// and by its fallbacks, sorted and without duplicates. Returns
+    /// an empty list if no locale is loaded.
+    pub fn message_ids(&self) -> Vec<String> {
+        self.reload_stale_bundles();
+        let Some(locale) = self.current_locale_id() else {
+            return vec![];
+        };
+        let mut locales = hashset![locale.clone()];
+        self.enumerate_fallbacks(locale, &mut locales);
+        let message_ids = self.m_message_ids.read().unwrap();
+        let mut r: Vec<String> = locales.iter()
+            .filter_map(|locale| message_ids.get(locale))
+            .flatten()
+            .cloned()
+            .collect();
+        r.sort();
+        r.dedup();
+        r
+    }
+
+    fn has_message_by_locale(&self, id: &str, locale: Locale) -> bool {
+        let assets = self.m_assets.read().unwrap();
+        self.locale_chain(&locale).iter().any(|locale| assets.get(locale).is_some_and(|assets| assets.has_message(id)))
     }
 
     pub fn format_pattern(&self, pattern: &fluent_syntax::ast::Pattern<&str>, args: Option<&Arguments>, errors: &mut Vec<fluent::FluentError>) -> String {
-        let locale = self.m_current_locale.read().unwrap().clone();
+        self.reload_stale_bundles();
+        let locale = self.current_locale_id();
         if locale.is_none() {
             return "".to_owned();
         }
-        let asset = &self.m_assets.read().unwrap()[&locale.unwrap()];
-        asset.format_pattern(pattern, args, errors).into_owned().to_owned()
+        let locale = locale.unwrap();
+        let asset = &self.m_assets.read().unwrap()[&locale];
+        let mut pattern = Cow::Borrowed(pattern);
+        resolve_selects(asset, &locale, pattern.to_mut(), args, &mut vec![]);
+        asset.format_pattern(&pattern, args, errors).into_owned().to_owned()
     }
 }
 
@@ -333,16 +3276,45 @@ impl Clone for Ftl {
     fn clone(&self) -> Self {
         Self {
             m_current_locale: RwLock::new(self.m_current_locale.read().unwrap().clone()),
+            m_formatting_locale: RwLock::new(self.m_formatting_locale.read().unwrap().clone()),
             m_locale_to_path_components: self.m_locale_to_path_components.clone(),
             m_supported_locales: self.m_supported_locales.clone(),
             m_default_locale: self.m_default_locale.clone(),
+            m_missing_behavior: self.m_missing_behavior,
+            m_fallback_on_format_error: self.m_fallback_on_format_error,
+            m_first_variant_as_default: self.m_first_variant_as_default,
+            m_lazy: self.m_lazy,
+            m_use_isolating: self.m_use_isolating,
+            m_trim_message_whitespace: self.m_trim_message_whitespace,
+            m_track_usage: self.m_track_usage,
+            m_global_args: self.m_global_args.clone(),
+            m_used_ids: self.m_used_ids.clone(),
             m_fallbacks: self.m_fallbacks.clone(),
             m_locale_initializers: self.m_locale_initializers.clone(),
+            m_functions: self.m_functions.clone(),
+            m_locale_observers: self.m_locale_observers.clone(),
+            m_next_locale_observer_id: self.m_next_locale_observer_id.clone(),
             m_assets: self.m_assets.clone(),
+            m_message_ids: self.m_message_ids.clone(),
+            m_plain_messages: self.m_plain_messages.clone(),
+            m_sources: self.m_sources.clone(),
+            m_pseudo_style: self.m_pseudo_style.clone(),
+            m_stale_locales: self.m_stale_locales.clone(),
+            m_added_sources: self.m_added_sources.clone(),
             m_assets_source: self.m_assets_source.clone(),
             m_assets_files: self.m_assets_files.clone(),
+            m_assets_embedded: self.m_assets_embedded.clone(),
+            m_assets_zip_archive: self.m_assets_zip_archive.clone(),
+            m_opened_zip_archive: self.m_opened_zip_archive.clone(),
+            m_assets_resolver: self.m_assets_resolver.clone(),
             m_assets_clean_unused: self.m_assets_clean_unused,
+            m_assets_strict: self.m_assets_strict,
+            m_assets_strict_duplicates: self.m_assets_strict_duplicates,
             m_assets_load_method: self.m_assets_load_method,
+            m_assets_http_max_retries: self.m_assets_http_max_retries,
+            m_assets_http_retry_backoff: self.m_assets_http_retry_backoff,
+            m_assets_watch_interval: self.m_assets_watch_interval,
+            m_http_client: self.m_http_client.clone(),
         }
     }
 }
@@ -352,6 +3324,13 @@ pub struct FtlOptions {
     m_default_locale: RwLock<String>,
     m_supported_locales: RwLock<Vec<String>>,
     m_fallbacks: RwLock<HashMap<String, Vec<String>>>,
+    m_on_missing: Cell<MissingBehavior>,
+    m_fallback_on_format_error: Cell<bool>,
+    m_first_variant_as_default: Cell<bool>,
+    m_lazy: Cell<bool>,
+    m_use_isolating: Cell<bool>,
+    m_trim_message_whitespace: Cell<bool>,
+    m_track_usage: Cell<bool>,
     m_assets: RwLock<FtlOptionsForAssets>,
 }
 
@@ -361,6 +3340,13 @@ impl FtlOptions {
             m_default_locale: RwLock::new("en".to_string()),
             m_supported_locales: RwLock::new(vec!["en".to_string()]),
             m_fallbacks: RwLock::new(hashmap! {}),
+            m_on_missing: Cell::new(if cfg!(debug_assertions) { MissingBehavior::ReturnId } else { MissingBehavior::Error }),
+            m_fallback_on_format_error: Cell::new(false),
+            m_first_variant_as_default: Cell::new(false),
+            m_lazy: Cell::new(false),
+            m_use_isolating: Cell::new(true),
+            m_trim_message_whitespace: Cell::new(false),
+            m_track_usage: Cell::new(false),
             m_assets: RwLock::new(FtlOptionsForAssets::new()),
         }
     }
@@ -375,6 +3361,13 @@ impl FtlOptions {
         self
     }
 
+    /// Maps supported locales to the locales in which their missing
+    /// messages are resolved, in order. The default locale is always
+    /// tried last; see `Ftl::fallback_chain()`.
+    ///
+    /// A locale without fallbacks falls back to its parent locale if it
+    /// is supported, so that `en-GB` may define only the messages that
+    /// differ from `en`.
     pub fn fallbacks(&mut self, map: HashMap<impl AsRef<str>, Vec<impl AsRef<str>>>) -> &mut Self {
         *self.m_fallbacks.write().unwrap() = map.iter().map(|(k, v)| (
             k.as_ref().to_owned(),
@@ -383,6 +3376,73 @@ impl FtlOptions {
         self
     }
 
+    /// Sets what is returned for messages that are not defined by the
+    /// current locale nor by its fallbacks. The default is
+    /// `MissingBehavior::ReturnId` in debug builds and
+    /// `MissingBehavior::Error` otherwise.
+    pub fn on_missing(&mut self, value: MissingBehavior) -> &mut Self {
+        self.m_on_missing.set(value);
+        self
+    }
+
+    /// Whether `Ftl::format()` falls back to the message of the default
+    /// locale when a message fails to format, such as when it references
+    /// a missing variable. If the message of the default locale fails as
+    /// well, the message is treated as missing according to the
+    /// `on_missing` option. Each fallback is logged. The default is `false`.
+    pub fn fallback_on_format_error(&mut self, value: bool) -> &mut Self {
+        self.m_fallback_on_format_error.set(value);
+        self
+    }
+
+    /// Whether a select expression without a default variant, which is
+    /// a syntax error, formats as its first variant when no variant
+    /// matches the selector. Otherwise, `Ftl::format()` fails with
+    /// `FtlError::Format` giving the unmatched selector value. Either way,
+    /// the syntax error is reported when loading, and the other variants
+    /// are selected as usual. Select expressions of the terms and messages
+    /// that a message references always format as their first variant
+    /// in that case. The default is `false`.
+    pub fn first_variant_as_default(&mut self, value: bool) -> &mut Self {
+        self.m_first_variant_as_default.set(value);
+        self
+    }
+
+    /// Whether `Ftl::load_all()` loads only the default and current
+    /// locales, deferring other locales to `Ftl::switch_locale()`.
+    /// The default is `false`.
+    pub fn lazy(&mut self, value: bool) -> &mut Self {
+        self.m_lazy.set(value);
+        self
+    }
+
+    /// Whether placeables, such as `{ $name }`, are wrapped in the Unicode
+    /// isolation marks U+2068 and U+2069, so that arguments in another
+    /// text direction do not reorder the surrounding text. Disable it only
+    /// for renderers that do not support these characters. The default
+    /// is `true`.
+    pub fn use_isolating(&mut self, value: bool) -> &mut Self {
+        self.m_use_isolating.set(value);
+        self
+    }
+
+    /// Whether the ASCII whitespace at the start and end of messages and
+    /// attributes is removed, such as indentation left by translators in
+    /// multiline values. Whitespace of arguments at the start or end
+    /// is kept. The default is `false`.
+    pub fn trim_message_whitespace(&mut self, value: bool) -> &mut Self {
+        self.m_trim_message_whitespace.set(value);
+        self
+    }
+
+    /// Whether the IDs of the requested messages are recorded, so that
+    /// `Ftl::unused_keys()` reports the messages that are never requested.
+    /// The default is `false`.
+    pub fn track_usage(&mut self, value: bool) -> &mut Self {
+        self.m_track_usage.set(value);
+        self
+    }
+
     pub fn assets(&mut self, options: &FtlOptionsForAssets) -> &mut Self {
         *self.m_assets.write().unwrap() = options.clone();
         self
@@ -392,8 +3452,19 @@ impl FtlOptions {
 pub struct FtlOptionsForAssets {
     m_source: RwLock<String>,
     m_files: RwLock<Vec<String>>,
+    m_embedded: RwLock<HashMap<(String, String), &'static str>>,
+    m_zip_archive: RwLock<Option<FtlZipArchive>>,
+    m_resolver: RwLock<Option<Arc<dyn AssetResolver>>>,
     m_clean_unused: Cell<bool>,
+    m_strict: Cell<bool>,
+    m_strict_duplicates: Cell<bool>,
     m_load_method: Cell<FtlLoadMethod>,
+    m_http_timeout: Cell<Option<Duration>>,
+    m_http_max_retries: Cell<u32>,
+    m_http_retry_backoff: Cell<Duration>,
+    m_watch_interval: Cell<Duration>,
+    m_http_base_headers: RwLock<Vec<(String, String)>>,
+    m_http_accept_encoding: RwLock<Vec<String>>,
 }
 
 impl Clone for FtlOptionsForAssets {
@@ -401,8 +3472,19 @@ impl Clone for FtlOptionsForAssets {
         Self {
             m_source: RwLock::new(self.m_source.read().unwrap().clone()),
             m_files: RwLock::new(self.m_files.read().unwrap().clone()),
+            m_embedded: RwLock::new(self.m_embedded.read().unwrap().clone()),
+            m_zip_archive: RwLock::new(self.m_zip_archive.read().unwrap().clone()),
+            m_resolver: RwLock::new(self.m_resolver.read().unwrap().clone()),
             m_clean_unused: self.m_clean_unused.clone(),
+            m_strict: self.m_strict.clone(),
+            m_strict_duplicates: self.m_strict_duplicates.clone(),
             m_load_method: self.m_load_method.clone(),
+            m_http_timeout: self.m_http_timeout.clone(),
+            m_http_max_retries: self.m_http_max_retries.clone(),
+            m_http_retry_backoff: self.m_http_retry_backoff.clone(),
+            m_watch_interval: self.m_watch_interval.clone(),
+            m_http_base_headers: RwLock::new(self.m_http_base_headers.read().unwrap().clone()),
+            m_http_accept_encoding: RwLock::new(self.m_http_accept_encoding.read().unwrap().clone()),
         }
     }
 }
@@ -412,8 +3494,19 @@ impl FtlOptionsForAssets {
         FtlOptionsForAssets {
             m_source: RwLock::new("res/lang".to_string()),
             m_files: RwLock::new(vec![]),
+            m_embedded: RwLock::new(hashmap! {}),
+            m_zip_archive: RwLock::new(None),
+            m_resolver: RwLock::new(None),
             m_clean_unused: Cell::new(true),
+            m_strict: Cell::new(false),
+            m_strict_duplicates: Cell::new(false),
             m_load_method: Cell::new(FtlLoadMethod::Http),
+            m_http_timeout: Cell::new(None),
+            m_http_max_retries: Cell::new(0),
+            m_http_retry_backoff: Cell::new(Duration::from_millis(250)),
+            m_watch_interval: Cell::new(Duration::from_millis(500)),
+            m_http_base_headers: RwLock::new(vec![]),
+            m_http_accept_encoding: RwLock::new(vec![]),
         }
     }
     
@@ -427,19 +3520,214 @@ impl FtlOptionsForAssets {
         self
     }
 
+    /// Embeds the source of the file `{locale}/{file}.ftl`, used by
+    /// `FtlLoadMethod::Embedded`. The locale is given in the same form
+    /// as in the `supported_locales` option.
+    ///
+    /// ```ignore
+    /// FtlOptionsForAssets::new()
+    ///     .files(vec!["_"])
+    ///     .embedded("en", "_", include_str!("../res/lang/en/_.ftl"))
+    ///     .load_method(FtlLoadMethod::Embedded)
+    /// ```
+    pub fn embedded(&mut self, locale: impl AsRef<str>, file: impl AsRef<str>, source: &'static str) -> &mut Self {
+        self.m_embedded.write().unwrap().insert((locale.as_ref().to_owned(), file.as_ref().to_owned()), source);
+        self
+    }
+
+    /// Sets the zip archive read by `FtlLoadMethod::ZipArchive`, given as
+    /// a path in the file system or as the contents of an archive. The
+    /// archive, which may be a ZIP64 archive, contains the files
+    /// `{locale}/{file}.ftl`, whose entries are stored or compressed with
+    /// DEFLATE. The archive is opened once and reused by later loads
+    /// unless its file is modified. The `source` option is ignored.
+    ///
+    /// ```ignore
+    /// FtlOptionsForAssets::new()
+    ///     .files(vec!["_"])
+    ///     .zip_archive(include_bytes!("../res/lang.zip"))
+    ///     .load_method(FtlLoadMethod::ZipArchive)
+    /// ```
+    pub fn zip_archive(&mut self, archive: impl Into<FtlZipArchive>) -> &mut Self {
+        *self.m_zip_archive.write().unwrap() = Some(archive.into());
+        self
+    }
+
+    /// Reads the resources of `FtlLoadMethod::FileSystem` through a custom
+    /// resolver instead of the file system, such as for an in-memory file
+    /// system or an encrypted asset pack. The resolver is given the path
+    /// or URL of each resource, such as
+    /// `app://rialight_test_app/res/lang/en/_.ftl`, and reads it or the
+    /// `.ftl.gz` resource compressed with gzip. `Ftl::watch()` is not
+    /// supported with a custom resolver.
+    pub fn resolver(&mut self, resolver: impl AssetResolver + 'static) -> &mut Self {
+        *self.m_resolver.write().unwrap() = Some(Arc::new(resolver));
+        self
+    }
+
     pub fn clean_unused(&mut self, value: bool) -> &mut Self {
         self.m_clean_unused.set(value);
         self
     }
 
+    /// Whether syntax errors in resources fail loading with
+    /// `FtlError::Parse`. By default, the valid entries of
+    /// such resources are loaded and the errors are returned
+    /// by `Ftl::load_locale()` and `Ftl::load_all()`.
+    pub fn strict(&mut self, value: bool) -> &mut Self {
+        self.m_strict.set(value);
+        self
+    }
+
+    /// Whether a resource that defines a message or term already defined
+    /// by a previous resource of its locale fails loading with
+    /// `FtlError::InvalidResource`. By default, resources override the
+    /// messages and terms of the resources before them, in the order of
+    /// the `files` option, followed by the resources given through
+    /// `Ftl::add_source()`.
+    pub fn strict_duplicates(&mut self, value: bool) -> &mut Self {
+        self.m_strict_duplicates.set(value);
+        self
+    }
+
     pub fn load_method(&mut self, value: FtlLoadMethod) -> &mut Self {
         self.m_load_method.set(value);
         self
     }
+
+    /// Sets the timeout of each HTTP request made by `FtlLoadMethod::Http`.
+    /// By default, requests do not time out.
+    pub fn http_timeout(&mut self, value: Duration) -> &mut Self {
+        self.m_http_timeout.set(Some(value));
+        self
+    }
+
+    /// Sets how many times a failed HTTP request made by `FtlLoadMethod::Http`
+    /// is retried. Connection errors, timeouts, server errors and the
+    /// `429 Too Many Requests` status are retried. The default is 0.
+    pub fn http_max_retries(&mut self, value: u32) -> &mut Self {
+        self.m_http_max_retries.set(value);
+        self
+    }
+
+    /// Sets the delay before the first retry of an HTTP request, which
+    /// doubles after each retry. The default is 250 milliseconds.
+    pub fn http_retry_backoff(&mut self, value: Duration) -> &mut Self {
+        self.m_http_retry_backoff.set(value);
+        self
+    }
+
+    /// Sets how often `Ftl::watch()` checks the resources for changes.
+    /// The default is 500 milliseconds.
+    pub fn watch_interval(&mut self, value: Duration) -> &mut Self {
+        self.m_watch_interval.set(value);
+        self
+    }
+
+    /// Sets headers sent with every HTTP request made by `FtlLoadMethod::Http`.
+    pub fn http_base_headers(&mut self, map: HashMap<impl AsRef<str>, impl AsRef<str>>) -> &mut Self {
+        *self.m_http_base_headers.write().unwrap() = map.iter().map(|(k, v)| (
+            k.as_ref().to_owned(),
+            v.as_ref().to_owned(),
+        )).collect();
+        self
+    }
+
+    /// Sets the encodings sent in the `Accept-Encoding` header of HTTP
+    /// requests made by `FtlLoadMethod::Http`, such as `vec!["gzip", "deflate"]`.
+    /// Responses compressed with `gzip` or `deflate` are decompressed before
+    /// parsing. In the browser, compression is negotiated by the browser
+    /// itself and this option is ignored.
+    ///
+    /// With `FtlLoadMethod::FileSystem`, a resource missing as
+    /// `{locale}/{file}.ftl` is read from `{locale}/{file}.ftl.gz`
+    /// regardless of this option.
+    pub fn http_accept_encoding(&mut self, list: Vec<impl AsRef<str>>) -> &mut Self {
+        *self.m_http_accept_encoding.write().unwrap() = list.iter().map(|encoding| encoding.as_ref().to_owned()).collect();
+        self
+    }
 }
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum FtlLoadMethod {
     FileSystem,
+    /// Fetches the sources over HTTP. In the browser, with the `web`
+    /// feature, sources are fetched through the `fetch` API.
     Http,
+    /// Loads the sources embedded through `FtlOptionsForAssets::embedded()`,
+    /// such as the ones given by `include_str!`.
+    Embedded,
+    /// Loads only the sources given at runtime through `Ftl::add_source()`.
+    Memory,
+    /// Reads the sources from the archive given through
+    /// `FtlOptionsForAssets::zip_archive()`.
+    ZipArchive,
+}
+
+/// Future returned by `AssetResolver::read()`.
+pub type AssetFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, rialight_filesystem::FileError>> + 'a>>;
+
+/// Reads the resources of assets loaded with `FtlLoadMethod::FileSystem`,
+/// given through `FtlOptionsForAssets::resolver()`.
+///
+/// ```
+/// # use rialight::intl::ftl::*;
+/// struct InMemory(std::collections::HashMap<String, Vec<u8>>);
+///
+/// impl AssetResolver for InMemory {
+///     fn read<'a>(&'a self, url: &'a str) -> AssetFuture<'a> {
+///         let data = self.0.get(url).cloned().ok_or(rialight::filesystem::FileError::NotFound);
+///         Box::pin(async move { data })
+///     }
+/// }
+/// ```
+pub trait AssetResolver: Send + Sync {
+    /// Reads the file at the given path or URL.
+    fn read<'a>(&'a self, url: &'a str) -> AssetFuture<'a>;
+}
+
+/// Resolver that reads files from the file system, where `app:` and
+/// `app-storage:` URLs refer to the application directories, as
+/// resources are read without a custom resolver. It may be used by
+/// custom resolvers for the URLs that they do not handle.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FileSystemResolver;
+
+impl AssetResolver for FileSystemResolver {
+    fn read<'a>(&'a self, url: &'a str) -> AssetFuture<'a> {
+        Box::pin(async move { rialight_filesystem::File::new(url).read_bytes_async().await })
+    }
+}
+
+/// Zip archive read by `FtlLoadMethod::ZipArchive`.
+#[derive(Clone, Debug)]
+pub enum FtlZipArchive {
+    /// Path of the archive in the file system.
+    Path(String),
+    /// Contents of the archive, such as the ones given by `include_bytes!`.
+    Bytes(&'static [u8]),
+}
+
+impl From<&str> for FtlZipArchive {
+    fn from(value: &str) -> Self {
+        Self::Path(value.to_owned())
+    }
+}
+
+impl From<String> for FtlZipArchive {
+    fn from(value: String) -> Self {
+        Self::Path(value)
+    }
+}
+
+impl From<&'static [u8]> for FtlZipArchive {
+    fn from(value: &'static [u8]) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<const N: usize> From<&'static [u8; N]> for FtlZipArchive {
+    fn from(value: &'static [u8; N]) -> Self {
+        Self::Bytes(value)
+    }
 }
\ No newline at end of file
@@ -4,7 +4,37 @@ pub mod locale;
 pub use locale::Locale;
 
 pub use fluent;
+pub mod case_mapping;
+pub mod collator;
+pub mod datetime;
+pub mod display_names;
 pub mod ftl;
+pub mod list;
+pub mod number;
+pub mod relative_time;
+pub mod segment;
+
+/// Formats a message through `Ftl::format()`, given the `Ftl` object,
+/// the message ID and the arguments in named-argument syntax. The
+/// message ID must not be empty.
+///
+/// ## Example
+///
+/// ```
+/// use rialight::intl::{ftl::{Ftl, FtlError}, tr};
+///
+/// fn welcome(ftl: &Ftl) -> Result<String, FtlError> {
+///     tr!(ftl, "welcome", name = "Ada", count = 3)
+/// }
+/// ```
+pub macro tr {
+    ($ftl:expr, "" $(, $($_rest:tt)*)?) => {
+        ::std::compile_error!("the message ID given to `tr!` is empty")
+    },
+    ($ftl:expr, $id:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $ftl.format($id, &$crate::ftl::ftl_args! { $(::std::stringify!($name) => $value),* })
+    }
+}
 
 /// Represents a language's text reading direction.
 #[derive(Copy, Clone, PartialEq)]
@@ -24,8 +54,22 @@ impl HasTextDirection for locale::Locale {
     }
 }
 
+/// Returns the text direction of the script of a locale, or of its
+/// likely script if it has no script subtag; for example, `ar` and
+/// `az-Arab` are right-to-left, whereas `az` is left-to-right.
+/// Malformed locales are left-to-right.
+pub fn text_direction_of(locale: &str) -> TextDirection {
+    locale::Locale::try_from_bytes(locale.as_bytes()).map_or(TextDirection::Ltr, |locale| locale.text_direction())
+}
+
 impl HasTextDirection for locale::LanguageIdentifier {
     fn text_direction(&self) -> TextDirection {
+        match icu::locid_transform::LocaleDirectionality::new().get(self) {
+            Some(icu::locid_transform::Direction::RightToLeft) => return TextDirection::Rtl,
+            Some(icu::locid_transform::Direction::LeftToRight) => return TextDirection::Ltr,
+            _ => {},
+        }
+        // Languages without likely subtags data.
         match self.language.as_str() {
             | "ar" | "ara"
             | "arc"
@@ -39,4 +83,1933 @@ impl HasTextDirection for locale::LanguageIdentifier {
             _ => TextDirection::Ltr,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HasTextDirection, TextDirection, text_direction_of, tr, case_mapping, collator::{CollationSensitivity, Collator, CollatorOptions}, datetime::{self, DateTimeStyle}, ftl::{self, AssetFuture, AssetResolver, FileSystemResolver, Ftl, FtlArgs, FtlError, FtlOptions, FtlOptionsForAssets, FtlLoadMethod, FtlParseError, FtlSegment, FtlText, FtlValidationIssue, FtlValue, LocaleResolution, MissingBehavior, PluralCategory, PseudoStyle, ftl_args}, list::{self, ListStyle}, locale, number::{self, CompactStyle, CurrencyDisplay, CurrencyFormatOptions, NumberFormatOptions, RoundingMode, Unit, UnitStyle}, relative_time::{self, RelativeTimeUnit}, segment};
+    use rialight_util::hashmap;
+    use std::{cmp::Ordering, sync::{Arc, Mutex}, time::{Duration, SystemTime}};
+    use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+
+    /// Serves `body` over HTTP after answering the first `failures`
+    /// requests with `503 Service Unavailable`, with the entity tag
+    /// `"v1"`, answering revalidations with `304 Not Modified`. Returns
+    /// the base URL and the received requests.
+    async fn serve_http(failures: usize, body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::<String>::new()));
+        let requests_2 = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let n = socket.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..n]).into_owned();
+                let n = {
+                    let mut requests = requests_2.lock().unwrap();
+                    requests.push(request.clone());
+                    requests.len()
+                };
+                let response = if n <= failures {
+                    "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n".to_owned()
+                } else if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\n\r\n".to_owned()
+                } else {
+                    format!("HTTP/1.1 200 OK\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body)
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    /// Returns the directory of `app:` files, shared by the tests
+    /// that read them.
+    fn app_directory() -> std::path::PathBuf {
+        let app_dir = std::env::temp_dir().join(format!("rialight_intl_app_{}", std::process::id()));
+        unsafe {
+            rialight_filesystem::__APP_DIRECTORY = Some(app_dir.to_str().unwrap().to_owned());
+        }
+        app_dir
+    }
+
+    fn embedded_ftl() -> Ftl {
+        Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .fallbacks(hashmap! {
+                "pt-BR" => vec!["en"],
+            })
+            .on_missing(MissingBehavior::Error)
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .embedded("en", "_", concat!(
+                    "hello-world = Hello, world!\n",
+                    "bye = Bye!\n",
+                    "welcome = Welcome, {$name}!\n",
+                    "login = Log in\n    .tooltip = Click to sign in\n    .aria-label = Log in as {$name}\n",
+                    "place = { ORDINAL($n) ->\n    [one] {$n}st\n    [two] {$n}nd\n    [few] {$n}rd\n   *[other] {$n}th\n}\n",
+                ))
+                .embedded("pt-BR", "_", "hello-world = Olá, mundo!\nscore = {$score} pontos\nlogin = Entrar\n    .tooltip = Clique para entrar")
+                .load_method(FtlLoadMethod::Embedded)))
+    }
+
+    #[tokio::test]
+    async fn ftl_embedded() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Olá, mundo!");
+        assert_eq!(ftl.get_message("bye").unwrap(), "Bye!");
+    }
+
+    #[tokio::test]
+    async fn ftl_format() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.format("bye", &FtlArgs::new()), Err(FtlError::NoLocaleLoaded));
+        assert!(ftl.get_message("bye").is_none());
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.format("welcome", &ftl_args!{ "name" => "Ada" }).unwrap(), "Welcome, \u{2068}Ada\u{2069}!");
+        assert_eq!(ftl.format("missing", &FtlArgs::new()), Err(FtlError::MessageNotFound("missing".to_owned())));
+        assert!(ftl.get_message("missing").is_none());
+    }
+
+    #[tokio::test]
+    async fn ftl_set_locale() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.current_locale(), "en");
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.current_locale(), "pt-BR");
+        assert_eq!(ftl.set_locale("en"), LocaleResolution::Exact);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        assert_eq!(ftl.set_locale("pt-br"), LocaleResolution::Exact);
+        assert_eq!(ftl.current_locale(), "pt-BR");
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Olá, mundo!");
+        assert_eq!(ftl.set_locale("pt-PT"), LocaleResolution::FallbackTo("pt-BR".into()));
+        assert_eq!(ftl.set_locale("fr"), LocaleResolution::DefaultUsed);
+        assert_eq!(ftl.current_locale(), "en");
+        assert_eq!(ftl.set_locale("pt-BR"), LocaleResolution::Exact);
+        assert_eq!(ftl.set_locale("not a locale"), LocaleResolution::DefaultUsed);
+        assert_eq!(ftl.current_locale(), "en");
+    }
+
+    fn http_ftl(source: &str, max_retries: u32) -> Ftl {
+        Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(FtlOptionsForAssets::new()
+                .source(source)
+                .files(vec!["_"])
+                .http_timeout(Duration::from_secs(5))
+                .http_max_retries(max_retries)
+                .http_retry_backoff(Duration::from_millis(1))
+                .http_base_headers(hashmap! { "x-game" => "rialight" })
+                .load_method(FtlLoadMethod::Http)))
+    }
+
+    #[tokio::test]
+    async fn ftl_http_retry() {
+        let (url, requests) = serve_http(2, "hello-world = Hello, world!").await;
+        let ftl = http_ftl(&url, 2);
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].starts_with("GET /en/_.ftl "));
+        assert!(requests[0].contains("x-game: rialight"));
+    }
+
+    #[tokio::test]
+    async fn ftl_http_etag() {
+        let (url, requests) = serve_http(0, "hello-world = Hello, world!").await;
+        assert!(http_ftl(&url, 0).load(None).await);
+        let ftl = http_ftl(&url, 0);
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].contains("if-none-match"));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+    }
+
+    #[tokio::test]
+    async fn ftl_http_gzip() {
+        // `hello-world = Hello, world!`, compressed with gzip.
+        const BODY: &[u8] = &[
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0xd7,
+            0x2d, 0xcf, 0x2f, 0xca, 0x49, 0x51, 0xb0, 0x55, 0xf0, 0x00, 0xf1, 0x74, 0x14, 0xc0, 0x5c, 0x45,
+            0x00, 0xd8, 0xf3, 0x8e, 0x1f, 0x1b, 0x00, 0x00, 0x00,
+        ];
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let n = socket.read(&mut request).await.unwrap();
+            let header = format!("HTTP/1.1 200 OK\r\ncontent-encoding: gzip\r\ncontent-length: {}\r\nconnection: close\r\n\r\n", BODY.len());
+            socket.write_all(&[header.as_bytes(), BODY].concat()).await.unwrap();
+            String::from_utf8_lossy(&request[..n]).into_owned()
+        });
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new()
+                .source(&url)
+                .files(vec!["_"])
+                .http_accept_encoding(vec!["gzip", "deflate"])
+                .load_method(FtlLoadMethod::Http)));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        assert!(server.await.unwrap().contains("accept-encoding: gzip, deflate"));
+    }
+
+    #[tokio::test]
+    async fn ftl_gzip_file_output_limit() {
+        use std::io::Write;
+        let dir = std::env::temp_dir().join(format!("rialight_intl_gzip_limit_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("en")).unwrap();
+        // A comment of 64 MiB, which decompresses past the limit.
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(b"#").unwrap();
+        encoder.write_all(&vec![b' '; 64 * 1024 * 1024]).unwrap();
+        std::fs::write(dir.join("en/_.ftl.gz"), encoder.finish().unwrap()).unwrap();
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new()
+                .source(dir.to_str().unwrap())
+                .files(vec!["_"])
+                .load_method(FtlLoadMethod::FileSystem)));
+        assert!(ftl.load_locale("en").await.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ftl_gzip_file() {
+        let dir = std::env::temp_dir().join(format!("rialight_intl_gzip_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("en")).unwrap();
+        // `m0 = Message 0` through `m9 = Message 9`, compressed with gzip.
+        std::fs::write(dir.join("en/_.ftl.gz"), [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x4d, 0xc7, 0xa9, 0x0d, 0xc0, 0x30,
+            0x00, 0x04, 0x41, 0xbe, 0x55, 0xb8, 0x04, 0xff, 0x0f, 0x48, 0x09, 0x29, 0x22, 0xc0, 0x32, 0x3a,
+            0xe4, 0xfe, 0xa5, 0xd0, 0x1b, 0x36, 0x8a, 0xe1, 0x09, 0xef, 0xbe, 0xf7, 0x3b, 0x3b, 0x44, 0x94,
+            0xac, 0x09, 0x65, 0x6b, 0x46, 0xc5, 0x5a, 0x50, 0xb5, 0x56, 0xd4, 0xac, 0x0d, 0x75, 0x6b, 0x47,
+            0xc3, 0x3a, 0xd0, 0xb4, 0x4e, 0xb4, 0xac, 0x8b, 0x1f, 0x49, 0xcb, 0xf2, 0xdd, 0x96, 0x00, 0x00,
+            0x00,
+        ]).unwrap();
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new()
+                .source(dir.to_str().unwrap())
+                .files(vec!["_"])
+                .load_method(FtlLoadMethod::FileSystem)));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("m0").unwrap(), "Message 0");
+        assert_eq!(ftl.get_message("m9").unwrap(), "Message 9");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ftl_gzip_app_file() {
+        let app_dir = app_directory();
+        std::fs::create_dir_all(app_dir.join("lang/en")).unwrap();
+        // `hello-world = Hello, world!`, compressed with gzip.
+        std::fs::write(app_dir.join("lang/en/_.ftl.gz"), [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0xd7,
+            0x2d, 0xcf, 0x2f, 0xca, 0x49, 0x51, 0xb0, 0x55, 0xf0, 0x00, 0xf1, 0x74, 0x14, 0xc0, 0x5c, 0x45,
+            0x00, 0xd8, 0xf3, 0x8e, 0x1f, 0x1b, 0x00, 0x00, 0x00,
+        ]).unwrap();
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new()
+                .source("app://lang")
+                .files(vec!["_"])
+                .load_method(FtlLoadMethod::FileSystem)));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        std::fs::remove_dir_all(app_dir.join("lang")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ftl_zip_archive() {
+        // `en/_.ftl`, compressed with DEFLATE, and `pt-BR/_.ftl`, stored.
+        const ARCHIVE: &[u8] = &[
+            0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x21, 0x00, 0x17, 0xb5,
+            0x07, 0x7b, 0x13, 0x00, 0x00, 0x00, 0x1d, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x65, 0x6e,
+            0x2f, 0x5f, 0x2e, 0x66, 0x74, 0x6c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xb0, 0x55, 0xf0, 0x00,
+            0xd1, 0x3a, 0x0a, 0x19, 0x48, 0x94, 0x22, 0x17, 0x00, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x00, 0xa3, 0x92, 0xa7, 0xa1, 0x0d, 0x00, 0x00, 0x00, 0x0d,
+            0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x70, 0x74, 0x2d, 0x42, 0x52, 0x2f, 0x5f, 0x2e, 0x66,
+            0x74, 0x6c, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x3d, 0x20, 0x4f, 0x6c, 0xc3, 0xa1, 0x0a, 0x50,
+            0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x21, 0x00, 0x17,
+            0xb5, 0x07, 0x7b, 0x13, 0x00, 0x00, 0x00, 0x1d, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00, 0x00, 0x00, 0x65, 0x6e, 0x2f,
+            0x5f, 0x2e, 0x66, 0x74, 0x6c, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x21, 0x00, 0xa3, 0x92, 0xa7, 0xa1, 0x0d, 0x00, 0x00, 0x00, 0x0d, 0x00, 0x00,
+            0x00, 0x0b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x39,
+            0x00, 0x00, 0x00, 0x70, 0x74, 0x2d, 0x42, 0x52, 0x2f, 0x5f, 0x2e, 0x66, 0x74, 0x6c, 0x50, 0x4b,
+            0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x02, 0x00, 0x6f, 0x00, 0x00, 0x00, 0x6f, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        let zip_ftl = |archive: ftl::FtlZipArchive| Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .zip_archive(archive)
+                .load_method(FtlLoadMethod::ZipArchive)));
+        let ftl = zip_ftl(ARCHIVE.into());
+        assert_eq!(ftl.load_all().await.unwrap(), vec![]);
+        ftl.set_locale("en");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello, hello, hello!");
+        ftl.set_locale("pt-BR");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Olá");
+
+        let path = std::env::temp_dir().join(format!("rialight_intl_lang_{}.zip", std::process::id()));
+        std::fs::write(&path, ARCHIVE).unwrap();
+        let ftl = zip_ftl(path.to_str().unwrap().into());
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello, hello, hello!");
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(zip_ftl(path.to_str().unwrap().into()).load_locale("en").await, Err(FtlError::ResourceNotFound {
+            locale: "en".to_owned(),
+            path: path.to_str().unwrap().to_owned(),
+        }));
+
+        let app_dir = app_directory();
+        std::fs::create_dir_all(&app_dir).unwrap();
+        std::fs::write(app_dir.join("lang.zip"), ARCHIVE).unwrap();
+        let ftl = zip_ftl("app://lang.zip".into());
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello, hello, hello!");
+        std::fs::remove_file(app_dir.join("lang.zip")).unwrap();
+
+        assert!(matches!(zip_ftl(b"not a zip archive".into()).load_locale("en").await, Err(FtlError::ZipArchive { locale, path, .. }) if locale == "en" && path == "<zip archive>"));
+        // The CRC-32 of `pt-BR/_.ftl` is altered.
+        let mut corrupt = ARCHIVE.to_vec();
+        corrupt[71] ^= 0xff;
+        corrupt[181] ^= 0xff;
+        let corrupt: &'static [u8] = corrupt.leak();
+        assert!(matches!(zip_ftl(corrupt.into()).load_locale("pt-BR").await, Err(FtlError::ZipArchive { message, .. }) if message.starts_with("pt-BR/_.ftl: ")));
+    }
+
+    #[tokio::test]
+    async fn ftl_zip64_archive() {
+        // `en/_.ftl`, stored, with its sizes and the central directory
+        // given by ZIP64 records.
+        const ARCHIVE: &[u8] = &[
+            0x50, 0x4b, 0x03, 0x04, 0x2d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x00, 0x5a, 0xc1,
+            0xff, 0x22, 0x0b, 0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x65, 0x6e,
+            0x2f, 0x5f, 0x2e, 0x66, 0x74, 0x6c, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x3d, 0x20, 0x48, 0x69,
+            0x0a, 0x50, 0x4b, 0x01, 0x02, 0x2d, 0x00, 0x2d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21,
+            0x00, 0x5a, 0xc1, 0xff, 0x22, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x08, 0x00, 0x14,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x65,
+            0x6e, 0x2f, 0x5f, 0x2e, 0x66, 0x74, 0x6c, 0x01, 0x00, 0x10, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x50, 0x4b, 0x06, 0x06, 0x2c,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2d, 0x00, 0x2d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x31, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x50, 0x4b, 0x06, 0x07, 0x00, 0x00, 0x00, 0x00, 0x7b, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x50, 0x4b, 0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+        ];
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .zip_archive(ARCHIVE)
+                .load_method(FtlLoadMethod::ZipArchive)));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hi");
+    }
+
+    #[tokio::test]
+    async fn ftl_cache() {
+        let dir = std::env::temp_dir().join(format!("rialight_intl_cache_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("en")).unwrap();
+        let file = dir.join("en/_.ftl");
+        std::fs::write(&file, "hello = Hello\n").unwrap();
+        let fs_ftl = || Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .assets(FtlOptionsForAssets::new()
+                    .source(dir.to_str().unwrap())
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::FileSystem)));
+        let ftl = fs_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+
+        // A file with the same modification time is not read again.
+        let modified = std::fs::metadata(&file).unwrap().modified().unwrap();
+        std::fs::write(&file, "hello = Hi\n").unwrap();
+        std::fs::File::options().write(true).open(&file).unwrap().set_modified(modified).unwrap();
+        let ftl = fs_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+
+        Ftl::clear_cache();
+        let ftl = fs_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hi");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ftl_http_failure() {
+        let (url, requests) = serve_http(usize::MAX, "").await;
+        let ftl = http_ftl(&url, 1);
+        assert!(!ftl.load(None).await);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        assert!(ftl.get_message("hello-world").is_none());
+        match ftl.load_locale("en").await {
+            Err(FtlError::Http { url: error_url, locale, .. }) => {
+                assert_eq!(error_url, format!("{}/en/_.ftl", url));
+                assert_eq!(locale, "en");
+            },
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn ftl_load_locale() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.load_locale("fr").await, Err(FtlError::UnsupportedLocale("fr".to_owned())));
+        ftl.load_locale("pt-BR").await.unwrap();
+        assert_eq!(ftl.format("bye", &FtlArgs::new()), Err(FtlError::NoLocaleLoaded));
+        ftl.set_locale("pt-BR");
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Olá, mundo!");
+        assert_eq!(ftl.get_message("bye").unwrap(), "Bye!");
+
+        let ftl = embedded_ftl();
+        ftl.load_all().await.unwrap();
+        ftl.set_locale("en");
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        ftl.set_locale("pt-BR");
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Olá, mundo!");
+
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "es"])
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .embedded("en", "_", "hello-world = Hello, world!")
+                .load_method(FtlLoadMethod::Embedded)));
+        assert_eq!(ftl.load_all().await, Err(FtlError::ResourceNotFound {
+            locale: "es".to_owned(),
+            path: "es/_.ftl".to_owned(),
+        }));
+    }
+
+    #[test]
+    fn ftl_negotiate() {
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "en-GB", "pt", "zh", "zh-Hant", "sr-Latn"])
+            .default_locale("en"));
+        assert_eq!(ftl.negotiate(&["en-US"]).unwrap(), "en");
+        assert_eq!(ftl.negotiate(&["en-GB"]).unwrap(), "en-GB");
+        assert_eq!(ftl.negotiate(&["pt-BR"]).unwrap(), "pt");
+        assert_eq!(ftl.negotiate(&["zh-Hant-TW"]).unwrap(), "zh-Hant");
+        assert_eq!(ftl.negotiate(&["zh-TW"]).unwrap(), "zh-Hant");
+        assert_eq!(ftl.negotiate(&["zh-CN"]).unwrap(), "zh");
+        assert_eq!(ftl.negotiate(&["sr"]).unwrap(), "sr-Latn");
+        assert_eq!(ftl.negotiate(&["fr", "pt-PT"]).unwrap(), "pt");
+        assert_eq!(ftl.negotiate(&["not a locale", "en"]).unwrap(), "en");
+        assert!(ftl.negotiate(&["fr"]).is_none());
+        assert_eq!(ftl.set_locale("en-AU"), LocaleResolution::FallbackTo("en".into()));
+    }
+
+    #[test]
+    fn ftl_negotiate_accept_language() {
+        let supported = ["en", "pt", "zh-Hant"];
+        assert_eq!(ftl::negotiate_accept_language("pt-BR,pt;q=0.9,en;q=0.8", &supported, "en"), "pt");
+        assert_eq!(ftl::negotiate_accept_language("fr;q=0.9, en;q=0.95, pt;q=0.8", &supported, "pt"), "en");
+        assert_eq!(ftl::negotiate_accept_language("de, zh-TW;q=0.5", &supported, "en"), "zh-Hant");
+        assert_eq!(ftl::negotiate_accept_language("pt;q=0, en;q=0.1", &supported, "zh-Hant"), "en");
+        assert_eq!(ftl::negotiate_accept_language("@@@, pt;q=abc, *, en-US;q=0.2", &supported, "pt"), "en");
+        assert_eq!(ftl::negotiate_accept_language("", &supported, "en"), "en");
+        assert_eq!(ftl::negotiate_accept_language("fr-CA,fr;q=0.9", &supported, "en"), "en");
+    }
+
+    #[tokio::test]
+    async fn ftl_on_locale_change() {
+        let ftl = embedded_ftl();
+        let changes = Arc::new(Mutex::new(Vec::<String>::new()));
+        let handle = {
+            let changes = changes.clone();
+            ftl.on_locale_change(move |locale| changes.lock().unwrap().push(locale.to_owned()))
+        };
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        ftl.set_locale("en");
+        ftl.set_locale("en");
+        ftl.set_locale("pt-BR");
+        assert_eq!(*changes.lock().unwrap(), vec!["pt-BR", "en", "pt-BR"]);
+        drop(handle);
+        ftl.set_locale("en");
+        assert_eq!(changes.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn ftl_plurals() {
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["pl", "ar"])
+            .default_locale("pl")
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .embedded("pl", "_", "apples = { $count ->\n    [one] one\n    [few] few\n    [many] many\n   *[other] other\n}")
+                .embedded("ar", "_", "apples = { $count ->\n    [zero] zero\n    [one] one\n    [two] two\n    [few] few\n    [many] many\n   *[other] other\n}")
+                .load_method(FtlLoadMethod::Embedded)));
+        ftl.load_all().await.unwrap();
+        let category = |count: FtlValue| ftl.format("apples", FtlArgs::new().set("count", count)).unwrap();
+
+        ftl.set_locale("pl");
+        for (count, expected) in [(1, "one"), (2, "few"), (4, "few"), (5, "many"), (12, "many"), (14, "many"), (22, "few"), (25, "many"), (101, "many"), (0, "many")] {
+            assert_eq!(category(FtlValue::Integer(count)), expected, "pl {}", count);
+        }
+        assert_eq!(category(FtlValue::Float(1.5)), "other");
+
+        ftl.set_locale("ar");
+        for (count, expected) in [(0, "zero"), (1, "one"), (2, "two"), (3, "few"), (10, "few"), (11, "many"), (99, "many"), (100, "other"), (102, "other"), (103, "few"), (111, "many"), (1011, "many")] {
+            assert_eq!(category(FtlValue::Integer(count)), expected, "ar {}", count);
+        }
+    }
+
+    #[tokio::test]
+    async fn ftl_ordinals() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        let place = |n: i64| ftl.format("place", &ftl_args!{ "n" => n }).unwrap().replace(['\u{2068}', '\u{2069}'], "");
+        for (n, expected) in [(1, "1st"), (2, "2nd"), (3, "3rd"), (4, "4th"), (11, "11th"), (12, "12th"), (13, "13th"), (21, "21st"), (22, "22nd"), (23, "23rd"), (101, "101st"), (111, "111th")] {
+            assert_eq!(place(n), expected);
+        }
+        ftl.add_source("en", "rank", "rank = { NUMBER($n, type: \"ordinal\") ->\n    [one] st\n    [two] nd\n    [few] rd\n   *[other] th\n}\n").unwrap();
+        for (n, expected) in [(1, "st"), (2, "nd"), (23, "rd"), (11, "th"), (14, "th")] {
+            assert_eq!(ftl.format("rank", &ftl_args!{ "n" => n }).unwrap(), expected);
+        }
+        assert_eq!(ftl.ordinal_category(2), PluralCategory::Two);
+        assert_eq!(ftl.ordinal_category(3), PluralCategory::Few);
+        assert_eq!(ftl.ordinal_category(14), PluralCategory::Other);
+        ftl.set_locale("pt-BR");
+        assert_eq!(ftl.ordinal_category(1), PluralCategory::Other);
+    }
+
+    #[tokio::test]
+    async fn ftl_plural_category() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.plural_category(1), PluralCategory::One);
+        assert_eq!(ftl.plural_category(2), PluralCategory::Other);
+        assert_eq!(ftl.plural_category(1.5), PluralCategory::Other);
+        assert_eq!(ftl.plural_category("1"), PluralCategory::One);
+        assert_eq!(ftl.plural_category("1.0"), PluralCategory::Other);
+        assert_eq!(ftl.plural_category("many"), PluralCategory::Other);
+        ftl.set_locale("pt-BR");
+        assert_eq!(ftl.plural_category(0), PluralCategory::One);
+        assert_eq!(ftl.plural_category(1.5), PluralCategory::One);
+    }
+
+    #[test]
+    fn number_format() {
+        let options = NumberFormatOptions::new();
+        assert_eq!(number::format(&locale::locale!("en"), 1234567.5, &options), "1,234,567.5");
+        assert_eq!(number::format(&locale::locale!("de"), 1234567.5, &options), "1.234.567,5");
+        assert_eq!(number::format(&locale::locale!("en"), -0.12345, &options), "-0.123");
+        assert_eq!(number::format(&locale::locale!("en"), 2.0005, &options), "2");
+        assert_eq!(number::format(&locale::locale!("en"), 1234.5, NumberFormatOptions::new().use_grouping(false)), "1234.5");
+        assert_eq!(number::format(&locale::locale!("en"), 7.0, NumberFormatOptions::new().minimum_fraction_digits(2)), "7.00");
+        assert_eq!(number::format(&locale::locale!("en"), 7.256, NumberFormatOptions::new().maximum_fraction_digits(1)), "7.3");
+        assert_eq!(number::format(&locale::locale!("en"), 7.256, NumberFormatOptions::new().minimum_fraction_digits(2).maximum_fraction_digits(0)), "7.26");
+        assert_eq!(number::format(&locale::locale!("en"), f64::NAN, &options), "NaN");
+        assert_eq!(number::format(&locale::locale!("en"), f64::NEG_INFINITY, &options), "-∞");
+    }
+
+    #[test]
+    fn number_format_scientific() {
+        let mut options = NumberFormatOptions::new();
+        options.scientific(true).maximum_fraction_digits(2);
+        assert_eq!(number::format(&locale::locale!("en"), 12345.0, &options), "1.23E4");
+        assert_eq!(number::format(&locale::locale!("de"), 12345.0, &options), "1,23E4");
+        assert_eq!(number::format(&locale::locale!("en"), -0.00012, &options), "-1.2E-4");
+        assert_eq!(number::format(&locale::locale!("en"), 9999.0, &options), "1E4");
+        assert_eq!(number::format(&locale::locale!("en"), 0.0, &options), "0E0");
+        assert_eq!(number::format(&locale::locale!("en"), 5.0, options.minimum_fraction_digits(2)), "5.00E0");
+        assert_eq!(number::format(&locale::locale!("en"), f64::INFINITY, &options), "∞");
+    }
+
+    #[test]
+    fn number_format_significant_digits() {
+        let mut options = NumberFormatOptions::new();
+        options.maximum_significant_digits(3);
+        assert_eq!(number::format(&locale::locale!("en"), 12345.0, &options), "12,300");
+        assert_eq!(number::format(&locale::locale!("en"), 1.23456, &options), "1.23");
+        assert_eq!(number::format(&locale::locale!("en"), 0.00123456, &options), "0.00123");
+        assert_eq!(number::format(&locale::locale!("en"), 99.96, &options), "100");
+        assert_eq!(number::format(&locale::locale!("en"), 1.5, options.minimum_significant_digits(3)), "1.50");
+
+        // Significant digits take precedence over fraction digits.
+        let mut options = NumberFormatOptions::new();
+        options.minimum_fraction_digits(2).minimum_significant_digits(2);
+        assert_eq!(number::format(&locale::locale!("en"), 7.0, &options), "7.0");
+        assert_eq!(number::format(&locale::locale!("en"), 0.0, &options), "0.0");
+        assert_eq!(number::format(&locale::locale!("en"), 123.456, &options), "123.456");
+    }
+
+    #[test]
+    fn number_format_rounding_mode() {
+        let en = locale::locale!("en");
+        let mut options = NumberFormatOptions::new();
+        options.maximum_fraction_digits(0);
+        let cases = [
+            (RoundingMode::HalfEven, ["2", "4", "-2", "2"]),
+            (RoundingMode::HalfUp, ["3", "4", "-3", "2"]),
+            (RoundingMode::HalfDown, ["2", "3", "-2", "2"]),
+            (RoundingMode::Ceil, ["3", "4", "-2", "3"]),
+            (RoundingMode::Floor, ["2", "3", "-3", "2"]),
+            (RoundingMode::Trunc, ["2", "3", "-2", "2"]),
+        ];
+        for (mode, expected) in cases {
+            options.rounding_mode(mode);
+            let actual = [2.5, 3.5, -2.5, 2.1].map(|n| number::format(&en, n, &options));
+            assert_eq!(actual, expected, "{mode:?}");
+        }
+
+        let mut options = NumberFormatOptions::new();
+        options.maximum_significant_digits(2).rounding_mode(RoundingMode::Ceil);
+        assert_eq!(number::format(&en, 1201.0, &options), "1,300");
+        let mut options = NumberFormatOptions::new();
+        options.percent(true).maximum_fraction_digits(0).rounding_mode(RoundingMode::Floor);
+        assert_eq!(number::format(&en, -0.121, &options), "-13%");
+        let mut options = CurrencyFormatOptions::new();
+        options.rounding_mode(RoundingMode::Floor);
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), -1.231, "USD", &options), "-$1.24");
+    }
+
+    #[test]
+    fn number_default_currency() {
+        assert_eq!(number::default_currency("en-US").as_deref(), Some("USD"));
+        assert_eq!(number::default_currency("ja-JP").as_deref(), Some("JPY"));
+        assert_eq!(number::default_currency("de-AT").as_deref(), Some("EUR"));
+        assert_eq!(number::default_currency("pt-BR").as_deref(), Some("BRL"));
+        assert_eq!(number::default_currency("en-US-u-cu-eur").as_deref(), Some("EUR"));
+        assert_eq!(number::default_currency("en"), None);
+        assert_eq!(number::default_currency("en-001"), None);
+        assert_eq!(number::default_currency("not a locale"), None);
+    }
+
+    #[test]
+    fn number_format_bidi_marks() {
+        let mut options = NumberFormatOptions::new();
+        options.bidi_marks(true);
+        assert_eq!(number::format(&locale::locale!("en"), -1234.5, &options), "\u{200e}-1,234.5\u{200e}");
+        assert_eq!(number::format(&locale::locale!("he"), 5.0, &options), "\u{200f}5\u{200f}");
+        assert!(number::format(&locale::locale!("ar"), 0.5, options.percent(true)).starts_with('\u{200f}'));
+        assert_eq!(number::format_unit(&locale::locale!("en"), 5.0, Unit::Kilometer, UnitStyle::Short, &options), "\u{200e}5 km\u{200e}");
+        assert_eq!(number::format(&locale::locale!("he"), 5.0, &NumberFormatOptions::new()), "5");
+    }
+
+    #[test]
+    fn number_format_currency() {
+        let options = CurrencyFormatOptions::new();
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1234.5, "USD", &options), "$1,234.50");
+        assert_eq!(number::format_currency(&locale::locale!("de-DE"), 1234.5, "EUR", &options), "1.234,50\u{a0}€");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1234.5, "JPY", &options), "¥1,234");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1235.5, "JPY", &options), "¥1,236");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1234.5, "JPY", CurrencyFormatOptions::new().rounding_mode(RoundingMode::HalfUp)), "¥1,235");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1.2346, "KWD", &options), "KWD\u{a0}1.235");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), -5.0, "usd", &options), "-$5.00");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1234.5, "USD", CurrencyFormatOptions::new().use_grouping(false)), "$1234.50");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 9876543210.0, "XYZ", &options), "XYZ\u{a0}9,876,543,210.00");
+        assert_eq!(number::format_currency(&locale::locale!("fr-FR"), 1234.5, "EUR", &options), "1\u{202f}234,50\u{a0}€");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1234.5, "USD", CurrencyFormatOptions::new().display(CurrencyDisplay::Code)), "USD\u{a0}1,234.50");
+        assert_eq!(number::format_currency(&locale::locale!("de-DE"), 1234.5, "EUR", CurrencyFormatOptions::new().display(CurrencyDisplay::Code)), "1.234,50\u{a0}EUR");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1234.5, "USD", CurrencyFormatOptions::new().display(CurrencyDisplay::Name)), "1,234.50 US dollars");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), -1.0, "JPY", CurrencyFormatOptions::new().display(CurrencyDisplay::Name)), "-1 Japanese yen");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1.0, "EUR", CurrencyFormatOptions::new().display(CurrencyDisplay::Name)), "1.00 euros");
+        assert_eq!(number::format_currency(&locale::locale!("en-US"), 1.0, "KWD", CurrencyFormatOptions::new().display(CurrencyDisplay::Name)), "1.000 Kuwaiti dinars");
+        assert_eq!(number::format_currency(&locale::locale!("de-DE"), 1234.5, "EUR", CurrencyFormatOptions::new().display(CurrencyDisplay::Name)), "1.234,50\u{a0}EUR");
+        assert_eq!(number::currency_display_for(&locale::locale!("de-DE"), "EUR", CurrencyDisplay::Name), CurrencyDisplay::Code);
+        assert_eq!(number::currency_display_for(&locale::locale!("en-US"), "eur", CurrencyDisplay::Name), CurrencyDisplay::Name);
+        assert_eq!(number::currency_display_for(&locale::locale!("en-US"), "XYZ", CurrencyDisplay::Name), CurrencyDisplay::Code);
+        assert_eq!(number::currency_display_for(&locale::locale!("de-DE"), "EUR", CurrencyDisplay::Symbol), CurrencyDisplay::Symbol);
+    }
+
+    #[test]
+    fn datetime_format() {
+        // 2024-03-09T16:00:00Z
+        let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(1_710_000_000);
+        let en = locale::locale!("en-US");
+        let de = locale::locale!("de-DE");
+        assert_eq!(datetime::format_date(&en, ts, DateTimeStyle::Short), "3/9/24");
+        assert_eq!(datetime::format_date(&de, ts, DateTimeStyle::Medium), "09.03.2024");
+        assert_eq!(datetime::format_date(&en, ts, DateTimeStyle::Full), "Saturday, March 9, 2024");
+        assert_eq!(datetime::format_date(&de, ts, DateTimeStyle::Long), "9. März 2024");
+        assert_eq!(datetime::format_time(&en, ts, DateTimeStyle::Short), "4:00\u{202f}PM");
+        assert_eq!(datetime::format_time(&de, ts, DateTimeStyle::Long), "16:00:00 GMT");
+        assert_eq!(datetime::format(&en, ts, DateTimeStyle::Short), "3/9/24, 4:00\u{202f}PM");
+        assert_eq!(datetime::format(&de, ts, DateTimeStyle::Full), "Samstag, 9. März 2024, 16:00:00 GMT");
+
+        // 1969-12-31T23:59:59Z
+        let before_epoch = SystemTime::UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(datetime::format(&de, before_epoch, DateTimeStyle::Medium), "31.12.1969, 23:59:59");
+    }
+
+    #[test]
+    fn relative_time_format() {
+        let en = locale::locale!("en");
+        assert_eq!(relative_time::format(&en, 3, RelativeTimeUnit::Day), "in 3 days");
+        assert_eq!(relative_time::format(&en, -3, RelativeTimeUnit::Day), "3 days ago");
+        assert_eq!(relative_time::format(&en, 1, RelativeTimeUnit::Second), "in 1 second");
+        assert_eq!(relative_time::format(&en, -1, RelativeTimeUnit::Year), "1 year ago");
+        assert_eq!(relative_time::format(&en, -1500, RelativeTimeUnit::Hour), "1,500 hours ago");
+        assert_eq!(relative_time::format(&en, 2, RelativeTimeUnit::Quarter), "in 2 quarters");
+        assert_eq!(relative_time::format(&locale::locale!("de"), -3, RelativeTimeUnit::Minute), "vor 3 Minuten");
+        assert_eq!(relative_time::format(&locale::locale!("es"), 1, RelativeTimeUnit::Week), "dentro de 1 semana");
+    }
+
+    #[test]
+    fn list_format() {
+        let en = locale::locale!("en");
+        let es = locale::locale!("es");
+        assert_eq!(list::format(&en, &["A", "B", "C"], ListStyle::Conjunction), "A, B, and C");
+        assert_eq!(list::format(&es, &["A", "B", "C"], ListStyle::Conjunction), "A, B y C");
+        assert_eq!(list::format(&en, &["A", "B"], ListStyle::Conjunction), "A and B");
+        assert_eq!(list::format(&es, &["A", "B"], ListStyle::Disjunction), "A o B");
+        assert_eq!(list::format(&en, &["A", "B", "C"], ListStyle::Disjunction), "A, B, or C");
+        assert_eq!(list::format(&en, &["3 feet", "7 inches"], ListStyle::Unit), "3 feet, 7 inches");
+        assert_eq!(list::format(&en, &["A"], ListStyle::Conjunction), "A");
+        assert_eq!(list::format(&en, &[], ListStyle::Conjunction), "");
+        // "y" becomes "e" before an "i" sound
+        assert_eq!(list::format(&es, &["Juan", "Irene"], ListStyle::Conjunction), "Juan e Irene");
+    }
+
+    #[tokio::test]
+    async fn ftl_formatting() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.format_number(1234567.5, &NumberFormatOptions::new()), "1,234,567.5");
+        ftl.set_locale("pt-BR");
+        assert_eq!(ftl.format_number(1234567.5, NumberFormatOptions::new().minimum_fraction_digits(2)), "1.234.567,50");
+        assert_eq!(ftl.format_currency(1234.5, "BRL", &CurrencyFormatOptions::new()), "R$\u{a0}1.234,50");
+        let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(1_710_000_000);
+        assert_eq!(ftl.format_datetime(ts, DateTimeStyle::Short), "09/03/2024 16:00");
+        assert_eq!(ftl.format_date(ts, DateTimeStyle::Long), "9 de março de 2024");
+        assert_eq!(ftl.format_time(ts, DateTimeStyle::Medium), "16:00:00");
+        assert_eq!(ftl.format_relative_time(-2, RelativeTimeUnit::Month), "há 2 meses");
+        assert_eq!(ftl.format_relative_time(1, RelativeTimeUnit::Day), "em 1 dia");
+        assert_eq!(ftl.format_list(&["X", "Y", "Z"], ListStyle::Conjunction), "X, Y e Z");
+    }
+
+    #[tokio::test]
+    async fn ftl_add_function() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "pt-BR"])
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "sword = Get the { ASSET_NAME($id) }!\ntwice = { DOUBLE($n) }\nshout = { UPPER(\"hi\", suffix: \"!\") }")
+                    .embedded("pt-BR", "_", "sword = Pegue a { ASSET_NAME($id) }!")));
+        assert_eq!(ftl.add_function("NUMBER", |_, _| FtlValue::Integer(0)), Err(FtlError::ReservedFunction("NUMBER".to_owned())));
+        assert_eq!(ftl.add_function("DATETIME", |_, _| FtlValue::Integer(0)), Err(FtlError::ReservedFunction("DATETIME".to_owned())));
+        ftl.add_function("ASSET_NAME", |positional, _| {
+            match positional.first() {
+                Some(FtlValue::String(id)) if id == "sword" => "Sword of Dawn".into(),
+                _ => "?".into(),
+            }
+        }).unwrap();
+        ftl.add_function("DOUBLE", |positional, _| {
+            match positional.first() {
+                Some(FtlValue::Integer(n)) => FtlValue::Integer(n * 2),
+                Some(FtlValue::Float(n)) => FtlValue::Float(n * 2.0),
+                _ => FtlValue::Integer(0),
+            }
+        }).unwrap();
+        ftl.add_function("UPPER", |positional, named| {
+            let suffix = match named.get("suffix") {
+                Some(FtlValue::String(s)) => s.clone(),
+                _ => String::new(),
+            };
+            match positional.first() {
+                Some(FtlValue::String(s)) => FtlValue::String(s.to_uppercase() + &suffix),
+                _ => FtlValue::String(suffix),
+            }
+        }).unwrap();
+        ftl.load_all().await.unwrap();
+        let unisolated = |s: String| s.replace(['\u{2068}', '\u{2069}'], "");
+
+        ftl.set_locale("en");
+        assert_eq!(unisolated(ftl.format("sword", &ftl_args!{ "id" => "sword" }).unwrap()), "Get the Sword of Dawn!");
+        assert_eq!(unisolated(ftl.format("twice", &ftl_args!{ "n" => 21 }).unwrap()), "42");
+        assert_eq!(unisolated(ftl.format("twice", &ftl_args!{ "n" => 1.25 }).unwrap()), "2.5");
+        assert_eq!(ftl.get_message("shout").unwrap(), "HI!");
+        assert!(matches!(ftl.format("sword", &FtlArgs::new()), Err(FtlError::MissingVariable { .. })));
+        ftl.set_locale("pt-BR");
+        assert_eq!(unisolated(ftl.format("sword", &ftl_args!{ "id" => "sword" }).unwrap()), "Pegue a Sword of Dawn!");
+    }
+
+    #[tokio::test]
+    async fn ftl_message_ids() {
+        let ftl = embedded_ftl();
+        assert!(!ftl.has_message("hello-world"));
+        assert!(ftl.message_ids().is_empty());
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert!(ftl.has_message("score"));
+        // Defined by the `en` fallback only.
+        assert!(ftl.has_message("bye"));
+        assert!(!ftl.has_message("undefined"));
+        assert_eq!(ftl.message_ids(), ["bye", "hello-world", "login", "place", "score", "welcome"]);
+        ftl.set_locale("en");
+        assert!(!ftl.has_message("score"));
+        assert_eq!(ftl.message_ids(), ["bye", "hello-world", "login", "place", "welcome"]);
+    }
+
+    #[tokio::test]
+    async fn ftl_get_attribute() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.get_attribute("login", "tooltip", &FtlArgs::new()), None);
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.get_message("login").unwrap(), "Entrar");
+        assert_eq!(ftl.get_attribute("login", "tooltip", &FtlArgs::new()).unwrap(), "Clique para entrar");
+        // Defined by the `en` fallback only.
+        assert_eq!(ftl.get_attribute("login", "aria-label", &ftl_args!{ "name" => "Ada" }).unwrap(), "Log in as \u{2068}Ada\u{2069}");
+        assert_eq!(ftl.get_attribute("login", "undefined", &FtlArgs::new()), None);
+        assert_eq!(ftl.get_attribute("undefined", "tooltip", &FtlArgs::new()), None);
+        ftl.set_locale("en");
+        assert_eq!(ftl.get_attribute("login", "tooltip", &FtlArgs::new()).unwrap(), "Click to sign in");
+    }
+
+    #[tokio::test]
+    async fn ftl_terms() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .on_missing(MissingBehavior::Error)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", concat!(
+                        "-brand-name = { $case ->\n",
+                        "    [possessive] Rialight's\n",
+                        "   *[nominative] Rialight\n",
+                        "}\n",
+                        "    .article = the\n",
+                        "welcome = Welcome to { -brand-name }\n",
+                        "thanks = Thanks for using { -brand-name }!\n",
+                        "docs = Read { -brand-name(case: \"possessive\") } docs\n",
+                        "about = { -brand-name.article ->\n",
+                        "    [the] About the engine\n",
+                        "   *[other] About\n",
+                        "}\n",
+                    ))));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("welcome").unwrap(), "Welcome to Rialight");
+        assert_eq!(ftl.get_message("thanks").unwrap(), "Thanks for using Rialight!");
+        assert_eq!(ftl.get_message("docs").unwrap(), "Read Rialight's docs");
+        assert_eq!(ftl.get_message("about").unwrap(), "About the engine");
+
+        // Terms are private.
+        assert_eq!(ftl.get_message("-brand-name"), None);
+        assert_eq!(ftl.get_message("brand-name"), None);
+        assert!(!ftl.has_message("-brand-name"));
+        assert_eq!(ftl.format("-brand-name", &FtlArgs::new()), Err(FtlError::MessageNotFound("-brand-name".to_owned())));
+        assert!(!ftl.message_ids().contains(&"brand-name".to_owned()));
+    }
+
+    #[tokio::test]
+    async fn ftl_pseudolocalization() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.pseudolocalization(), None);
+        ftl.enable_pseudolocalization(PseudoStyle::Accented);
+        assert_eq!(ftl.pseudolocalization(), Some(PseudoStyle::Accented));
+        assert_eq!(ftl.get_message("bye").unwrap(), "[Ɓẏḗ!~~]");
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "[Ħḗŀŀǿ, ẇǿřŀḓ!~~~~~~]");
+        // Arguments are kept intact.
+        assert_eq!(ftl.format("welcome", &ftl_args!{ "name" => "Ada" }).unwrap(), "[Ẇḗŀƈǿḿḗ, \u{2068}Ada\u{2069}!~~~~~~]");
+        assert_eq!(ftl.get_attribute("login", "tooltip", &FtlArgs::new()).unwrap(), "[Ƈŀīƈķ ŧǿ şīɠƞ īƞ~~~~~~~]");
+
+        ftl.enable_pseudolocalization(PseudoStyle::Bidi);
+        assert_eq!(ftl.get_message("bye").unwrap(), "[\u{202e}Ԑʎǝ!\u{202c}~~]");
+
+        // Locales loaded afterwards are pseudolocalized too.
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.format("score", &ftl_args!{ "score" => 10 }).unwrap(), "[\u{2068}10\u{2069}\u{202e} douʇos\u{202c}~~~~]");
+
+        ftl.disable_pseudolocalization();
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Olá, mundo!");
+    }
+
+    #[tokio::test]
+    async fn ftl_parse_errors() {
+        let malformed_ftl = |strict: bool| Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "pt-BR"])
+                .assets(FtlOptionsForAssets::new()
+                    .source("res/lang")
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .strict(strict)
+                    .embedded("en", "_", "hello = Hello\nbroken = { $a\nbye = Bye\n")
+                    .embedded("pt-BR", "_", "olá = Olá\n")));
+
+        let ftl = malformed_ftl(false);
+        let errors = ftl.load_all().await.unwrap();
+        assert_eq!(errors, vec![
+            FtlParseError {
+                locale: "en".to_owned(),
+                file: "res/lang/en/_.ftl".to_owned(),
+                line: 3,
+                column: 1,
+                message: "Expected a token starting with \"}\"".to_owned(),
+            },
+            FtlParseError {
+                locale: "pt-BR".to_owned(),
+                file: "res/lang/pt-BR/_.ftl".to_owned(),
+                // Identifiers are ASCII.
+                line: 1,
+                column: 3,
+                message: "Expected a token starting with \"=\"".to_owned(),
+            },
+        ]);
+        assert_eq!(errors[0].to_string(), "res/lang/en/_.ftl:3:1: Expected a token starting with \"}\"");
+        // Valid entries are still loaded.
+        ftl.set_locale("en");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+        assert_eq!(ftl.get_message("bye").unwrap(), "Bye");
+        assert!(!ftl.has_message("broken"));
+
+        let ftl = malformed_ftl(true);
+        assert!(matches!(ftl.load_locale("en").await, Err(FtlError::Parse(errors)) if errors.len() == 1));
+        assert!(matches!(ftl.load_all().await, Err(FtlError::Parse(_))));
+        assert!(!ftl.load(None).await);
+    }
+
+    #[tokio::test]
+    async fn ftl_watch() {
+        let dir = std::env::temp_dir().join(format!("rialight_intl_watch_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("en")).unwrap();
+        let file = dir.join("en/_.ftl");
+        std::fs::write(&file, "hello = Hello\n").unwrap();
+
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .assets(FtlOptionsForAssets::new()
+                    .source(dir.to_str().unwrap())
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::FileSystem)
+                    .watch_interval(Duration::from_millis(10))));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+
+        let reloaded = Arc::new(Mutex::new(Vec::<String>::new()));
+        let _subscription = {
+            let reloaded = reloaded.clone();
+            ftl.on_locale_change(move |locale| reloaded.lock().unwrap().push(locale.to_owned()))
+        };
+        let wait_for = |expected: &str| {
+            for _ in 0..200 {
+                if ftl.get_message("hello").as_deref() == Some(expected) {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            false
+        };
+
+        let handle = ftl.watch().unwrap();
+        std::fs::write(&file, "hello = Hi\n").unwrap();
+        assert!(wait_for("Hi"));
+        assert_eq!(*reloaded.lock().unwrap(), vec!["en"]);
+
+        // A resource with syntax errors keeps the previous one.
+        std::fs::write(&file, "hello = { $a\n").unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hi");
+
+        drop(handle);
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(&file, "hello = Hey\n").unwrap();
+        assert!(!wait_for("Hey"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(embedded_ftl().watch(), Err(FtlError::UnsupportedLoadMethod)));
+    }
+
+    #[tokio::test]
+    async fn ftl_add_source() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "pt-BR"])
+                .default_locale("en")
+                .fallbacks(hashmap! {
+                    "pt-BR" => vec!["en"],
+                })
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Memory)));
+        assert_eq!(ftl.add_source("en", "_", "hello = Hello\nbye = Bye\n").unwrap(), vec![]);
+        assert_eq!(ftl.add_source("pt-BR", "_", "hello = Olá\n").unwrap(), vec![]);
+        assert_eq!(ftl.add_source("fr", "_", "hello = Bonjour\n"), Err(FtlError::UnsupportedLocale("fr".to_owned())));
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Olá");
+        // In-memory resources participate in fallbacks.
+        assert_eq!(ftl.get_message("bye").unwrap(), "Bye");
+
+        // Loaded locales are rebuilt.
+        assert_eq!(ftl.add_source("pt-BR", "generated", "bye = Tchau\n").unwrap(), vec![]);
+        assert_eq!(ftl.get_message("bye").unwrap(), "Tchau");
+        assert_eq!(ftl.add_source("pt-BR", "_", "hello = Oi\n").unwrap(), vec![]);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Oi");
+        // Later resources override earlier ones.
+        assert_eq!(ftl.add_source("pt-BR", "other", "bye = Adeus\n").unwrap(), vec![]);
+        assert_eq!(ftl.get_message("bye").unwrap(), "Adeus");
+        assert_eq!(ftl.add_source("pt-BR", "broken", "broken = { $a\n").unwrap().len(), 1);
+
+        // Added resources supplement the configured ones.
+        let ftl = embedded_ftl();
+        ftl.add_source("en", "extra", "extra = Extra\n").unwrap();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        assert_eq!(ftl.get_message("extra").unwrap(), "Extra");
+    }
+
+    #[tokio::test]
+    async fn ftl_on_missing() {
+        let missing_ftl = |on_missing: Option<MissingBehavior>| {
+            let mut options = FtlOptions::new();
+            options
+                .supported_locales(vec!["en"])
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "hello = Hello\n"));
+            if let Some(on_missing) = on_missing {
+                options.on_missing(on_missing);
+            }
+            Ftl::new(&mut options)
+        };
+
+        let ftl = missing_ftl(None);
+        assert!(ftl.load(None).await);
+        let default = if cfg!(debug_assertions) { Some("missing".to_owned()) } else { None };
+        assert_eq!(ftl.get_message("missing"), default);
+
+        let ftl = missing_ftl(Some(MissingBehavior::ReturnId));
+        assert!(ftl.get_message("missing").is_none());
+        assert_eq!(ftl.format("missing", &FtlArgs::new()), Err(FtlError::NoLocaleLoaded));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+        assert_eq!(ftl.get_message("missing").unwrap(), "missing");
+        assert_eq!(ftl.format("missing", &FtlArgs::new()).unwrap(), "missing");
+        assert_eq!(ftl.get_attribute("hello", "tooltip", &FtlArgs::new()), None);
+
+        let ftl = missing_ftl(Some(MissingBehavior::ReturnEmpty));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("missing").unwrap(), "");
+        assert_eq!(ftl.format("missing", &FtlArgs::new()).unwrap(), "");
+
+        let ftl = missing_ftl(Some(MissingBehavior::Error));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("missing"), None);
+        assert_eq!(ftl.format("missing", &FtlArgs::new()), Err(FtlError::MessageNotFound("missing".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn ftl_fallback_chain() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "es", "pt", "pt-BR"])
+                .default_locale("en")
+                .fallbacks(hashmap! {
+                    "pt-BR" => vec!["pt"],
+                    "pt" => vec!["pt-BR"],
+                    "es" => vec!["es"],
+                })
+                .on_missing(MissingBehavior::Error)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "one = One\ntwo = Two\nthree = Three\n")
+                    .embedded("es", "_", "one = Uno\n")
+                    .embedded("pt", "_", "one = Um\ntwo = Dois\n")
+                    .embedded("pt-BR", "_", "one = Um (Brasil)\n")));
+        assert_eq!(ftl.fallback_chain("pt-BR"), vec!["pt-BR", "pt", "en"]);
+        assert_eq!(ftl.fallback_chain("pt"), vec!["pt", "pt-BR", "en"]);
+        assert_eq!(ftl.fallback_chain("es"), vec!["es", "en"]);
+        assert_eq!(ftl.fallback_chain("en"), vec!["en"]);
+        assert_eq!(ftl.fallback_chain("fr"), Vec::<String>::new());
+
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.get_message("one").unwrap(), "Um (Brasil)");
+        assert_eq!(ftl.get_message("two").unwrap(), "Dois");
+        // The default locale is tried last.
+        assert_eq!(ftl.get_message("three").unwrap(), "Three");
+        assert!(ftl.has_message("three"));
+        assert_eq!(ftl.format("missing", &FtlArgs::new()), Err(FtlError::MessageNotFound("missing".to_owned())));
+        assert!(!ftl.has_message("missing"));
+
+        assert!(ftl.load(Some(locale::locale!("es"))).await);
+        assert_eq!(ftl.get_message("two").unwrap(), "Two");
+    }
+
+    #[tokio::test]
+    async fn ftl_lazy() {
+        let lazy_ftl = |lazy: bool| Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "es", "pt-BR"])
+                .default_locale("en")
+                .lazy(lazy)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "hello = Hello\n")
+                    .embedded("es", "_", "hello = Hola\n")
+                    .embedded("pt-BR", "_", "hello = Olá\n")));
+
+        let ftl = lazy_ftl(true);
+        assert_eq!(ftl.load_all().await.unwrap(), vec![]);
+        assert!(ftl.is_loaded("en"));
+        assert!(!ftl.is_loaded("es"));
+        assert!(!ftl.is_loaded("pt-BR"));
+        assert_eq!(ftl.switch_locale("pt-BR").await.unwrap(), "pt-BR");
+        assert!(ftl.is_loaded("pt-BR"));
+        assert_eq!(ftl.get_message("hello").unwrap(), "Olá");
+        assert_eq!(ftl.switch_locale("es-MX").await.unwrap(), "es");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hola");
+        // Previously loaded locales are kept.
+        assert!(ftl.is_loaded("pt-BR"));
+        assert_eq!(ftl.switch_locale("fr").await.unwrap(), "en");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+
+        let ftl = lazy_ftl(false);
+        assert_eq!(ftl.load_all().await.unwrap(), vec![]);
+        assert!(ftl.is_loaded("es"));
+        assert!(ftl.is_loaded("pt-BR"));
+    }
+
+    #[tokio::test]
+    async fn ftl_load_all_with_progress() {
+        let ftl = embedded_ftl();
+        let mut progress = vec![];
+        assert_eq!(ftl.load_all_with_progress(|completed, total| progress.push((completed, total))).await.unwrap(), vec![]);
+        assert_eq!(progress, [(1, 2), (2, 2)]);
+        assert!(ftl.is_loaded("pt-BR"));
+    }
+
+    #[tokio::test]
+    async fn ftl_preload_all() {
+        let ftl = |default_source: &'static str| Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "es", "pt-BR"])
+                .default_locale("en")
+                .lazy(true)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .strict(true)
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", default_source)
+                    .embedded("es", "_", "hello\n")
+                    .embedded("pt-BR", "_", "hello = Olá\n")));
+
+        let ftl_ok = ftl("hello = Hello\n");
+        let failures = ftl_ok.preload_all().await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "es");
+        assert!(matches!(failures[0].1, FtlError::Parse(_)));
+        assert!(ftl_ok.is_loaded("en"));
+        assert!(!ftl_ok.is_loaded("es"));
+        assert!(ftl_ok.is_loaded("pt-BR"));
+        ftl_ok.set_locale("pt-BR");
+        assert_eq!(ftl_ok.get_message("hello").unwrap(), "Olá");
+
+        assert!(matches!(ftl("hello\n").preload_all().await, Err(FtlError::Parse(_))));
+    }
+
+    #[tokio::test]
+    async fn ftl_unload_locale() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "es", "pt", "pt-BR"])
+                .default_locale("en")
+                .fallbacks(hashmap! {
+                    "pt-BR" => vec!["pt"],
+                })
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "hello = Hello\n")
+                    .embedded("es", "_", "hello = Hola\n")
+                    .embedded("pt", "_", "hello = Olá\n")
+                    .embedded("pt-BR", "_", "bye = Tchau\n")));
+        assert_eq!(ftl.load_all().await.unwrap(), vec![]);
+        assert_eq!(ftl.unload_locale("en"), Err(FtlError::LocaleInUse("en".to_owned())));
+        assert_eq!(ftl.unload_locale("fr"), Err(FtlError::UnsupportedLocale("fr".to_owned())));
+
+        assert_eq!(ftl.switch_locale("pt-BR").await.unwrap(), "pt-BR");
+        assert_eq!(ftl.unload_locale("pt-BR"), Err(FtlError::LocaleInUse("pt-BR".to_owned())));
+        assert_eq!(ftl.unload_locale("pt"), Err(FtlError::LocaleInUse("pt".to_owned())));
+        assert_eq!(ftl.unload_locale("es"), Ok(()));
+        assert!(!ftl.is_loaded("es"));
+        // Unloading a locale that is not loaded does nothing.
+        assert_eq!(ftl.unload_locale("es"), Ok(()));
+
+        // Unloaded locales are loaded again when selected.
+        assert_eq!(ftl.switch_locale("es").await.unwrap(), "es");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hola");
+        assert_eq!(ftl.unload_locale("pt-BR"), Ok(()));
+        assert!(!ftl.is_loaded("pt-BR"));
+        assert!(ftl.is_loaded("pt"));
+    }
+
+    #[tokio::test]
+    async fn ftl_format_into() {
+        let ftl = embedded_ftl();
+        let mut buffer = String::with_capacity(64);
+        assert_eq!(ftl.format_into(&mut buffer, "bye", &FtlArgs::new()), Err(FtlError::NoLocaleLoaded));
+        assert!(ftl.load(None).await);
+        let capacity = buffer.capacity();
+        for name in ["Ada", "Grace"] {
+            buffer.clear();
+            ftl.format_into(&mut buffer, "welcome", &ftl_args!{ "name" => name }).unwrap();
+            assert_eq!(buffer, format!("Welcome, \u{2068}{name}\u{2069}!"));
+        }
+        assert_eq!(buffer.capacity(), capacity);
+
+        // Messages are appended.
+        ftl.format_into(&mut buffer, "bye", &FtlArgs::new()).unwrap();
+        assert_eq!(buffer, "Welcome, \u{2068}Grace\u{2069}!Bye!");
+        buffer.clear();
+        assert!(matches!(ftl.format_into(&mut buffer, "welcome", &FtlArgs::new()), Err(FtlError::MissingVariable { .. })));
+        assert_eq!(ftl.format_into(&mut buffer, "missing", &FtlArgs::new()), Err(FtlError::MessageNotFound("missing".to_owned())));
+
+        ftl.enable_pseudolocalization(PseudoStyle::Accented);
+        buffer.clear();
+        ftl.format_into(&mut buffer, "bye", &FtlArgs::new()).unwrap();
+        assert_eq!(buffer, ftl.get_message("bye").unwrap());
+    }
+
+    #[tokio::test]
+    async fn text_direction() {
+        assert!(text_direction_of("en") == TextDirection::Ltr);
+        assert!(text_direction_of("ar-EG") == TextDirection::Rtl);
+        assert!(text_direction_of("he") == TextDirection::Rtl);
+        assert!(text_direction_of("fa") == TextDirection::Rtl);
+        assert!(text_direction_of("az") == TextDirection::Ltr);
+        assert!(text_direction_of("az-Arab") == TextDirection::Rtl);
+        assert!(text_direction_of("sr-Cyrl") == TextDirection::Ltr);
+        assert!(text_direction_of("!") == TextDirection::Ltr);
+        assert!(locale::locale!("ur").text_direction() == TextDirection::Rtl);
+
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "ar"])
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "hello = Hello\n")
+                    .embedded("ar", "_", "hello = مرحبا\n")));
+        assert!(ftl.text_direction() == TextDirection::Ltr);
+        assert_eq!(ftl.switch_locale("ar-SA").await.unwrap(), "ar");
+        assert!(ftl.text_direction() == TextDirection::Rtl);
+    }
+
+    #[tokio::test]
+    async fn ftl_use_isolating() {
+        let isolating_ftl = |use_isolating: bool| Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .use_isolating(use_isolating)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "welcome = Welcome, {$name}!\ncount = {$n} items\n")));
+        let ftl = isolating_ftl(false);
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.format("welcome", &ftl_args!{ "name" => "Ada" }).unwrap(), "Welcome, Ada!");
+        assert_eq!(ftl.format("count", &ftl_args!{ "n" => 1234 }).unwrap(), "1,234 items");
+
+        let ftl = isolating_ftl(true);
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.format("welcome", &ftl_args!{ "name" => "Ada" }).unwrap(), "Welcome, \u{2068}Ada\u{2069}!");
+    }
+
+    #[tokio::test]
+    async fn ftl_validate() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "pl", "pt-BR"])
+                .default_locale("en")
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", concat!(
+                        "welcome = Welcome, {$name}!\n",
+                        "items = { $n ->\n    [one] One item\n   *[other] {$n} items\n}\n",
+                        "pronoun = { $g ->\n    [male] He\n   *[other] They\n}\n",
+                        "only-en = Only in English\n",
+                    ))
+                    .embedded("pl", "_", concat!(
+                        "welcome = Witaj, {$name}!\n",
+                        "items = { $n ->\n    [one] Jeden\n    [few] {$n} elementy\n   *[many] {$n} elementów\n}\n",
+                        "pronoun = { $g ->\n    [male] On\n   *[other] Oni\n}\n",
+                        "only-en = Tylko po angielsku\n",
+                        "only-pl = Tylko po polsku\n",
+                    ))
+                    .embedded("pt-BR", "_", concat!(
+                        "welcome = Bem-vindo, {$naam}!\n",
+                        "items = { $n ->\n    [one] Um item\n   *[other] {$n} itens\n}\n",
+                        "pronoun = { $g ->\n    [masculine] Ele\n   *[other] Eles\n}\n",
+                    ))));
+        assert_eq!(ftl.validate(), vec![]);
+        ftl.load_all().await.unwrap();
+        let issues = ftl.validate();
+        assert_eq!(issues, vec![
+            FtlValidationIssue::MissingMessage { id: "only-en".to_owned(), locale: "pt-BR".to_owned() },
+            FtlValidationIssue::MissingMessage { id: "only-pl".to_owned(), locale: "en".to_owned() },
+            FtlValidationIssue::MissingMessage { id: "only-pl".to_owned(), locale: "pt-BR".to_owned() },
+            FtlValidationIssue::MismatchedSelectArms {
+                id: "pronoun".to_owned(),
+                locale: "pt-BR".to_owned(),
+                reference_locale: "en".to_owned(),
+                arms: vec!["masculine".to_owned(), "other".to_owned()],
+                reference_arms: vec!["male".to_owned(), "other".to_owned()],
+            },
+            FtlValidationIssue::MismatchedVariables {
+                id: "welcome".to_owned(),
+                locale: "pt-BR".to_owned(),
+                reference_locale: "en".to_owned(),
+                variables: vec!["naam".to_owned()],
+                reference_variables: vec!["name".to_owned()],
+            },
+        ]);
+        assert_eq!(issues[4].to_string(), "message welcome references [naam] in locale pt-BR, but [name] in locale en");
+    }
+
+    #[tokio::test]
+    async fn ftl_unused_keys() {
+        let usage_ftl = |track_usage: bool| Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en", "pt-BR"])
+                .default_locale("en")
+                .track_usage(track_usage)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "a = A\nb = B\nc = C\n    .tooltip = Tooltip\nd = D {$x}\ne = E\n")
+                    .embedded("pt-BR", "_", "a = A\nf = F\n")));
+        let ftl = usage_ftl(true);
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.unused_keys(), vec!["a", "b", "c", "d", "e"]);
+        ftl.get_message("a");
+        ftl.get_attribute("c", "tooltip", &FtlArgs::new());
+        ftl.format("d", &ftl_args!{ "x" => 1 }).unwrap();
+        // Clones share the recorded usage.
+        ftl.clone().get_message("b");
+        assert_eq!(ftl.unused_keys(), vec!["e"]);
+
+        let ftl = usage_ftl(false);
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.unused_keys(), Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn ftl_format_segments() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .on_missing(MissingBehavior::ReturnId)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", concat!(
+                        "-brand = Rialight\n",
+                        "jump = Press { $key } to jump\n",
+                        "score = { $name }: { $points } points in { -brand }\n",
+                        "items = { $n ->\n    [one] One item\n   *[other] { $n } items\n}!\n",
+                        "plain = Plain text\n",
+                    ))));
+        assert!(ftl.load(None).await);
+        let text = |s: &str| FtlSegment::Text(s.to_owned());
+        let variable = |value: &str, name: &str| FtlSegment::Placeable { value: value.to_owned(), variable: Some(name.to_owned()) };
+        assert_eq!(ftl.format_segments("jump", &ftl_args!{ "key" => "Space" }).unwrap(), vec![
+            text("Press "),
+            variable("Space", "key"),
+            text(" to jump"),
+        ]);
+        assert_eq!(ftl.format_segments("score", &ftl_args!{ "name" => "Ada", "points" => 1500 }).unwrap(), vec![
+            variable("Ada", "name"),
+            text(": "),
+            variable("1,500", "points"),
+            text(" points in "),
+            FtlSegment::Placeable { value: "Rialight".to_owned(), variable: None },
+        ]);
+        assert_eq!(ftl.format_segments("items", &ftl_args!{ "n" => 3 }).unwrap(), vec![
+            FtlSegment::Placeable { value: "\u{2068}3\u{2069} items".to_owned(), variable: None },
+            text("!"),
+        ]);
+        assert_eq!(ftl.format_segments("plain", &FtlArgs::new()).unwrap(), vec![text("Plain text")]);
+        assert!(matches!(ftl.format_segments("jump", &FtlArgs::new()), Err(FtlError::MissingVariable { .. })));
+        assert_eq!(ftl.format_segments("missing", &FtlArgs::new()).unwrap(), vec![text("missing")]);
+
+        ftl.enable_pseudolocalization(PseudoStyle::Accented);
+        let segments = ftl.format_segments("jump", &ftl_args!{ "key" => "Space" }).unwrap();
+        assert_eq!(segments, vec![
+            text("[Ƥřḗşş "),
+            variable("Space", "key"),
+            text(" ŧǿ ĵŭḿƥ~~~~~~~~]"),
+        ]);
+        let flat: String = segments.iter().map(|segment| match segment {
+            FtlSegment::Text(value) | FtlSegment::Placeable { value, .. } => value.clone(),
+        }).collect();
+        assert_eq!(flat, ftl.format("jump", &ftl_args!{ "key" => "Space" }).unwrap().replace(['\u{2068}', '\u{2069}'], ""));
+    }
+
+    #[tokio::test]
+    async fn ftl_global_args() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .use_isolating(false)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", "version = Version {$appVersion}\ngreeting = Hi, {$playerName}!\n    .title = {$playerName}'s profile\n")));
+        assert!(ftl.load(None).await);
+        ftl.set_global_arg("appVersion", "1.2.4");
+        ftl.set_global_arg("playerName", FtlValue::from("Ada"));
+        assert_eq!(ftl.get_message("version").unwrap(), "Version 1.2.4");
+        assert_eq!(ftl.format("greeting", &FtlArgs::new()).unwrap(), "Hi, Ada!");
+        assert_eq!(ftl.get_attribute("greeting", "title", &FtlArgs::new()).unwrap(), "Ada's profile");
+        // Arguments override global arguments.
+        assert_eq!(ftl.format("greeting", &ftl_args!{ "playerName" => "Grace" }).unwrap(), "Hi, Grace!");
+        let mut buffer = String::new();
+        ftl.format_into(&mut buffer, "greeting", &FtlArgs::new()).unwrap();
+        assert_eq!(buffer, "Hi, Ada!");
+        ftl.set_global_arg("playerName", "Linus");
+        assert_eq!(ftl.clone().format("greeting", &FtlArgs::new()).unwrap(), "Hi, Linus!");
+    }
+
+    #[test]
+    fn display_names() {
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.display_name("fr").as_deref(), Some("français"));
+        assert_eq!(ftl.display_name("ja").as_deref(), Some("日本語"));
+        assert_eq!(ftl.display_name("pt-BR").as_deref(), Some("português (Brasil)"));
+        assert_eq!(ftl.display_name_in("fr", "en").as_deref(), Some("French"));
+        assert_eq!(ftl.display_name_in("ja", "fr").as_deref(), Some("japonais"));
+        assert_eq!(ftl.display_name("xx"), None);
+        assert_eq!(ftl.display_name("not a locale"), None);
+    }
+
+    #[test]
+    fn collation() {
+        let mut names = vec!["zebra", "Ärger", "apple", "Zoe"];
+        Collator::new(&locale::locale!("de"), &CollatorOptions::new()).sort(&mut names);
+        assert_eq!(names, ["apple", "Ärger", "zebra", "Zoe"]);
+        Collator::new(&locale::locale!("sv"), &CollatorOptions::new()).sort(&mut names);
+        assert_eq!(names, ["apple", "zebra", "Zoe", "Ärger"]);
+
+        let en = locale::locale!("en");
+        assert_eq!(Collator::new(&en, &CollatorOptions::new()).compare("a", "A"), Ordering::Less);
+        assert_eq!(Collator::new(&en, CollatorOptions::new().sensitivity(CollationSensitivity::Accent)).compare("a", "A"), Ordering::Equal);
+        assert_eq!(Collator::new(&en, CollatorOptions::new().sensitivity(CollationSensitivity::Accent)).compare("a", "á"), Ordering::Less);
+        assert_eq!(Collator::new(&en, CollatorOptions::new().sensitivity(CollationSensitivity::Base)).compare("a", "Á"), Ordering::Equal);
+        assert_eq!(Collator::new(&en, CollatorOptions::new().sensitivity(CollationSensitivity::Case)).compare("a", "á"), Ordering::Equal);
+        assert_eq!(Collator::new(&en, CollatorOptions::new().sensitivity(CollationSensitivity::Case)).compare("a", "A"), Ordering::Less);
+        assert_eq!(Collator::new(&en, &CollatorOptions::new()).compare("item10", "item2"), Ordering::Less);
+        assert_eq!(Collator::new(&en, CollatorOptions::new().numeric(true)).compare("item10", "item2"), Ordering::Greater);
+
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.compare("b", "a"), Ordering::Greater);
+    }
+
+    #[test]
+    fn segmentation() {
+        assert_eq!(segment::graphemes("e\u{301}👨\u{200d}👩\u{200d}👧!").collect::<Vec<_>>(), ["e\u{301}", "👨\u{200d}👩\u{200d}👧", "!"]);
+        assert_eq!(segment::graphemes("").count(), 0);
+        assert_eq!(segment::words("Hello, world!", &locale::locale!("en")).collect::<Vec<_>>(), ["Hello", ",", " ", "world", "!"]);
+        let thai = segment::words("สวัสดีครับ", &locale::locale!("th")).collect::<Vec<_>>();
+        assert!(thai.len() > 1);
+        assert_eq!(thai.concat(), "สวัสดีครับ");
+        assert_eq!(thai.last(), Some(&"ครับ"));
+        assert_eq!(segment::words("今日は晴れ", &locale::locale!("ja")).collect::<Vec<_>>(), ["今日", "は", "晴れ"]);
+    }
+
+    #[test]
+    fn case_conversion() {
+        let tr = locale::locale!("tr");
+        let en = locale::locale!("en");
+        assert_eq!(case_mapping::to_uppercase(&tr, "istanbul"), "İSTANBUL");
+        assert_eq!(case_mapping::to_uppercase(&en, "istanbul"), "ISTANBUL");
+        assert_eq!(case_mapping::to_lowercase(&tr, "KIRMIZI"), "kırmızı");
+        assert_eq!(case_mapping::to_lowercase(&en, "KIRMIZI"), "kirmizi");
+        assert_eq!(case_mapping::to_uppercase(&locale::locale!("de"), "straße"), "STRASSE");
+        assert_eq!(case_mapping::fold(&tr, "İstanbul"), case_mapping::fold(&tr, "istanbul"));
+        assert_eq!(case_mapping::fold(&en, "Straße"), "strasse");
+
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.to_locale_uppercase("ı"), "I");
+    }
+
+    #[test]
+    fn number_compact() {
+        let en = locale::locale!("en");
+        let de = locale::locale!("de");
+        let mut short = NumberFormatOptions::new();
+        short.compact(CompactStyle::Short);
+        let mut long = NumberFormatOptions::new();
+        long.compact(CompactStyle::Long);
+        assert_eq!(number::format(&en, 999.0, &short), "999");
+        assert_eq!(number::format(&en, 1234.0, &short), "1.2K");
+        assert_eq!(number::format(&en, 3_400_000.0, &short), "3.4M");
+        assert_eq!(number::format(&en, -15_127.0, &short), "-15K");
+        assert_eq!(number::format(&en, 1234.0, &long), "1.2 thousand");
+        assert_eq!(number::format(&de, 1_234_567.0, &short), "1,2\u{a0}Mio.");
+        assert_eq!(number::format(&de, 1_234_567.0, &long), "1,2 Millionen");
+        assert_eq!(number::format(&en, f64::INFINITY, &short), "∞");
+    }
+
+    #[test]
+    fn number_percent() {
+        let mut options = NumberFormatOptions::new();
+        options.percent(true);
+        assert_eq!(number::format(&locale::locale!("en"), 0.875, &options), "87.5%");
+        assert_eq!(number::format(&locale::locale!("en"), 0.07, &options), "7%");
+        assert_eq!(number::format(&locale::locale!("en"), -0.25, &options), "-25%");
+        assert_eq!(number::format(&locale::locale!("fr"), 0.875, &options), "87,5\u{a0}%");
+        assert_eq!(number::format(&locale::locale!("de"), 0.875, &options), "87,5\u{a0}%");
+        assert_eq!(number::format(&locale::locale!("tr"), 0.875, &options), "%87,5");
+        assert_eq!(number::format(&locale::locale!("en"), 12.5, &options), "1,250%");
+        options.minimum_fraction_digits(1).maximum_fraction_digits(1);
+        assert_eq!(number::format(&locale::locale!("en"), 0.5, &options), "50.0%");
+        assert_eq!(number::format(&locale::locale!("en"), 0.12345, &options), "12.3%");
+    }
+
+    #[test]
+    fn number_unit() {
+        let en = locale::locale!("en");
+        let options = NumberFormatOptions::new();
+        assert_eq!(number::format_unit(&en, 5.0, Unit::Kilometer, UnitStyle::Narrow, &options), "5km");
+        assert_eq!(number::format_unit(&en, 5.0, Unit::Kilometer, UnitStyle::Short, &options), "5 km");
+        assert_eq!(number::format_unit(&en, 5.0, Unit::Kilometer, UnitStyle::Long, &options), "5 kilometers");
+        assert_eq!(number::format_unit(&en, 1.0, Unit::Kilometer, UnitStyle::Long, &options), "1 kilometer");
+        assert_eq!(number::format_unit(&en, 1.5, Unit::Foot, UnitStyle::Long, &options), "1.5 feet");
+        assert_eq!(number::format_unit(&en, 1234.0, Unit::KilometerPerHour, UnitStyle::Short, &options), "1,234 km/h");
+        assert_eq!(number::format_unit(&en, -3.0, Unit::Celsius, UnitStyle::Short, &options), "-3°C");
+        assert_eq!(number::format_unit(&locale::locale!("de"), 2.5, Unit::Kilogram, UnitStyle::Long, &options), "2,5 kg");
+        assert_eq!(number::unit_style_for(&locale::locale!("de"), UnitStyle::Long), UnitStyle::Short);
+        assert_eq!(number::unit_style_for(&en, UnitStyle::Long), UnitStyle::Long);
+        assert_eq!(number::unit_style_for(&locale::locale!("de"), UnitStyle::Narrow), UnitStyle::Narrow);
+
+        let ftl = embedded_ftl();
+        assert_eq!(ftl.format_unit(120.0, Unit::KilometerPerHour, UnitStyle::Short), "120 km/h");
+    }
+
+    #[test]
+    fn number_numbering_system() {
+        let options = NumberFormatOptions::new();
+        assert_eq!(number::format(&locale::locale!("ar-EG"), 1234.5, &options), "١٬٢٣٤٫٥");
+        assert_eq!(number::format(&locale::locale!("ar"), 1234.5, &options), "1,234.5");
+        assert_eq!(number::format(&"ar-u-nu-arab".parse().unwrap(), 1234.5, &options), "١٬٢٣٤٫٥");
+        assert_eq!(number::format(&locale::locale!("ar"), 1234.5, NumberFormatOptions::new().numbering_system("arab")), "١٬٢٣٤٫٥");
+        assert_eq!(number::format(&locale::locale!("ar-EG"), 1234.5, NumberFormatOptions::new().numbering_system("latn")), "1,234.5");
+        assert_eq!(number::format(&locale::locale!("hi"), 1234.5, NumberFormatOptions::new().numbering_system("deva")), "१,२३४.५");
+        // Without locale data for the numbering system, only digits are shaped.
+        assert_eq!(number::format(&locale::locale!("en"), 1234.5, NumberFormatOptions::new().numbering_system("arab")), "١,٢٣٤.٥");
+        assert_eq!(number::format(&locale::locale!("en"), 1234.5, NumberFormatOptions::new().numbering_system("bogus")), "1,234.5");
+        assert!(number::format_currency(&locale::locale!("ar"), 5.0, "USD", CurrencyFormatOptions::new().numbering_system("arab")).contains("٥٫٠٠"));
+
+        let ftl = Ftl::new(FtlOptions::new()
+            .default_locale("ar-EG")
+            .supported_locales(vec!["ar-EG"])
+            .assets(FtlOptionsForAssets::new().load_method(FtlLoadMethod::Memory)));
+        assert_eq!(ftl.format_number(12.0, &options), "١٢");
+    }
+
+    #[tokio::test]
+    async fn ftl_literal_braces() {
+        let ftl = Ftl::new(
+            FtlOptions::new()
+                .supported_locales(vec!["en"])
+                .default_locale("en")
+                .use_isolating(false)
+                .assets(FtlOptionsForAssets::new()
+                    .files(vec!["_"])
+                    .load_method(FtlLoadMethod::Embedded)
+                    .embedded("en", "_", concat!(
+                        "open = {\"{\"}\n",
+                        "json = {\"{\"} \"key\": {$value} {\"}\"}\n",
+                        "show = Payload: {$payload}\n",
+                    ))));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.get_message("open").unwrap(), "{");
+        assert_eq!(ftl.format("json", &ftl_args! { "value" => 1 }).unwrap(), "{ \"key\": 1 }");
+        let payload = FtlValue::literal("{ \"value\": {$value} }");
+        assert_eq!(ftl.format("show", &ftl_args! { "payload" => payload, "value" => 1 }).unwrap(), "Payload: { \"value\": {$value} }");
+    }
+
+    #[tokio::test]
+    async fn ftl_format_shared() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        let label = ftl.format_shared("hello-world", &ftl_args! {}).unwrap();
+        assert!(matches!(label, FtlText::Shared(_)));
+        assert_eq!(label, "Hello, world!");
+        let FtlText::Shared(shared) = &label else { unreachable!() };
+        assert!(matches!(ftl.format_shared("hello-world", &ftl_args! {}).unwrap(), FtlText::Shared(again) if Arc::ptr_eq(&again, shared)));
+        let welcome = ftl.format_shared("welcome", &ftl_args! { "name" => "Ada" }).unwrap();
+        assert!(matches!(welcome, FtlText::Owned(_)));
+        assert_eq!(welcome, ftl.format("welcome", &ftl_args! { "name" => "Ada" }).unwrap());
+        assert!(matches!(ftl.format_shared("no-such-message", &ftl_args! {}), Err(FtlError::MessageNotFound(_))));
+
+        // Shared messages outlive the bundle that they were taken from.
+        ftl.add_source("en", "extra", "extra = Extra\nhello-world = Hello, { \"world\" }!\n").unwrap();
+        assert!(matches!(ftl.format_shared("extra", &ftl_args! {}).unwrap(), FtlText::Shared(text) if &*text == "Extra"));
+        assert!(matches!(ftl.format_shared("hello-world", &ftl_args! {}).unwrap(), FtlText::Owned(_)));
+        assert_eq!(label, "Hello, world!");
+
+        ftl.enable_pseudolocalization(PseudoStyle::Accented);
+        assert!(matches!(ftl.format_shared("extra", &ftl_args! {}).unwrap(), FtlText::Owned(_)));
+    }
+
+    #[tokio::test]
+    async fn ftl_fallback_on_format_error() {
+        let ftl = |fallback: bool| Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .on_missing(MissingBehavior::ReturnId)
+            .fallback_on_format_error(fallback)
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .embedded("en", "_", "welcome = Welcome!\nscore = {$score} points\n")
+                .embedded("pt-BR", "_", "welcome = Bem-vindo, {$name}!\nscore = {$score} pontos\n")
+                .load_method(FtlLoadMethod::Embedded)));
+        let strict_ftl = ftl(false);
+        assert!(strict_ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert!(matches!(strict_ftl.format("welcome", &FtlArgs::new()), Err(FtlError::MissingVariable { .. })));
+
+        let ftl = ftl(true);
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.format("welcome", &FtlArgs::new()).unwrap(), "Welcome!");
+        // The message of the default locale fails as well.
+        assert_eq!(ftl.format("score", &FtlArgs::new()).unwrap(), "score");
+        assert_eq!(ftl.format("score", &ftl_args! { "score" => 3 }).unwrap(), "\u{2068}3\u{2069} pontos");
+    }
+
+    #[tokio::test]
+    async fn ftl_extract_schema() {
+        let ftl = embedded_ftl();
+        ftl.load_all().await.unwrap();
+        let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(ftl.extract_schema(), vec![
+            ("bye".to_owned(), vec![]),
+            ("hello-world".to_owned(), vec![]),
+            ("login".to_owned(), strings(&["name"])),
+            ("place".to_owned(), strings(&["n"])),
+            ("score".to_owned(), strings(&["score"])),
+            ("welcome".to_owned(), strings(&["name"])),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn ftl_strict_duplicates() {
+        let ftl = |strict: bool| Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_", "ui"])
+                .embedded("en", "_", "color = Color\nsize = Size\n")
+                .embedded("en", "ui", "color = Colour\n")
+                .strict_duplicates(strict)
+                .load_method(FtlLoadMethod::Embedded)));
+        let overriding_ftl = ftl(false);
+        assert!(overriding_ftl.load(None).await);
+        assert_eq!(overriding_ftl.get_message("color").unwrap(), "Colour");
+        assert_eq!(overriding_ftl.get_message("size").unwrap(), "Size");
+        assert_eq!(overriding_ftl.message_ids(), ["color", "size"]);
+        assert_eq!(ftl(true).load_locale("en").await, Err(FtlError::InvalidResource {
+            locale: "en".to_owned(),
+            path: "res/lang/en/ui.ftl".to_owned(),
+        }));
+    }
+
+    #[tokio::test]
+    async fn ftl_parent_locale() {
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "en-GB", "pt", "pt-BR", "zh", "zh-Hant", "zh-Hant-TW"])
+            .default_locale("pt")
+            .fallbacks(hashmap! {
+                "pt-BR" => vec!["pt"],
+            })
+            .on_missing(MissingBehavior::Error)
+            .assets(FtlOptionsForAssets::new()
+                .files(vec!["_"])
+                .embedded("en", "_", "color = Color\nhello = Hello\n")
+                .embedded("en-GB", "_", "color = Colour\n")
+                .embedded("pt", "_", "color = Cor\nbye = Tchau\n")
+                .load_method(FtlLoadMethod::Embedded)));
+        assert_eq!(ftl.fallback_chain("en-GB"), vec!["en-GB", "en", "pt"]);
+        assert_eq!(ftl.fallback_chain("zh-Hant-TW"), vec!["zh-Hant-TW", "zh-Hant", "zh", "pt"]);
+        assert_eq!(ftl.fallback_chain("pt-BR"), vec!["pt-BR", "pt"]);
+        assert!(ftl.load(Some(locale::locale!("en-GB"))).await);
+        assert_eq!(ftl.get_message("color").unwrap(), "Colour");
+        assert_eq!(ftl.get_message("hello").unwrap(), "Hello");
+        assert_eq!(ftl.get_message("bye").unwrap(), "Tchau");
+    }
+
+    #[tokio::test]
+    async fn ftl_tr() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(tr!(ftl, "hello-world").unwrap(), "Hello, world!");
+        assert_eq!(tr!(&ftl, "welcome", name = "Ada",).unwrap(), "Welcome, \u{2068}Ada\u{2069}!");
+        assert_eq!(tr!(ftl, "place", n = 2).unwrap(), "\u{2068}2\u{2069}nd");
+    }
+
+    #[tokio::test]
+    async fn ftl_assert_ids() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        ftl.assert_ids(&["hello-world", "welcome"]);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "messages not defined by the default locale en: score, welcom")]
+    async fn ftl_assert_ids_missing() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        ftl.assert_ids(&["hello-world", "score", "welcom"]);
+    }
+
+    #[tokio::test]
+    async fn ftl_with_locale() {
+        let ftl = embedded_ftl();
+        ftl.load_all().await.unwrap();
+        ftl.set_locale("en");
+        let (outer, inner, restored) = ftl.with_locale("pt-BR", || {
+            let inner = ftl.with_locale("en", || ftl.get_message("hello-world").unwrap());
+            (ftl.get_message("hello-world").unwrap(), inner, ftl.current_locale())
+        });
+        assert_eq!(outer, "Olá, mundo!");
+        assert_eq!(inner, "Hello, world!");
+        assert_eq!(restored, "pt-BR");
+        assert_eq!(ftl.current_locale(), "en");
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| ftl.with_locale("pt-BR", || panic!())));
+        assert!(result.is_err());
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+
+        // Clones share the override of the current thread only.
+        let clone = ftl.clone();
+        let (cloned, other_thread) = ftl.with_locale("pt-BR", || {
+            let other = clone.clone();
+            let other_thread = std::thread::spawn(move || other.get_message("hello-world").unwrap()).join().unwrap();
+            (clone.get_message("hello-world").unwrap(), other_thread)
+        });
+        assert_eq!(cloned, "Olá, mundo!");
+        assert_eq!(other_thread, "Hello, world!");
+        let other = ftl.clone();
+        let other_thread = std::thread::spawn(move || other.with_locale("pt-BR", || other.format("hello-world", &FtlArgs::new()).unwrap()));
+        assert_eq!(other_thread.join().unwrap(), "Olá, mundo!");
+    }
+
+    #[tokio::test]
+    async fn ftl_formatting_locale() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.formatting_locale(), "en");
+        ftl.set_formatting_locale("de-DE");
+        ftl.set_formatting_locale("not a locale");
+        assert_eq!(ftl.formatting_locale(), "de-DE");
+        assert_eq!(ftl.current_locale(), "en");
+        assert_eq!(ftl.format_number(1234.56, &NumberFormatOptions::new()), "1.234,56");
+        assert_eq!(ftl.get_message("hello-world").unwrap(), "Hello, world!");
+        ftl.reset_formatting_locale();
+        assert_eq!(ftl.format_number(1234.56, &NumberFormatOptions::new()), "1,234.56");
+    }
+
+    #[tokio::test]
+    async fn ftl_bool_and_datetime_args() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        ftl.add_source("en", "dates", concat!(
+            "last-seen = Last seen {DATETIME($ts)}\n",
+            "joined = Joined on {DATETIME($ts, dateStyle: \"long\")} at {DATETIME($ts, timeStyle: \"short\")}\n",
+            "raw = {$ts} {$online}\n",
+            "status = {$online ->\n    [true] Online\n   *[false] Offline\n}\n",
+        )).unwrap();
+        let ts = SystemTime::UNIX_EPOCH + Duration::from_secs(1_710_000_000);
+        let args = ftl_args!{ "ts" => ts, "online" => true };
+        assert_eq!(ftl.format("last-seen", &args).unwrap(), "Last seen \u{2068}3/9/24, 4:00\u{202f}PM\u{2069}");
+        assert_eq!(ftl.format("joined", &args).unwrap(), "Joined on \u{2068}March 9, 2024\u{2069} at \u{2068}4:00\u{202f}PM\u{2069}");
+        assert_eq!(ftl.format("raw", &args).unwrap(), "\u{2068}3/9/24, 4:00\u{202f}PM\u{2069} \u{2068}true\u{2069}");
+        assert_eq!(ftl.format("status", &args).unwrap(), "Online");
+        assert_eq!(ftl.format("status", &ftl_args!{ "online" => false }).unwrap(), "Offline");
+    }
+
+    #[tokio::test]
+    async fn ftl_select_without_default_variant() {
+        let source = concat!(
+            "status = { $status ->\n    [online] Online\n    [offline] Offline\n}\n",
+            "items = { $n ->\n    [one]\n        One item\n        in the cart\n    [many] {$n} items\n}\n",
+            "nested = { $a ->\n    [x] { $b ->\n        [y] XY\n    }\n    [z] Z\n}\n",
+        );
+        let ftl = embedded_ftl();
+        assert!(ftl.load(None).await);
+        let errors = ftl.add_source("en", "status", source).unwrap();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(ftl.format("status", &ftl_args!{ "status" => "offline" }).unwrap(), "Offline");
+        assert_eq!(ftl.format("status", &ftl_args!{ "status" => "away" }), Err(FtlError::Format {
+            id: "status".to_owned(),
+            errors: vec!["no variant matches the selector value \"away\" and there is no default variant".to_owned()],
+        }));
+        assert_eq!(ftl.format("items", &ftl_args!{ "n" => 1 }).unwrap(), "One item\nin the cart");
+        assert!(matches!(ftl.format("items", &ftl_args!{ "n" => 0 }), Err(FtlError::Format { errors, .. }) if errors[0].contains("\"0\"")));
+        assert_eq!(ftl.format("nested", &ftl_args!{ "a" => "x", "b" => "y" }).unwrap(), "XY");
+        assert!(ftl.format("nested", &ftl_args!{ "a" => "x", "b" => "w" }).is_err());
+        assert_eq!(ftl.format("hello-world", &FtlArgs::new()).unwrap(), "Hello, world!");
+
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .first_variant_as_default(true)
+            .assets(FtlOptionsForAssets::new().load_method(FtlLoadMethod::Memory)));
+        ftl.add_source("en", "status", source).unwrap();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.format("status", &ftl_args!{ "status" => "away" }).unwrap(), "Online");
+        assert_eq!(ftl.format("items", &ftl_args!{ "n" => 0 }).unwrap(), "One item\nin the cart");
+        assert_eq!(ftl.format("nested", &ftl_args!{ "a" => "w", "b" => "w" }).unwrap(), "XY");
+        assert_eq!(ftl.format("nested", &ftl_args!{ "a" => "z" }).unwrap(), "Z");
+
+        // The added default variants are not part of the signatures.
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .assets(FtlOptionsForAssets::new().load_method(FtlLoadMethod::Memory)));
+        ftl.add_source("en", "status", source).unwrap();
+        ftl.add_source("pt-BR", "status", concat!(
+            "status = { $status ->\n    [online] Online\n   *[offline] Offline\n}\n",
+            "items = { $n ->\n    [one] Um item\n   *[other] {$n} itens\n}\n",
+            "nested = { $a ->\n    [x] { $b ->\n       *[y] XY\n    }\n   *[z] Z\n}\n",
+        )).unwrap();
+        ftl.load_all().await.unwrap();
+        assert_eq!(ftl.validate(), vec![]);
+        assert_eq!(ftl.extract_schema(), vec![
+            ("items".to_owned(), vec!["n".to_owned()]),
+            ("nested".to_owned(), vec!["a".to_owned(), "b".to_owned()]),
+            ("status".to_owned(), vec!["status".to_owned()]),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn ftl_select_without_default_variant_literals() {
+        let source = concat!(
+            "# A comment with a { brace\n",
+            "close = { $s ->\n    [a] {\"}\"} A\n    [b] B\n}\n",
+            "open = { $s ->\n    [a] {\"{\"} A \"quoted\"\n    [b] { \"->\" }\n}\n",
+            "outer = { $s ->\n    [a] A\n}{ INNER() }\n",
+            "inner = Inner\n",
+        );
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .use_isolating(false)
+            .assets(FtlOptionsForAssets::new().load_method(FtlLoadMethod::Memory)));
+        thread_local! {
+            static INNER_FTL: std::cell::RefCell<Option<Ftl>> = const { std::cell::RefCell::new(None) };
+        }
+        ftl.add_function("INNER", |_, _| INNER_FTL.with(|inner| {
+            FtlValue::String(inner.borrow().as_ref().unwrap().format("inner", &FtlArgs::new()).unwrap())
+        })).unwrap();
+        ftl.add_source("en", "_", source).unwrap();
+        assert!(ftl.load(None).await);
+        INNER_FTL.with(|inner| *inner.borrow_mut() = Some(ftl.clone()));
+        assert_eq!(ftl.format("close", &ftl_args!{ "s" => "a" }).unwrap(), "} A");
+        assert_eq!(ftl.format("close", &ftl_args!{ "s" => "b" }).unwrap(), "B");
+        assert!(ftl.format("close", &ftl_args!{ "s" => "c" }).is_err());
+        assert_eq!(ftl.format("open", &ftl_args!{ "s" => "a" }).unwrap(), "{ A \"quoted\"");
+        assert_eq!(ftl.format("open", &ftl_args!{ "s" => "b" }).unwrap(), "->");
+        assert!(ftl.format("open", &ftl_args!{ "s" => "c" }).is_err());
+        // A custom function formatting another message does not
+        // discard the unmatched selector of the outer message.
+        assert_eq!(ftl.format("outer", &ftl_args!{ "s" => "a" }).unwrap(), "AInner");
+        assert_eq!(ftl.format("outer", &ftl_args!{ "s" => "b" }), Err(FtlError::Format {
+            id: "outer".to_owned(),
+            errors: vec!["no variant matches the selector value \"b\" and there is no default variant".to_owned()],
+        }));
+    }
+
+    #[tokio::test]
+    async fn ftl_trim_message_whitespace() {
+        let source = concat!(
+            "poem =\n        Roses are red,\n    violets are blue\n",
+            "greeting =\n        Hi, {$name}\n    .title =\n          Welcome\n",
+            "name = {$name}\n",
+        );
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .use_isolating(false)
+            .trim_message_whitespace(true)
+            .assets(FtlOptionsForAssets::new().load_method(FtlLoadMethod::Memory)));
+        ftl.add_source("en", "_", source).unwrap();
+        assert!(ftl.load(None).await);
+        let args = ftl_args!{ "name" => " Ada " };
+        assert_eq!(ftl.format("poem", &FtlArgs::new()).unwrap(), "Roses are red,\nviolets are blue");
+        assert_eq!(ftl.format_shared("poem", &FtlArgs::new()).unwrap(), "Roses are red,\nviolets are blue");
+        assert_eq!(ftl.format("greeting", &args).unwrap(), "Hi,  Ada ");
+        assert_eq!(ftl.get_attribute("greeting", "title", &args).unwrap(), "Welcome");
+        assert_eq!(ftl.format("name", &args).unwrap(), " Ada ");
+
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en"])
+            .assets(FtlOptionsForAssets::new().load_method(FtlLoadMethod::Memory)));
+        ftl.add_source("en", "_", source).unwrap();
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.format("poem", &FtlArgs::new()).unwrap(), "    Roses are red,\nviolets are blue");
+    }
+
+    #[tokio::test]
+    async fn ftl_asset_resolver() {
+        struct InMemory(std::collections::HashMap<String, Vec<u8>>);
+
+        impl AssetResolver for InMemory {
+            fn read<'a>(&'a self, url: &'a str) -> AssetFuture<'a> {
+                let data = self.0.get(url).cloned().ok_or(rialight_filesystem::FileError::NotFound);
+                Box::pin(async move { data })
+            }
+        }
+
+        let resolver = InMemory(hashmap! {
+            "app://game/lang/en/_.ftl".to_owned() => b"hello = Hello from memory".to_vec(),
+        });
+        let ftl = Ftl::new(FtlOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .assets(FtlOptionsForAssets::new()
+                .source("app://game/lang")
+                .files(vec!["_"])
+                .load_method(FtlLoadMethod::FileSystem)
+                .resolver(resolver)));
+        assert!(ftl.load(None).await);
+        assert_eq!(ftl.format("hello", &FtlArgs::new()).unwrap(), "Hello from memory");
+        assert_eq!(ftl.load_locale("pt-BR").await, Err(FtlError::ResourceNotFound {
+            locale: "pt-BR".to_owned(),
+            path: "app://game/lang/pt-BR/_.ftl".to_owned(),
+        }));
+        assert!(matches!(ftl.watch(), Err(FtlError::UnsupportedLoadMethod)));
+
+        let path = std::env::temp_dir().join("rialight_intl_asset_resolver.ftl");
+        std::fs::write(&path, "hello = Hello").unwrap();
+        let data = FileSystemResolver.read(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(data, b"hello = Hello");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ftl_typed_args() {
+        let ftl = embedded_ftl();
+        assert!(ftl.load(Some(locale::locale!("pt-BR"))).await);
+        assert_eq!(ftl.format("score", &ftl_args!{ "score" => 1234567 }).unwrap(), "\u{2068}1.234.567\u{2069} pontos");
+        assert_eq!(ftl.format("score", &ftl_args!{ "score" => 1234.5 }).unwrap(), "\u{2068}1.234,5\u{2069} pontos");
+        assert_eq!(ftl.format("welcome", &FtlArgs::new()), Err(FtlError::MissingVariable {
+            id: "welcome".to_owned(),
+            name: "name".to_owned(),
+        }));
+        let mut args = ftl_args!{ "name" => "Ada" };
+        args.set("name", "Grace");
+        assert_eq!(ftl.format("welcome", &args).unwrap(), "Welcome, \u{2068}Grace\u{2069}!");
+    }
+}
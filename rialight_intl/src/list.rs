@@ -0,0 +1,38 @@
+//! Locale-aware list formatting.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, list::{self, ListStyle}};
+//!
+//! assert_eq!(list::format(&locale!("en"), &["A", "B", "C"], ListStyle::Conjunction), "A, B, and C");
+//! assert_eq!(list::format(&locale!("es"), &["A", "B", "C"], ListStyle::Conjunction), "A, B y C");
+//! ```
+
+use crate::locale::Locale;
+use icu::list::{ListFormatter, ListLength};
+
+/// Kind of list.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ListStyle {
+    /// A list of items joined by "and", such as `A, B, and C`.
+    Conjunction,
+    /// A list of alternatives joined by "or", such as `A, B, or C`.
+    Disjunction,
+    /// A list of measurements, such as `3 feet, 7 inches`.
+    Unit,
+}
+
+/// Formats a list according to the rules of the given locale.
+pub fn format(locale: &Locale, items: &[&str], style: ListStyle) -> String {
+    let locale = &locale.into();
+    let formatter = match style {
+        ListStyle::Conjunction => ListFormatter::try_new_and_with_length(locale, ListLength::Wide),
+        ListStyle::Disjunction => ListFormatter::try_new_or_with_length(locale, ListLength::Wide),
+        ListStyle::Unit => ListFormatter::try_new_unit_with_length(locale, ListLength::Wide),
+    };
+    match formatter {
+        Ok(formatter) => formatter.format_to_string(items.iter()),
+        Err(_) => items.join(", "),
+    }
+}
@@ -0,0 +1,807 @@
+//! Locale-aware number formatting.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, number::{self, CompactStyle, CurrencyFormatOptions, NumberFormatOptions, Unit, UnitStyle}};
+//!
+//! let options = NumberFormatOptions::new();
+//! assert_eq!(number::format(&locale!("en"), 1234567.5, &options), "1,234,567.5");
+//! assert_eq!(number::format(&locale!("de"), 1234567.5, &options), "1.234.567,5");
+//!
+//! let mut options = NumberFormatOptions::new();
+//! options.compact(CompactStyle::Short);
+//! assert_eq!(number::format(&locale!("en"), 1234.0, &options), "1.2K");
+//! assert_eq!(number::format(&locale!("de"), 1234567.0, &options), "1,2\u{a0}Mio.");
+//!
+//! let mut options = NumberFormatOptions::new();
+//! options.percent(true);
+//! assert_eq!(number::format(&locale!("en"), 0.875, &options), "87.5%");
+//!
+//! let mut options = NumberFormatOptions::new();
+//! options.scientific(true).maximum_fraction_digits(2);
+//! assert_eq!(number::format(&locale!("en"), 12345.0, &options), "1.23E4");
+//! assert_eq!(number::format(&locale!("de"), 12345.0, &options), "1,23E4");
+//!
+//! let mut options = NumberFormatOptions::new();
+//! options.numbering_system("arab");
+//! assert_eq!(number::format(&locale!("ar"), 1234.5, &options), "١٬٢٣٤٫٥");
+//!
+//! let options = NumberFormatOptions::new();
+//! assert_eq!(number::format_unit(&locale!("en"), 5.0, Unit::Kilometer, UnitStyle::Long, &options), "5 kilometers");
+//!
+//! let options = CurrencyFormatOptions::new();
+//! assert_eq!(number::format_currency(&locale!("en-US"), 1234.5, "USD", &options), "$1,234.50");
+//! assert_eq!(number::format_currency(&locale!("de-DE"), 1234.5, "EUR", &options), "1.234,50\u{a0}€");
+//! ```
+
+use crate::{HasTextDirection, TextDirection, locale::{Locale, extensions_unicode_key as key, extensions::unicode::Value}};
+use fixed_decimal::FixedDecimal;
+use icu::decimal::{FixedDecimalFormatter, options::{FixedDecimalFormatterOptions, GroupingStrategy}};
+use icu::experimental::compactdecimal::CompactDecimalFormatter;
+use icu::experimental::dimension::provider::{currency::{CurrencyEssentialsV1Marker, PatternSelection, PlaceholderValue}, percent::PercentEssentialsV1Marker};
+use icu::plurals::{PluralCategory, PluralRules};
+use icu_provider::{DataProvider, DataRequest};
+use std::{borrow::Cow, cell::Cell, str::FromStr};
+use tinystr::TinyAsciiStr;
+use writeable::Writeable;
+
+/// Compact notation, as in `1.2K`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompactStyle {
+    /// Abbreviated, such as `1.2K` in English or `1,2 Mio.` in German.
+    Short,
+    /// Spelled out, such as `1.2 thousand` in English or `1,2 Millionen`
+    /// in German.
+    Long,
+}
+
+/// How numbers are rounded to the displayed digits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Rounds half to the even digit, as in `2.5` to `2` and `3.5` to `4`.
+    HalfEven,
+    /// Rounds half away from zero, as in `2.5` to `3` and `-2.5` to `-3`.
+    HalfUp,
+    /// Rounds half toward zero, as in `2.5` to `2` and `-2.5` to `-2`.
+    HalfDown,
+    /// Rounds toward positive infinity, as in `2.1` to `3` and `-2.9` to `-2`.
+    Ceil,
+    /// Rounds toward negative infinity, as in `2.9` to `2` and `-2.1` to `-3`.
+    Floor,
+    /// Rounds toward zero, as in `2.9` to `2` and `-2.9` to `-2`.
+    Trunc,
+}
+
+/// Options for formatting numbers.
+///
+/// By default, digits are grouped and at most three fraction digits
+/// are displayed.
+#[derive(Clone)]
+pub struct NumberFormatOptions {
+    m_use_grouping: Cell<bool>,
+    m_minimum_fraction_digits: Cell<u8>,
+    m_maximum_fraction_digits: Cell<u8>,
+    m_minimum_significant_digits: Cell<Option<u8>>,
+    m_maximum_significant_digits: Cell<Option<u8>>,
+    m_rounding_mode: Cell<RoundingMode>,
+    m_compact: Cell<Option<CompactStyle>>,
+    m_percent: Cell<bool>,
+    m_scientific: Cell<bool>,
+    m_bidi_marks: Cell<bool>,
+    m_numbering_system: Cell<Option<TinyAsciiStr<8>>>,
+}
+
+impl Default for NumberFormatOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NumberFormatOptions {
+    pub fn new() -> Self {
+        NumberFormatOptions {
+            m_use_grouping: Cell::new(true),
+            m_minimum_fraction_digits: Cell::new(0),
+            m_maximum_fraction_digits: Cell::new(3),
+            m_minimum_significant_digits: Cell::new(None),
+            m_maximum_significant_digits: Cell::new(None),
+            m_rounding_mode: Cell::new(RoundingMode::HalfEven),
+            m_compact: Cell::new(None),
+            m_percent: Cell::new(false),
+            m_scientific: Cell::new(false),
+            m_bidi_marks: Cell::new(false),
+            m_numbering_system: Cell::new(None),
+        }
+    }
+
+    /// Whether to use the locale's grouping separators,
+    /// as in `1,234,567`. Default is `true`.
+    pub fn use_grouping(&mut self, value: bool) -> &mut Self {
+        self.m_use_grouping.set(value);
+        self
+    }
+
+    /// Minimum number of fraction digits, padded with zeros. Default is `0`.
+    pub fn minimum_fraction_digits(&mut self, value: u8) -> &mut Self {
+        self.m_minimum_fraction_digits.set(value);
+        self
+    }
+
+    /// Maximum number of fraction digits, rounding by the rounding mode.
+    /// Default is `3`. A maximum lower than the minimum is raised
+    /// to the minimum.
+    pub fn maximum_fraction_digits(&mut self, value: u8) -> &mut Self {
+        self.m_maximum_fraction_digits.set(value);
+        self
+    }
+
+    /// Minimum number of significant digits, padded with zeros, so that
+    /// `1.5` is displayed as `1.50` with a minimum of three. Setting either
+    /// significant digit option overrides the fraction digit options.
+    /// Default is `1` when the maximum is set.
+    pub fn minimum_significant_digits(&mut self, value: u8) -> &mut Self {
+        self.m_minimum_significant_digits.set(Some(value));
+        self
+    }
+
+    /// Maximum number of significant digits, so that `12345` is displayed
+    /// as `12,300` with a maximum of three. Setting either significant
+    /// digit option overrides the fraction digit options. Default is `21`
+    /// when the minimum is set. A maximum lower than the minimum is raised
+    /// to the minimum.
+    pub fn maximum_significant_digits(&mut self, value: u8) -> &mut Self {
+        self.m_maximum_significant_digits.set(Some(value));
+        self
+    }
+
+    /// How numbers are rounded to the maximum fraction or significant
+    /// digits. Default is `RoundingMode::HalfEven`, as in CLDR.
+    pub fn rounding_mode(&mut self, value: RoundingMode) -> &mut Self {
+        self.m_rounding_mode.set(value);
+        self
+    }
+
+    /// Uses compact notation, as in `1.2K`. Compact numbers are rounded
+    /// to two significant digits, or to an integer if it has more digits,
+    /// so the digit and rounding options are ignored. Default is no compact
+    /// notation.
+    pub fn compact(&mut self, value: CompactStyle) -> &mut Self {
+        self.m_compact.set(Some(value));
+        self
+    }
+
+    /// Formats the number as a percentage, so that `0.875` is displayed
+    /// as `87.5%` in English and `87,5 %` in French. The fraction digit
+    /// options apply to the percentage. Compact notation is not used
+    /// for percentages. Default is `false`.
+    pub fn percent(&mut self, value: bool) -> &mut Self {
+        self.m_percent.set(value);
+        self
+    }
+
+    /// Uses scientific notation, as in `1.23E4` in English and `1,23E4`
+    /// in German. The fraction digit options apply to the mantissa, so
+    /// `maximum_fraction_digits(2)` gives three significant digits.
+    /// Compact notation is not used for scientific numbers, and percentages
+    /// are not in scientific notation. Default is `false`.
+    pub fn scientific(&mut self, value: bool) -> &mut Self {
+        self.m_scientific.set(value);
+        self
+    }
+
+    /// Wraps the number in the directional mark of the locale, U+200F
+    /// RIGHT-TO-LEFT MARK for right-to-left locales and U+200E
+    /// LEFT-TO-RIGHT MARK otherwise, so that its signs and symbols stay
+    /// in place when it is displayed next to text in another direction.
+    /// Default is `false`.
+    pub fn bidi_marks(&mut self, value: bool) -> &mut Self {
+        self.m_bidi_marks.set(value);
+        self
+    }
+
+    /// Returns the digits to which numbers are rounded and padded.
+    fn digits(&self) -> Digits {
+        let (minimum, maximum) = (self.m_minimum_significant_digits.get(), self.m_maximum_significant_digits.get());
+        if minimum.is_some() || maximum.is_some() {
+            let minimum = minimum.unwrap_or(1).max(1);
+            return Digits::Significant(minimum, maximum.unwrap_or(21).max(minimum));
+        }
+        let minimum = self.m_minimum_fraction_digits.get();
+        Digits::Fraction(minimum, self.m_maximum_fraction_digits.get().max(minimum))
+    }
+
+    /// Forces a CLDR numbering system, such as `arab` for Arabic-Indic
+    /// digits or `latn` for ASCII digits. Default is the locale's
+    /// numbering system, which may also be given by the `-u-nu-` locale
+    /// extension, as in `ar-u-nu-arab`. Unknown numbering systems are
+    /// ignored.
+    ///
+    /// Numbers in FTL messages always use the numbering system of the
+    /// message's locale; for example, `ar-EG` uses Arabic-Indic digits.
+    pub fn numbering_system(&mut self, value: &str) -> &mut Self {
+        self.m_numbering_system.set(TinyAsciiStr::from_str(&value.to_ascii_lowercase()).ok());
+        self
+    }
+}
+
+/// How a currency is displayed by [`format_currency`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CurrencyDisplay {
+    /// Localized currency symbol, such as `$` or `US$`.
+    Symbol,
+    /// ISO 4217 currency code, such as `USD`.
+    Code,
+    /// Currency name, such as `US dollars`. Names are only included for
+    /// English and common currencies, taken from CLDR; otherwise the
+    /// currency is displayed as with `Code`, which
+    /// [`currency_display_for`] tells beforehand.
+    Name,
+}
+
+/// Options for formatting currency amounts.
+///
+/// By default, the currency symbol is displayed and digits are grouped.
+#[derive(Clone)]
+pub struct CurrencyFormatOptions {
+    m_display: Cell<CurrencyDisplay>,
+    m_use_grouping: Cell<bool>,
+    m_rounding_mode: Cell<RoundingMode>,
+    m_numbering_system: Cell<Option<TinyAsciiStr<8>>>,
+}
+
+impl Default for CurrencyFormatOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CurrencyFormatOptions {
+    pub fn new() -> Self {
+        CurrencyFormatOptions {
+            m_display: Cell::new(CurrencyDisplay::Symbol),
+            m_use_grouping: Cell::new(true),
+            m_rounding_mode: Cell::new(RoundingMode::HalfEven),
+            m_numbering_system: Cell::new(None),
+        }
+    }
+
+    /// How the currency is displayed. Default is `CurrencyDisplay::Symbol`.
+    pub fn display(&mut self, value: CurrencyDisplay) -> &mut Self {
+        self.m_display.set(value);
+        self
+    }
+
+    /// Whether to use the locale's grouping separators. Default is `true`.
+    pub fn use_grouping(&mut self, value: bool) -> &mut Self {
+        self.m_use_grouping.set(value);
+        self
+    }
+
+    /// How amounts are rounded to the currency's fraction digits.
+    /// Default is `RoundingMode::HalfEven`, as in CLDR.
+    pub fn rounding_mode(&mut self, value: RoundingMode) -> &mut Self {
+        self.m_rounding_mode.set(value);
+        self
+    }
+
+    /// Forces a CLDR numbering system, as with
+    /// [`NumberFormatOptions::numbering_system`].
+    pub fn numbering_system(&mut self, value: &str) -> &mut Self {
+        self.m_numbering_system.set(TinyAsciiStr::from_str(&value.to_ascii_lowercase()).ok());
+        self
+    }
+}
+
+/// Measurement unit for [`format_unit`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Kilometer,
+    Inch,
+    Foot,
+    Mile,
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    MeterPerSecond,
+    KilometerPerHour,
+    MilePerHour,
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+/// How a unit is displayed by [`format_unit`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnitStyle {
+    /// Unit symbol without spacing, such as `5km`.
+    Narrow,
+    /// Unit symbol, such as `5 km`.
+    Short,
+    /// Unit name, such as `5 kilometers`. Unit names are only included
+    /// for English, so other locales display the same as `Short`, which
+    /// [`unit_style_for`] tells beforehand.
+    Long,
+}
+
+/// Formats a number according to the rules of the given locale.
+///
+/// `NaN` is formatted as `NaN` and infinities as `∞` and `-∞`.
+pub fn format(locale: &Locale, n: f64, options: &NumberFormatOptions) -> String {
+    with_bidi_marks(locale, format_number(locale, n, options), options)
+}
+
+fn format_number(locale: &Locale, n: f64, options: &NumberFormatOptions) -> String {
+    let locale: &Locale = &with_numbering_system(locale, options.m_numbering_system.get());
+    if options.m_percent.get() {
+        return format_percent(locale, n, options);
+    }
+    if options.m_scientific.get() {
+        return format_scientific(locale, n, options);
+    }
+    if let Some(style) = options.m_compact.get() {
+        return format_compact(locale, n, style, options.m_use_grouping.get());
+    }
+    match to_decimal(n, options.digits(), options.m_rounding_mode.get()) {
+        Ok(decimal) => format_decimal(locale, &decimal, options.m_use_grouping.get()),
+        Err(non_finite) => non_finite,
+    }
+}
+
+fn format_percent(locale: &Locale, n: f64, options: &NumberFormatOptions) -> String {
+    // The number is rounded with its sign, as rounding toward an infinity
+    // depends on it.
+    let mut decimal = match to_scaled_decimal(n, 2, options.digits(), options.m_rounding_mode.get()) {
+        Ok(decimal) => decimal,
+        Err(non_finite) => return format!("{non_finite}%"),
+    };
+    decimal.set_sign(fixed_decimal::Sign::None);
+    let number = format_decimal(locale, &decimal, options.m_use_grouping.get());
+    let sign = if n < 0.0 && !decimal.is_zero() {
+        let mut negative = decimal.clone();
+        negative.set_sign(fixed_decimal::Sign::Negative);
+        let negative = format_decimal(locale, &negative, options.m_use_grouping.get());
+        negative.strip_suffix(&number).unwrap_or("-").to_owned()
+    } else {
+        String::new()
+    };
+
+    let request = DataRequest { locale: &locale.into(), metadata: Default::default() };
+    let Ok(payload) = DataProvider::<PercentEssentialsV1Marker>::load(&icu::experimental::provider::Baked, request)
+        .and_then(|response| response.take_payload()) else {
+        return format!("{sign}{number}%");
+    };
+    let data = payload.get();
+    let affixes = &data.percent_sign_affixes;
+    let percent_sign = format!("{}{}{}", affixes.prefix, data.percent_sign_symbol, affixes.suffix);
+    if data.percent_symbol_index < data.number_index {
+        format!("{sign}{percent_sign}{number}")
+    } else {
+        format!("{sign}{number}{percent_sign}")
+    }
+}
+
+fn format_scientific(locale: &Locale, n: f64, options: &NumberFormatOptions) -> String {
+    let digits = options.digits();
+    let mut exponent = if n == 0.0 || !n.is_finite() { 0 } else {
+        FixedDecimal::from_str(&n.to_string()).unwrap().nonzero_magnitude_start()
+    };
+    let mut mantissa = match to_scaled_decimal(n, -exponent, digits, options.m_rounding_mode.get()) {
+        Ok(mantissa) => mantissa,
+        Err(non_finite) => return non_finite,
+    };
+    // Rounding may carry into a new digit, as in 9.996 to 10.00.
+    if mantissa.nonzero_magnitude_start() > 0 {
+        exponent += 1;
+        mantissa = to_scaled_decimal(n, -exponent, digits, options.m_rounding_mode.get()).unwrap();
+    }
+    let mantissa = format_decimal(locale, &mantissa, false);
+    let exponent = format_decimal(locale, &FixedDecimal::from(exponent), false);
+    format!("{mantissa}E{exponent}")
+}
+
+/// Formats a currency amount according to the rules of the given locale.
+/// `currency` is an ISO 4217 code, such as `USD`, `EUR` or `JPY`.
+///
+/// The amount is rounded to the currency's number of fraction digits;
+/// for example, `JPY` has none and `USD` has two. Currency names are only
+/// included for English (see [`currency_display_for`]).
+pub fn format_currency(locale: &Locale, amount: f64, currency: &str, options: &CurrencyFormatOptions) -> String {
+    let locale: &Locale = &with_numbering_system(locale, options.m_numbering_system.get());
+    let currency = currency.to_ascii_uppercase();
+    let fraction_digits = currency_fraction_digits(&currency);
+    let digits = Digits::Fraction(fraction_digits, fraction_digits);
+    let mut decimal = match to_decimal(amount, digits, options.m_rounding_mode.get()) {
+        Ok(decimal) => decimal,
+        Err(non_finite) => return non_finite,
+    };
+    decimal.set_sign(fixed_decimal::Sign::None);
+    let number = format_decimal(locale, &decimal, options.m_use_grouping.get());
+    let sign = if amount < 0.0 && !decimal.is_zero() {
+        let mut negative = decimal.clone();
+        negative.set_sign(fixed_decimal::Sign::Negative);
+        let negative = format_decimal(locale, &negative, options.m_use_grouping.get());
+        negative.strip_suffix(&number).unwrap_or("-").to_owned()
+    } else {
+        String::new()
+    };
+    let display = currency_display_for(locale, &currency, options.m_display.get());
+    if display == CurrencyDisplay::Name {
+        if let Some((one, other)) = currency_names(&currency) {
+            let plural = PluralRules::try_new_cardinal(&locale.into())
+                .map_or(PluralCategory::Other, |rules| rules.category_for(&decimal));
+            let name = if plural == PluralCategory::One { one } else { other };
+            return format!("{sign}{number} {name}");
+        }
+    }
+
+    let request = DataRequest { locale: &locale.into(), metadata: Default::default() };
+    let Ok(payload) = DataProvider::<CurrencyEssentialsV1Marker>::load(&icu::experimental::provider::Baked, request)
+        .and_then(|response| response.take_payload()) else {
+        return format!("{sign}{number} {currency}");
+    };
+    let data = payload.get();
+    // Codes use the locale's pattern for currencies without a symbol.
+    let config = match TinyAsciiStr::<3>::from_str(&currency) {
+        Ok(code) if display == CurrencyDisplay::Symbol => data.pattern_config_map
+            .get_copied(&code.to_unvalidated())
+            .unwrap_or(data.default_pattern_config),
+        _ => data.default_pattern_config,
+    };
+    let symbol = match config.short_placeholder_value {
+        Some(PlaceholderValue::Index(index)) if display == CurrencyDisplay::Symbol => data.placeholders.get(index.into()),
+        _ => None,
+    };
+    let pattern = match config.short_pattern_selection {
+        PatternSelection::Standard => data.standard_pattern.as_ref(),
+        PatternSelection::StandardAlphaNextToNumber => data.standard_alpha_next_to_number_pattern.as_ref(),
+    };
+    let Some(pattern) = pattern else {
+        return format!("{sign}{number} {currency}");
+    };
+    format!("{sign}{}", pattern.interpolate((number.as_str(), symbol.unwrap_or(&currency))))
+}
+
+/// Returns how [`format_currency`] displays a currency in the given
+/// locale when asked to display it as given. `CurrencyDisplay::Name`
+/// falls back to `CurrencyDisplay::Code` for the locales other than
+/// English and for the currencies without an English name.
+pub fn currency_display_for(locale: &Locale, currency: &str, display: CurrencyDisplay) -> CurrencyDisplay {
+    if display == CurrencyDisplay::Name && (locale.id.language.as_str() != "en" || currency_names(&currency.to_ascii_uppercase()).is_none()) {
+        return CurrencyDisplay::Code;
+    }
+    display
+}
+
+/// Formats a quantity in a measurement unit according to the rules of
+/// the given locale, such as `5 km` or `5 kilometers`. Only the grouping,
+/// digit and bidi mark options apply.
+///
+/// Unit symbols and English unit names are taken from CLDR. The symbols
+/// are the English ones in every locale, and unit names are only included
+/// for English; [`unit_style_for`] tells the style actually displayed.
+pub fn format_unit(locale: &Locale, value: f64, unit: Unit, style: UnitStyle, options: &NumberFormatOptions) -> String {
+    let locale: &Locale = &with_numbering_system(locale, options.m_numbering_system.get());
+    let (number, plural) = match to_decimal(value, options.digits(), options.m_rounding_mode.get()) {
+        Ok(decimal) => {
+            let plural = PluralRules::try_new_cardinal(&locale.into())
+                .map_or(PluralCategory::Other, |rules| rules.category_for(&decimal));
+            (format_decimal(locale, &decimal, options.m_use_grouping.get()), plural)
+        },
+        Err(non_finite) => (non_finite, PluralCategory::Other),
+    };
+    let style = unit_style_for(locale, style);
+    let (narrow, short, long_one, long_other) = unit_patterns(unit);
+    let pattern = match style {
+        UnitStyle::Narrow => narrow,
+        UnitStyle::Short => short,
+        UnitStyle::Long if plural == PluralCategory::One => long_one,
+        UnitStyle::Long => long_other,
+    };
+    with_bidi_marks(locale, pattern.replacen("{0}", &number, 1), options)
+}
+
+/// Returns the style in which [`format_unit`] displays a unit in the given
+/// locale when asked to display it in the given style. `UnitStyle::Long`
+/// falls back to `UnitStyle::Short` for the locales other than English.
+pub fn unit_style_for(locale: &Locale, style: UnitStyle) -> UnitStyle {
+    if style == UnitStyle::Long && locale.id.language.as_str() != "en" {
+        return UnitStyle::Short;
+    }
+    style
+}
+
+/// Returns the English narrow, short and long (singular and plural)
+/// patterns of a unit.
+fn unit_patterns(unit: Unit) -> (&'static str, &'static str, &'static str, &'static str) {
+    match unit {
+        Unit::Millimeter => ("{0}mm", "{0} mm", "{0} millimeter", "{0} millimeters"),
+        Unit::Centimeter => ("{0}cm", "{0} cm", "{0} centimeter", "{0} centimeters"),
+        Unit::Meter => ("{0}m", "{0} m", "{0} meter", "{0} meters"),
+        Unit::Kilometer => ("{0}km", "{0} km", "{0} kilometer", "{0} kilometers"),
+        Unit::Inch => ("{0}″", "{0} in", "{0} inch", "{0} inches"),
+        Unit::Foot => ("{0}′", "{0} ft", "{0} foot", "{0} feet"),
+        Unit::Mile => ("{0}mi", "{0} mi", "{0} mile", "{0} miles"),
+        Unit::Gram => ("{0}g", "{0} g", "{0} gram", "{0} grams"),
+        Unit::Kilogram => ("{0}kg", "{0} kg", "{0} kilogram", "{0} kilograms"),
+        Unit::Ounce => ("{0}oz", "{0} oz", "{0} ounce", "{0} ounces"),
+        Unit::Pound => ("{0}lb", "{0} lb", "{0} pound", "{0} pounds"),
+        Unit::MeterPerSecond => ("{0}m/s", "{0} m/s", "{0} meter per second", "{0} meters per second"),
+        Unit::KilometerPerHour => ("{0}km/h", "{0} km/h", "{0} kilometer per hour", "{0} kilometers per hour"),
+        Unit::MilePerHour => ("{0}mph", "{0} mph", "{0} mile per hour", "{0} miles per hour"),
+        Unit::Celsius => ("{0}°C", "{0}°C", "{0} degree Celsius", "{0} degrees Celsius"),
+        Unit::Fahrenheit => ("{0}°", "{0}°F", "{0} degree Fahrenheit", "{0} degrees Fahrenheit"),
+        Unit::Kelvin => ("{0}K", "{0} K", "{0} kelvin", "{0} kelvins"),
+    }
+}
+
+/// Returns the ISO 4217 code of the currency used in the region
+/// of a locale, such as `USD` for `en-US` and `JPY` for `ja-JP`,
+/// as given by the CLDR territory data. A `-u-cu-` extension,
+/// as in `en-US-u-cu-eur`, takes precedence over the region.
+///
+/// Returns `None` if the locale is malformed, has no region,
+/// or its region has no currency.
+pub fn default_currency(locale: &str) -> Option<String> {
+    let locale = Locale::from_str(locale).ok()?;
+    if let Some(currency) = locale.extensions.unicode.keywords.get(&key!("cu")) {
+        return Some(currency.to_string().to_ascii_uppercase());
+    }
+    let currency = match locale.id.region?.as_str() {
+        | "AD" | "AT" | "AX" | "BE" | "BG" | "BL" | "CY" | "DE" | "EA" | "EE" | "ES" | "FI"
+        | "FR" | "GF" | "GP" | "GR" | "HR" | "IC" | "IE" | "IT" | "LT" | "LU" | "LV" | "MC"
+        | "ME" | "MF" | "MQ" | "MT" | "NL" | "PM" | "PT" | "RE" | "SI" | "SK" | "SM" | "TF"
+        | "VA" | "XK" | "YT"
+            => "EUR",
+        | "AS" | "BQ" | "EC" | "FM" | "GU" | "IO" | "MH" | "MP" | "PR" | "PW" | "SV" | "TC"
+        | "TL" | "UM" | "US" | "VG" | "VI"
+            => "USD",
+        | "AU" | "CC" | "CX" | "HM" | "KI" | "NF" | "NR" | "TV" => "AUD",
+        | "CK" | "NU" | "NZ" | "PN" | "TK" => "NZD",
+        | "GB" | "GG" | "GS" | "IM" | "JE" => "GBP",
+        | "AG" | "AI" | "DM" | "GD" | "KN" | "LC" | "MS" | "VC" => "XCD",
+        | "BF" | "BJ" | "CI" | "GW" | "ML" | "NE" | "SN" | "TG" => "XOF",
+        | "CF" | "CG" | "CM" | "GA" | "GQ" | "TD" => "XAF",
+        | "NC" | "PF" | "WF" => "XPF",
+        | "BV" | "NO" | "SJ" => "NOK",
+        | "DK" | "FO" | "GL" => "DKK",
+        | "CH" | "LI" => "CHF",
+        | "CW" | "SX" => "ANG",
+        | "EH" | "MA" => "MAD",
+        | "IL" | "PS" => "ILS",
+        "AE" => "AED", "AF" => "AFN", "AL" => "ALL", "AM" => "AMD", "AO" => "AOA",
+        "AR" => "ARS", "AW" => "AWG", "AZ" => "AZN", "BA" => "BAM", "BB" => "BBD",
+        "BD" => "BDT", "BH" => "BHD", "BI" => "BIF", "BM" => "BMD", "BN" => "BND",
+        "BO" => "BOB", "BR" => "BRL", "BS" => "BSD", "BT" => "BTN", "BW" => "BWP",
+        "BY" => "BYN", "BZ" => "BZD", "CA" => "CAD", "CD" => "CDF", "CL" => "CLP",
+        "CN" => "CNY", "CO" => "COP", "CR" => "CRC", "CU" => "CUP", "CV" => "CVE",
+        "CZ" => "CZK", "DJ" => "DJF", "DO" => "DOP", "DZ" => "DZD", "EG" => "EGP",
+        "ER" => "ERN", "ET" => "ETB", "FJ" => "FJD", "FK" => "FKP", "GE" => "GEL",
+        "GH" => "GHS", "GI" => "GIP", "GM" => "GMD", "GN" => "GNF", "GT" => "GTQ",
+        "GY" => "GYD", "HK" => "HKD", "HN" => "HNL", "HT" => "HTG", "HU" => "HUF",
+        "ID" => "IDR", "IN" => "INR", "IQ" => "IQD", "IR" => "IRR", "IS" => "ISK",
+        "JM" => "JMD", "JO" => "JOD", "JP" => "JPY", "KE" => "KES", "KG" => "KGS",
+        "KH" => "KHR", "KM" => "KMF", "KP" => "KPW", "KR" => "KRW", "KW" => "KWD",
+        "KY" => "KYD", "KZ" => "KZT", "LA" => "LAK", "LB" => "LBP", "LK" => "LKR",
+        "LR" => "LRD", "LS" => "LSL", "LY" => "LYD", "MD" => "MDL", "MG" => "MGA",
+        "MK" => "MKD", "MM" => "MMK", "MN" => "MNT", "MO" => "MOP", "MR" => "MRU",
+        "MU" => "MUR", "MV" => "MVR", "MW" => "MWK", "MX" => "MXN", "MY" => "MYR",
+        "MZ" => "MZN", "NA" => "NAD", "NG" => "NGN", "NI" => "NIO", "NP" => "NPR",
+        "OM" => "OMR", "PA" => "PAB", "PE" => "PEN", "PG" => "PGK", "PH" => "PHP",
+        "PK" => "PKR", "PL" => "PLN", "PY" => "PYG", "QA" => "QAR", "RO" => "RON",
+        "RS" => "RSD", "RU" => "RUB", "RW" => "RWF", "SA" => "SAR", "SB" => "SBD",
+        "SC" => "SCR", "SD" => "SDG", "SE" => "SEK", "SG" => "SGD", "SH" => "SHP",
+        "SL" => "SLE", "SO" => "SOS", "SR" => "SRD", "SS" => "SSP", "ST" => "STN",
+        "SY" => "SYP", "SZ" => "SZL", "TH" => "THB", "TJ" => "TJS", "TM" => "TMT",
+        "TN" => "TND", "TO" => "TOP", "TR" => "TRY", "TT" => "TTD", "TW" => "TWD",
+        "TZ" => "TZS", "UA" => "UAH", "UG" => "UGX", "UY" => "UYU", "UZ" => "UZS",
+        "VE" => "VES", "VN" => "VND", "VU" => "VUV", "WS" => "WST", "YE" => "YER",
+        "ZA" => "ZAR", "ZM" => "ZMW", "ZW" => "ZWG",
+        _ => return None,
+    };
+    Some(currency.to_owned())
+}
+
+/// Returns the default number of fraction digits of an ISO 4217 currency,
+/// as given by the CLDR supplemental currency data.
+fn currency_fraction_digits(currency: &str) -> u8 {
+    match currency {
+        | "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG"
+        | "RWF" | "UGX" | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF"
+            => 0,
+        | "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND"
+            => 3,
+        | "CLF" | "UYW"
+            => 4,
+        _ => 2,
+    }
+}
+
+/// Returns the English singular and plural names of a currency.
+fn currency_names(currency: &str) -> Option<(&'static str, &'static str)> {
+    Some(match currency {
+        "AED" => ("UAE dirham", "UAE dirhams"),
+        "ARS" => ("Argentine peso", "Argentine pesos"),
+        "AUD" => ("Australian dollar", "Australian dollars"),
+        "BRL" => ("Brazilian real", "Brazilian reals"),
+        "CAD" => ("Canadian dollar", "Canadian dollars"),
+        "CHF" => ("Swiss franc", "Swiss francs"),
+        "CLP" => ("Chilean peso", "Chilean pesos"),
+        "CNY" => ("Chinese yuan", "Chinese yuan"),
+        "COP" => ("Colombian peso", "Colombian pesos"),
+        "CZK" => ("Czech koruna", "Czech korunas"),
+        "DKK" => ("Danish krone", "Danish kroner"),
+        "EGP" => ("Egyptian pound", "Egyptian pounds"),
+        "EUR" => ("euro", "euros"),
+        "GBP" => ("British pound", "British pounds"),
+        "HKD" => ("Hong Kong dollar", "Hong Kong dollars"),
+        "HUF" => ("Hungarian forint", "Hungarian forints"),
+        "IDR" => ("Indonesian rupiah", "Indonesian rupiahs"),
+        "ILS" => ("Israeli new shekel", "Israeli new shekels"),
+        "INR" => ("Indian rupee", "Indian rupees"),
+        "JPY" => ("Japanese yen", "Japanese yen"),
+        "KRW" => ("South Korean won", "South Korean won"),
+        "KWD" => ("Kuwaiti dinar", "Kuwaiti dinars"),
+        "MXN" => ("Mexican peso", "Mexican pesos"),
+        "MYR" => ("Malaysian ringgit", "Malaysian ringgits"),
+        "NGN" => ("Nigerian naira", "Nigerian nairas"),
+        "NOK" => ("Norwegian krone", "Norwegian kroner"),
+        "NZD" => ("New Zealand dollar", "New Zealand dollars"),
+        "PEN" => ("Peruvian sol", "Peruvian soles"),
+        "PHP" => ("Philippine peso", "Philippine pesos"),
+        "PKR" => ("Pakistani rupee", "Pakistani rupees"),
+        "PLN" => ("Polish zloty", "Polish zlotys"),
+        "RON" => ("Romanian leu", "Romanian lei"),
+        "RUB" => ("Russian ruble", "Russian rubles"),
+        "SAR" => ("Saudi riyal", "Saudi riyals"),
+        "SEK" => ("Swedish krona", "Swedish kronor"),
+        "SGD" => ("Singapore dollar", "Singapore dollars"),
+        "THB" => ("Thai baht", "Thai baht"),
+        "TWD" => ("New Taiwan dollar", "New Taiwan dollars"),
+        "UAH" => ("Ukrainian hryvnia", "Ukrainian hryvnias"),
+        "USD" => ("US dollar", "US dollars"),
+        "VND" => ("Vietnamese dong", "Vietnamese dong"),
+        "ZAR" => ("South African rand", "South African rand"),
+        _ => return None,
+    })
+}
+
+/// Minimum and maximum digits to which a number is rounded and padded.
+#[derive(Copy, Clone)]
+enum Digits {
+    Fraction(u8, u8),
+    Significant(u8, u8),
+}
+
+/// Converts a finite number to a decimal rounded to the given digits.
+/// Non-finite numbers are returned already formatted as `Err`.
+fn to_decimal(n: f64, digits: Digits, rounding_mode: RoundingMode) -> Result<FixedDecimal, String> {
+    to_scaled_decimal(n, 0, digits, rounding_mode)
+}
+
+/// Like `to_decimal`, but multiplies the number by a power of ten first,
+/// without the rounding errors of floating-point multiplication.
+fn to_scaled_decimal(n: f64, pow10: i16, digits: Digits, rounding_mode: RoundingMode) -> Result<FixedDecimal, String> {
+    if n.is_nan() {
+        return Err("NaN".to_owned());
+    }
+    if n.is_infinite() {
+        return Err(if n < 0.0 { "-∞".to_owned() } else { "∞".to_owned() });
+    }
+    // `Display` for `f64` never uses exponent notation.
+    let mut decimal = FixedDecimal::from_str(&n.to_string()).unwrap();
+    decimal.multiply_pow10(pow10);
+    decimal.trim_start();
+    match digits {
+        Digits::Fraction(minimum, maximum) => {
+            round(&mut decimal, -(maximum as i16), rounding_mode);
+            decimal.trim_end();
+            decimal.pad_end(-(minimum as i16));
+        },
+        Digits::Significant(minimum, maximum) => {
+            let position = decimal.nonzero_magnitude_start() - maximum as i16 + 1;
+            round(&mut decimal, position, rounding_mode);
+            decimal.trim_end();
+            // Rounding may carry into a new digit, as in 99.9 to 100.
+            decimal.pad_end((decimal.nonzero_magnitude_start() - minimum as i16 + 1).min(0));
+        },
+    }
+    Ok(decimal)
+}
+
+fn round(decimal: &mut FixedDecimal, position: i16, rounding_mode: RoundingMode) {
+    match rounding_mode {
+        RoundingMode::HalfEven => decimal.half_even(position),
+        RoundingMode::HalfUp => decimal.half_expand(position),
+        RoundingMode::HalfDown => decimal.half_trunc(position),
+        RoundingMode::Ceil => decimal.ceil(position),
+        RoundingMode::Floor => decimal.floor(position),
+        RoundingMode::Trunc => decimal.trunc(position),
+    }
+}
+
+fn format_compact(locale: &Locale, n: f64, style: CompactStyle, use_grouping: bool) -> String {
+    // Round to at most three fraction digits first, as with the default
+    // options, since compact numbers never display more.
+    let decimal = match to_decimal(n, Digits::Fraction(0, 3), RoundingMode::HalfEven) {
+        Ok(decimal) => decimal,
+        Err(non_finite) => return non_finite,
+    };
+    let grouping_strategy = if use_grouping { GroupingStrategy::Min2 } else { GroupingStrategy::Never };
+    let data_locale = &locale.into();
+    let formatter = match style {
+        CompactStyle::Short => CompactDecimalFormatter::try_new_short(data_locale, grouping_strategy.into()),
+        CompactStyle::Long => CompactDecimalFormatter::try_new_long(data_locale, grouping_strategy.into()),
+    };
+    match formatter {
+        Ok(formatter) => shape_digits(locale, formatter.format_fixed_decimal(decimal).write_to_string().into_owned()),
+        Err(_) => decimal.to_string(),
+    }
+}
+
+fn format_decimal(locale: &Locale, decimal: &FixedDecimal, use_grouping: bool) -> String {
+    let mut formatter_options = FixedDecimalFormatterOptions::default();
+    if !use_grouping {
+        formatter_options.grouping_strategy = GroupingStrategy::Never;
+    }
+    match FixedDecimalFormatter::try_new(&locale.into(), formatter_options) {
+        Ok(formatter) => shape_digits(locale, formatter.format_to_string(decimal)),
+        Err(_) => decimal.to_string(),
+    }
+}
+
+/// Wraps a formatted number in the directional mark of the locale,
+/// for the `bidi_marks` option.
+fn with_bidi_marks(locale: &Locale, formatted: String, options: &NumberFormatOptions) -> String {
+    if !options.m_bidi_marks.get() {
+        return formatted;
+    }
+    let mark = match locale.text_direction() {
+        TextDirection::Rtl => '\u{200f}',
+        TextDirection::Ltr => '\u{200e}',
+    };
+    format!("{mark}{formatted}{mark}")
+}
+
+/// Sets the `-u-nu-` extension of a locale to the given numbering system.
+fn with_numbering_system(locale: &Locale, numbering_system: Option<TinyAsciiStr<8>>) -> Cow<'_, Locale> {
+    let Some(value) = numbering_system.and_then(|ns| Value::try_from_bytes(ns.as_bytes()).ok()) else {
+        return Cow::Borrowed(locale);
+    };
+    let mut locale = locale.clone();
+    locale.extensions.unicode.keywords.set(key!("nu"), value);
+    Cow::Owned(locale)
+}
+
+/// Replaces ASCII digits by the digits of the locale's `-u-nu-` numbering
+/// system, for locales whose data does not include that numbering system.
+fn shape_digits(locale: &Locale, formatted: String) -> String {
+    let zero = match locale.extensions.unicode.keywords.get(&key!("nu")).map(|value| value.to_string()).as_deref() {
+        Some("arab") => '\u{660}',
+        Some("arabext") => '\u{6f0}',
+        Some("beng") => '\u{9e6}',
+        Some("deva") => '\u{966}',
+        Some("fullwide") => '\u{ff10}',
+        Some("gujr") => '\u{ae6}',
+        Some("guru") => '\u{a66}',
+        Some("khmr") => '\u{17e0}',
+        Some("knda") => '\u{ce6}',
+        Some("laoo") => '\u{ed0}',
+        Some("mlym") => '\u{d66}',
+        Some("mymr") => '\u{1040}',
+        Some("orya") => '\u{b66}',
+        Some("tamldec") => '\u{be6}',
+        Some("telu") => '\u{c66}',
+        Some("thai") => '\u{e50}',
+        Some("tibt") => '\u{f20}',
+        _ => return formatted,
+    };
+    formatted.chars().map(|c| match c.to_digit(10) {
+        Some(digit) if c.is_ascii_digit() => char::from_u32(zero as u32 + digit).unwrap(),
+        _ => c,
+    }).collect()
+}
@@ -0,0 +1,49 @@
+//! Locale-aware relative time formatting.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, relative_time::{self, RelativeTimeUnit}};
+//!
+//! assert_eq!(relative_time::format(&locale!("en"), 3, RelativeTimeUnit::Day), "in 3 days");
+//! assert_eq!(relative_time::format(&locale!("en"), -3, RelativeTimeUnit::Minute), "3 minutes ago");
+//! ```
+
+use crate::locale::Locale;
+use icu::experimental::relativetime::RelativeTimeFormatter;
+use writeable::Writeable;
+
+/// Unit of a relative time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RelativeTimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+/// Formats a relative time according to the rules of the given locale.
+/// A negative value is in the past and any other value is in the future,
+/// such as `3 days ago` and `in 3 days`.
+pub fn format(locale: &Locale, value: i64, unit: RelativeTimeUnit) -> String {
+    let locale = &locale.into();
+    let options = Default::default();
+    let formatter = match unit {
+        RelativeTimeUnit::Second => RelativeTimeFormatter::try_new_long_second(locale, options),
+        RelativeTimeUnit::Minute => RelativeTimeFormatter::try_new_long_minute(locale, options),
+        RelativeTimeUnit::Hour => RelativeTimeFormatter::try_new_long_hour(locale, options),
+        RelativeTimeUnit::Day => RelativeTimeFormatter::try_new_long_day(locale, options),
+        RelativeTimeUnit::Week => RelativeTimeFormatter::try_new_long_week(locale, options),
+        RelativeTimeUnit::Month => RelativeTimeFormatter::try_new_long_month(locale, options),
+        RelativeTimeUnit::Quarter => RelativeTimeFormatter::try_new_long_quarter(locale, options),
+        RelativeTimeUnit::Year => RelativeTimeFormatter::try_new_long_year(locale, options),
+    };
+    match formatter {
+        Ok(formatter) => formatter.format(value.into()).write_to_string().into_owned(),
+        Err(_) => format!("{value} {}", format!("{unit:?}").to_lowercase()),
+    }
+}
@@ -0,0 +1,36 @@
+//! Text segmentation into grapheme clusters and words.
+//!
+//! # Examples
+//!
+//! ```
+//! use rialight::intl::{locale::locale, segment};
+//!
+//! assert_eq!(segment::graphemes("e\u{301}🇧🇷").collect::<Vec<_>>(), ["e\u{301}", "🇧🇷"]);
+//! assert_eq!(segment::words("Hi, Ada", &locale!("en")).collect::<Vec<_>>(), ["Hi", ",", " ", "Ada"]);
+//! ```
+
+use crate::locale::Locale;
+use icu::segmenter::{GraphemeClusterSegmenter, WordSegmenter};
+
+/// Splits a string into its grapheme clusters, the units that users
+/// perceive as characters, such as an emoji or a letter with
+/// combining marks, for deleting or moving the caret by character.
+pub fn graphemes(s: &str) -> impl Iterator<Item = &str> {
+    let breakpoints: Vec<usize> = GraphemeClusterSegmenter::new().segment_str(s).collect();
+    segments(s, breakpoints)
+}
+
+/// Splits a string at its word boundaries, for moving the caret or
+/// selecting by word. The segments include the whitespace and punctuation
+/// between words. Words in scripts written without spaces, such as Thai
+/// and Japanese, are found through dictionaries and models selected by
+/// the script of the text, so the locale does not currently change
+/// the result.
+pub fn words<'a>(s: &'a str, _locale: &Locale) -> impl Iterator<Item = &'a str> {
+    let breakpoints: Vec<usize> = WordSegmenter::new_auto().segment_str(s).collect();
+    segments(s, breakpoints)
+}
+
+fn segments(s: &str, breakpoints: Vec<usize>) -> impl Iterator<Item = &str> {
+    (1..breakpoints.len()).map(move |i| &s[breakpoints[i - 1]..breakpoints[i]])
+}
@@ -7,5 +7,5 @@ rialight::initialize!(async move |app| {
     if !ftl.load(None).await {
         return;
     }
-    println!("{}", ftl.get_message("hello-world", None, &mut vec![]).unwrap());
+    println!("{}", ftl.get_message("hello-world").unwrap());
 });
\ No newline at end of file
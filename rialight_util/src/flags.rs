@@ -2,8 +2,11 @@
 //! It can be used for creating typesafe wrappers around C APIs.
 //!
 //! The `flags!` macro generates `struct`s that manage a set of flags. The
-//! flags should only be defined for integer types, otherwise unexpected type
-//! errors may occur at compile time.
+//! flag constants and most methods are generated by the crate
+//! [bitflags](https://crates.io/crates/bitflags), which is re-exported as
+//! `rialight::util::flags::bitflags`; refer to it for more information.
+//! The flags should only be defined for integer types, otherwise unexpected
+//! type errors may occur at compile time.
 //!
 //! # Example
 //!
@@ -15,7 +18,7 @@
 //!         const A = 0b00000001;
 //!         const B = 0b00000010;
 //!         const C = 0b00000100;
-//!         const ABC = Self::A.bits | Self::B.bits | Self::C.bits;
+//!         const ABC = Self::A.bits() | Self::B.bits() | Self::C.bits();
 //!     }
 //! }
 //!
@@ -27,11 +30,8 @@
 //! assert_eq!(!e2, Flags::A);           // set complement
 //! ```
 //!
-//! See [`example_generated::Flags`](./example_generated/struct.Flags.html) for documentation of code
-//! generated by the above `flags!` expansion.
-//!
-//! The generated `struct`s can also be extended with type and trait
-//! implementations:
+//! The generated `struct`s are newtypes over their bits type and can also be
+//! extended with type and trait implementations:
 //!
 //! ```
 //! use std::fmt;
@@ -46,9 +46,9 @@
 //! }
 //!
 //! impl Flags {
-//!     pub fn clear(&mut self) {
-//!         self.bits = 0;  // The `bits` field can be accessed from within the
-//!                         // same module where the `flags!` macro was invoked.
+//!     pub fn keep_low_bit(&mut self) {
+//!         self.0 &= 1; // The bits can be accessed from within the same
+//!                      // module where the `flags!` macro was invoked.
 //!     }
 //! }
 //!
@@ -60,6 +60,8 @@
 //!
 //! {
 //!     let mut flags = Flags::A | Flags::B;
+//!     flags.keep_low_bit();
+//!     assert_eq!(flags, Flags::A);
 //!     flags.clear();
 //!     assert!(flags.is_empty());
 //!     assert_eq!(format!("{}", flags), "hi!");
@@ -119,20 +121,119 @@
 //! }
 //! ```
 //!
+//! ## Options
+//!
+//! Additional behavior can be enabled for a single struct by placing a
+//! `#[flags(...)]` attribute before the `struct` keyword. The following
+//! options are supported:
+//!
+//! - `serde`: implements `Serialize` and `Deserialize`. The flags are
+//!   represented as a list of single-bit flag names, such as `["A", "C"]`,
+//!   followed by the names of the composite constants that cover any
+//!   remaining bits, and deserialized by combining the named flags. Bits
+//!   that no flag constant covers fail to serialize. Requires the `serde`
+//!   feature.
+//! - `arbitrary`: implements the proptest `Arbitrary` trait. Generated values
+//!   only combine defined flags, so that property-based tests never observe
+//!   unknown bits. Requires the `arbitrary` feature.
+//! - `bytemuck`: implements bytemuck's `Pod` and `Zeroable` traits, so that
+//!   slices of flags can be cast to bytes without `unsafe`. The struct must be
+//!   `#[repr(transparent)]`. Requires the `bytemuck` feature.
+//! - `strict`: `FromStr` rejects bits that do not correspond to a defined flag.
+//! - `schema`: defines a `schema` method describing each flag constant (see below).
+//!
+//! ```ignore
+//! use rialight::util::flags::flags;
+//!
+//! flags! {
+//!     #[flags(serde)]
+//!     struct Flags: u32 {
+//!         const A = 0b00000001;
+//!         const B = 0b00000010;
+//!         const C = 0b00000100;
+//!     }
+//! }
+//!
+//! {
+//!     let json = serde_json::to_string(&(Flags::A | Flags::C)).unwrap();
+//!     assert_eq!(json, r#"["A","C"]"#);
+//!     assert_eq!(serde_json::from_str::<Flags>(&json).unwrap(), Flags::A | Flags::C);
+//! }
+//! ```
+//!
+//! With the `schema` option, which requires the `schema` feature, the `schema`
+//! method returns a `FlagsSchema` listing the name, bits and doc comment of each
+//! flag constant, so that tools such as editors can build checkbox lists.
+//!
 //! # Trait implementations
 //!
 //! The `Copy`, `Clone`, `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash`
 //! traits are automatically derived for the `struct`s using the `derive` attribute.
 //! Additional traits can be derived by providing an explicit `derive`
-//! attribute on `struct`.
+//! attribute on `struct`. The bitflags `Flags` trait is implemented as well.
 //!
 //! The `Extend` and `FromIterator` traits are implemented for the `struct`s,
 //! too: `Extend` adds the union of the instances of the `struct` iterated over,
 //! while `FromIterator` calculates the union.
 //!
-//! The `Binary`, `Debug`, `LowerHex`, `Octal` and `UpperHex` traits are also
+//! The `Binary`, `LowerHex`, `Octal` and `UpperHex` traits are also
 //! implemented by displaying the bits value of the internal struct.
 //!
+//! The `Debug` trait displays the names of the single-bit flags in ascending
+//! bit order, regardless of the order in which they are declared, separated
+//! by `|`. Any remaining bits are named by the composite constants that
+//! contain them, in declaration order, and the bits that no constant covers
+//! are appended as a hexadecimal number. An empty set of flags is displayed
+//! as `(empty)`.
+//!
+//! ```
+//! use rialight::util::flags::flags;
+//!
+//! flags! {
+//!     struct Flags: u32 {
+//!         const C = 0b00000100;
+//!         const A = 0b00000001;
+//!         const AC = Self::A.bits() | Self::C.bits();
+//!         const HI = 0b110000;
+//!     }
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(format!("{:?}", Flags::AC), "A | C");
+//!     assert_eq!(format!("{:?}", Flags::all()), "A | C | HI");
+//!     assert_eq!(format!("{:?}", Flags::from_bits_retain(0b1001)), "A | 0x8");
+//!     assert_eq!(format!("{:?}", Flags::empty()), "(empty)");
+//! }
+//! ```
+//!
+//! The `IntoIterator` trait is implemented for the `struct`s and references
+//! to them, yielding the same flags as the `iter` method.
+//!
+//! The `FromStr` trait parses flag names separated by `|`, the same syntax
+//! that `Debug` displays, so that the `Debug` output of any value parses back
+//! to it. Whitespace around names is ignored, `0x` tokens are parsed as
+//! hexadecimal bits, and both an empty string and `(empty)` parse to an empty
+//! set of flags. An unrecognized name results in a [`ParseError`].
+//!
+//! ```
+//! use rialight::util::flags::flags;
+//!
+//! flags! {
+//!     struct Flags: u32 {
+//!         const A = 0b00000001;
+//!         const B = 0b00000010;
+//!         const C = 0b00000100;
+//!     }
+//! }
+//!
+//! {
+//!     assert_eq!("A | C".parse::<Flags>().unwrap(), Flags::A | Flags::C);
+//!     assert_eq!("".parse::<Flags>().unwrap(), Flags::empty());
+//!     assert_eq!("A | 0x8".parse::<Flags>().unwrap(), Flags::from_bits_retain(0b1001));
+//!     assert_eq!("A | D".parse::<Flags>().unwrap_err().token(), "D");
+//! }
+//! ```
+//!
 //! ## Operators
 //!
 //! The following operator traits are implemented for the generated `struct`s:
@@ -143,42 +244,66 @@
 //! - `Sub` and `SubAssign`: set difference
 //! - `Not`: set complement
 //!
+//! # Associated constants
+//!
+//! Besides the flags themselves, the following constants are defined for the
+//! generated `struct`s:
+//!
+//! - `NAMES`: the names of all flag constants, including composite and zero-
+//!   valued constants, in declaration order
+//! - `ENTRIES`: the names and values of all flag constants, including composite
+//!   and zero-valued constants, in declaration order
+//!
 //! # Methods
 //!
-//! The following methods are defined for the generated `struct`s:
-//!
-//! - `empty`: an empty set of flags
-//! - `all`: the set of all defined flags
-//! - `bits`: the raw value of the flags currently stored
-//! - `from_bits`: convert from underlying bit representation, unless that
-//!                representation contains bits that do not correspond to a
-//!                defined flag
-//! - `from_bits_truncate`: convert from underlying bit representation, dropping
-//!                         any bits that do not correspond to defined flags
-//! - `from_bits_unchecked`: convert from underlying bit representation, keeping
-//!                          all bits (even those not corresponding to defined
-//!                          flags)
-//! - `is_empty`: `true` if no flags are currently stored
-//! - `is_all`: `true` if currently set flags exactly equal all defined flags
-//! - `intersects`: `true` if there are flags common to both `self` and `other`
-//! - `contains`: `true` if all of the flags in `other` are contained within `self`
-//! - `insert`: inserts the specified flags in-place
-//! - `remove`: removes the specified flags in-place
-//! - `toggle`: the specified flags will be inserted if not present, and removed
-//!             if they are.
-//! - `set`: inserts or removes the specified flags depending on the passed value
-//! - `intersection`: returns a new set of flags, containing only the flags present
-//!                   in both `self` and `other` (the argument to the function).
-//! - `union`: returns a new set of flags, containing any flags present in
-//!            either `self` or `other` (the argument to the function).
-//! - `difference`: returns a new set of flags, containing all flags present in
-//!                 `self` without any of the flags present in `other` (the
-//!                 argument to the function).
-//! - `symmetric_difference`: returns a new set of flags, containing all flags
-//!                           present in either `self` or `other` (the argument
-//!                           to the function), but not both.
-//! - `complement`: returns a new set of flags, containing all flags which are
-//!                 not set in `self`, but which are allowed for this type.
+//! Besides the methods that bitflags generates, such as `empty`, `all`,
+//! `bits`, `from_bits`, `from_bits_truncate`, `from_bits_retain`,
+//! `from_name`, `contains`, `insert`, `iter` and `iter_names`, the following
+//! methods are defined for the generated `struct`s:
+//!
+//! - `bits_mut`: a mutable reference to the raw value of the flags currently stored
+//! - `from_bits_checked`: same as `from_bits`, but the error reports the bits
+//!   that do not correspond to a defined flag
+//! - `len`: the number of single-bit flags currently stored
+//! - `contains_any`: same as `intersects`, `true` if any flag of `other` is in `self`
+//! - `contains_all`: same as `contains`, `true` if every flag of `other` is in `self`
+//! - `clear`: removes all bits in-place
+//! - `insert_checked` and `remove_checked`: same as `insert` and `remove`, but
+//!   return `true` only if the stored bits changed
+//! - `insert_if`: inserts the specified flags if the passed value is `true`
+//! - `retain`: retains only the single-bit flags for which a predicate returns
+//!   `true`
+//! - `to_names_string`: joins the names of the single-bit flags contained in
+//!   `self` with a separator, such as `"A, C"`.
+//! - `description`: the doc comment of the flag constant equal to `self`, if any
+//!
+//! ## Constant expressions
+//!
+//! The operators are not usable in `const` contexts, but the methods `empty`,
+//! `all`, `bits`, `from_bits`, `from_bits_truncate`, `from_bits_retain`,
+//! `is_empty`, `is_all`, `intersects`, `contains`, `contains_any`,
+//! `contains_all`, `intersection`, `union`, `difference`,
+//! `symmetric_difference` and `complement` are `const fn`s that behave
+//! identically to their operator counterparts. They can be used to build
+//! `const` and `static` tables:
+//!
+//! ```
+//! use rialight::util::flags::flags;
+//!
+//! flags! {
+//!     struct Flags: u32 {
+//!         const A = 0b00000001;
+//!         const B = 0b00000010;
+//!         const C = 0b00000100;
+//!     }
+//! }
+//!
+//! const MASK: Flags = Flags::A.union(Flags::B);
+//! static TABLE: [Flags; 2] = [MASK.difference(Flags::A), MASK.complement()];
+//!
+//! assert_eq!(MASK, Flags::A | Flags::B);
+//! assert_eq!(TABLE, [Flags::B, Flags::C]);
+//! ```
 //!
 //! ## Default
 //!
@@ -263,4 +388,780 @@
 //!
 //! Users should generally avoid defining a flag with a value of zero.
 
-pub use bitflags::bitflags as flags;
\ No newline at end of file
+/// The macro used to generate the flag structures.
+///
+/// See the [module level docs](self) for complete documentation.
+pub use crate::__flags as flags;
+
+pub use bitflags::{self, Flags};
+
+use std::fmt;
+
+/// Error returned when parsing a `flags!` struct from a string
+/// fails because of an unrecognized flag name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    m_token: String,
+    m_valid_names: &'static [&'static str],
+}
+
+impl ParseError {
+    /// The unrecognized flag name.
+    pub fn token(&self) -> &str {
+        &self.m_token
+    }
+
+    /// The names of the flags defined for the struct.
+    pub fn valid_names(&self) -> &'static [&'static str] {
+        self.m_valid_names
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unrecognized flag name `{}`, expected one of ", self.m_token)?;
+        for (i, name) in self.m_valid_names.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "`{}`", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Error returned by the `from_bits_checked` method of a `flags!` struct
+/// when the given bits contain bits that do not correspond to a defined flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownBitsError<T> {
+    m_unknown_bits: T,
+}
+
+impl<T: Copy> UnknownBitsError<T> {
+    /// The mask of the bits that do not correspond to a defined flag.
+    pub fn unknown_bits(&self) -> T {
+        self.m_unknown_bits
+    }
+}
+
+impl<T: fmt::LowerHex> fmt::Display for UnknownBitsError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bits {:#x} do not correspond to a defined flag", self.m_unknown_bits)
+    }
+}
+
+impl<T: fmt::Debug + fmt::LowerHex> std::error::Error for UnknownBitsError<T> {}
+
+/// Description of a `flags!` struct, returned by its `schema` method
+/// when the `schema` option is enabled.
+#[cfg(feature = "schema")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlagsSchema<T> {
+    m_name: &'static str,
+    m_flags: Vec<FlagSchema<T>>,
+}
+
+#[cfg(feature = "schema")]
+impl<T> FlagsSchema<T> {
+    /// The name of the struct.
+    pub fn name(&self) -> &'static str {
+        self.m_name
+    }
+
+    /// The flag constants, including composite and zero-valued
+    /// constants, in declaration order.
+    pub fn flags(&self) -> &[FlagSchema<T>] {
+        &self.m_flags
+    }
+}
+
+/// Description of a flag constant in a `FlagsSchema`.
+#[cfg(feature = "schema")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlagSchema<T> {
+    m_name: &'static str,
+    m_bits: T,
+    m_doc: String,
+}
+
+#[cfg(feature = "schema")]
+impl<T: Copy> FlagSchema<T> {
+    /// The name of the constant.
+    pub fn name(&self) -> &'static str {
+        self.m_name
+    }
+
+    /// The bits of the constant.
+    pub fn bits(&self) -> T {
+        self.m_bits
+    }
+
+    /// The doc comment of the constant, without the leading space
+    /// of each line, or an empty string.
+    pub fn doc(&self) -> &str {
+        &self.m_doc
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use std::fmt;
+    use bitflags::{Bits as _, Flags};
+
+    pub use core;
+    pub use bitflags;
+
+    #[cfg(feature = "arbitrary")]
+    pub use proptest;
+
+    #[cfg(feature = "bytemuck")]
+    pub use bytemuck;
+
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    /// The integer types that store the bits of a `flags!` struct.
+    pub trait Bits: bitflags::Bits + bitflags::parser::ParseHex + Ord + fmt::LowerHex {
+        fn count_ones(self) -> u32;
+    }
+
+    macro_rules! impl_bits {
+        ($($T:ty),*) => {
+            $(
+                impl Bits for $T {
+                    fn count_ones(self) -> u32 {
+                        <$T>::count_ones(self)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_bits!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+    pub const fn names<F: Flags, const N: usize>() -> [&'static str; N] {
+        let mut r = [""; N];
+        let mut i = 0;
+        while i < N {
+            r[i] = F::FLAGS[i].name();
+            i += 1;
+        }
+        r
+    }
+
+    pub const fn entries<F: Flags + Copy, const N: usize>(filler: F) -> [(&'static str, F); N] {
+        let mut r = [("", filler); N];
+        let mut i = 0;
+        while i < N {
+            r[i] = (F::FLAGS[i].name(), *F::FLAGS[i].value());
+            i += 1;
+        }
+        r
+    }
+
+    /// The single-bit flags contained in `flags`, in ascending bit order.
+    /// If several flags have the same bit, only the first one is listed.
+    pub fn single_bits<F: Flags + Copy>(flags: F) -> Vec<(&'static str, F)>
+    where
+        F::Bits: Bits,
+    {
+        let mut r: Vec<(&'static str, F)> = vec![];
+        for flag in F::FLAGS {
+            let bits = flag.value().bits();
+            if bits.count_ones() == 1 && flags.contains(*flag.value()) && !r.iter().any(|(_, f)| f.bits() == bits) {
+                r.push((flag.name(), *flag.value()));
+            }
+        }
+        r.sort_by_key(|(_, f)| f.bits());
+        r
+    }
+
+    /// The names of the single-bit flags contained in `flags`, followed by
+    /// the composite constants contained in `flags` that cover the remaining
+    /// bits, in declaration order. Also returns the bits that are left unnamed.
+    pub fn names_of<F: Flags + Copy>(flags: F) -> (Vec<&'static str>, F::Bits)
+    where
+        F::Bits: Bits,
+    {
+        let mut remaining = flags.bits();
+        let mut names = vec![];
+        for (name, flag) in single_bits(flags) {
+            names.push(name);
+            remaining = remaining & !flag.bits();
+        }
+        for flag in F::FLAGS {
+            let bits = flag.value().bits();
+            if flags.contains(*flag.value()) && remaining & bits != F::Bits::EMPTY {
+                names.push(flag.name());
+                remaining = remaining & !bits;
+            }
+        }
+        (names, remaining)
+    }
+
+    pub fn fmt_debug<F: Flags + Copy>(flags: F, f: &mut fmt::Formatter) -> fmt::Result
+    where
+        F::Bits: Bits,
+    {
+        let (names, remaining) = names_of(flags);
+        if names.is_empty() && remaining == F::Bits::EMPTY {
+            return f.write_str("(empty)");
+        }
+        f.write_str(&names.join(" | "))?;
+        if remaining != F::Bits::EMPTY {
+            if !names.is_empty() {
+                f.write_str(" | ")?;
+            }
+            write!(f, "{:#x}", remaining)?;
+        }
+        Ok(())
+    }
+
+    pub fn len<F: Flags + Copy>(flags: F) -> u32
+    where
+        F::Bits: Bits,
+    {
+        single_bits(flags).len() as u32
+    }
+
+    pub fn retain<F: Flags + Copy>(flags: &mut F, mut predicate: impl FnMut(F) -> bool)
+    where
+        F::Bits: Bits,
+    {
+        for (_, flag) in single_bits(*flags) {
+            if !predicate(flag) {
+                flags.remove(flag);
+            }
+        }
+    }
+
+    pub fn to_names_string<F: Flags + Copy>(flags: F, sep: &str) -> String
+    where
+        F::Bits: Bits,
+    {
+        single_bits(flags).iter().map(|(name, _)| *name).collect::<Vec<_>>().join(sep)
+    }
+
+    pub fn from_bits_checked<F: Flags>(bits: F::Bits) -> Result<F, super::UnknownBitsError<F::Bits>> {
+        let unknown_bits = bits & !F::all().bits();
+        if unknown_bits == F::Bits::EMPTY {
+            Ok(F::from_bits_retain(bits))
+        } else {
+            Err(super::UnknownBitsError { m_unknown_bits: unknown_bits })
+        }
+    }
+
+    /// Parses flag names and `0x` hexadecimal bits separated by `|`.
+    /// With `strict`, hexadecimal bits must correspond to defined flags.
+    pub fn parse<F: Flags + Copy>(s: &str, names: &'static [&'static str], strict: bool) -> Result<F, super::ParseError>
+    where
+        F::Bits: Bits,
+    {
+        let mut r = F::empty();
+        let s = s.trim();
+        if s.is_empty() || s == "(empty)" {
+            return Ok(r);
+        }
+        for token in s.split('|') {
+            let token = token.trim();
+            let flags = match token.strip_prefix("0x") {
+                Some(hex) => <F::Bits as bitflags::parser::ParseHex>::parse_hex(hex).ok()
+                    .and_then(|bits| if strict { F::from_bits(bits) } else { Some(F::from_bits_retain(bits)) }),
+                None => F::from_name(token),
+            };
+            let Some(flags) = flags else {
+                return Err(super::ParseError {
+                    m_token: token.to_owned(),
+                    m_valid_names: names,
+                });
+            };
+            r.insert(flags);
+        }
+        Ok(r)
+    }
+
+    /// Removes the space that follows `///` from each line of a doc comment
+    /// given as lines ending in `\n`, as well as the surrounding blank lines.
+    pub fn clean_doc(raw: &str) -> String {
+        let lines: Vec<&str> = raw.lines().map(|line| line.strip_prefix(' ').unwrap_or(line)).collect();
+        let start = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(lines.len());
+        let end = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(start, |i| i + 1);
+        lines[start..end].join("\n")
+    }
+
+    pub fn description<F: Flags + PartialEq>(flags: &F, docs: &[(&'static str, &'static str)]) -> Option<String> {
+        let flag = F::FLAGS.iter().find(|flag| flag.value() == flags)?;
+        let (_, doc) = docs.iter().find(|(name, _)| *name == flag.name())?;
+        Some(clean_doc(doc)).filter(|doc| !doc.is_empty())
+    }
+
+    #[cfg(feature = "schema")]
+    pub fn schema<F: Flags>(name: &'static str, docs: &[(&'static str, &'static str)]) -> super::FlagsSchema<F::Bits> {
+        let flags = F::FLAGS.iter().map(|flag| {
+            let doc = docs.iter().find(|(name, _)| *name == flag.name()).map(|(_, doc)| clean_doc(doc)).unwrap_or_default();
+            super::FlagSchema { m_name: flag.name(), m_bits: flag.value().bits(), m_doc: doc }
+        }).collect();
+        super::FlagsSchema { m_name: name, m_flags: flags }
+    }
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __flags {
+    // Separates the `#[flags(...)]` options from the
+    // attributes that are applied to the generated struct,
+    // remembering whether the struct is `#[repr(transparent)]`.
+    (
+        @outer [$($opts:ident)*] $repr:tt [$($outer:tt)*]
+        #[flags($($opt:ident),* $(,)?)]
+        $($rest:tt)*
+    ) => {
+        $crate::__flags! {
+            @outer [$($opts)* $($opt)*] $repr [$($outer)*]
+            $($rest)*
+        }
+    };
+    (
+        @outer [$($opts:ident)*] $repr:tt [$($outer:tt)*]
+        #[repr(transparent)]
+        $($rest:tt)*
+    ) => {
+        $crate::__flags! {
+            @outer [$($opts)*] [transparent] [$($outer)* #[repr(transparent)]]
+            $($rest)*
+        }
+    };
+    (
+        @outer [$($opts:ident)*] $repr:tt [$($outer:tt)*]
+        #[$($attr:tt)*]
+        $($rest:tt)*
+    ) => {
+        $crate::__flags! {
+            @outer [$($opts)*] $repr [$($outer)* #[$($attr)*]]
+            $($rest)*
+        }
+    };
+    (
+        @outer [$($opts:ident)*] $repr:tt [$($outer:tt)*]
+        $vis:vis struct $BitFlags:ident: $T:ty {
+            $(
+                $(#[$inner:ident $($args:tt)*])*
+                const $Flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $($outer)*
+        #[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord, Hash)]
+        $vis struct $BitFlags($T);
+
+        $crate::flags::__private::bitflags::bitflags! {
+            impl $BitFlags: $T {
+                $(
+                    $(#[$inner $($args)*])*
+                    const $Flag = $value;
+                )*
+            }
+        }
+
+        $crate::__impl_flags! {
+            $crate::__flags_strict!($($opts)*), $BitFlags: $T {
+                $(
+                    $(#[$inner $($args)*])*
+                    $Flag;
+                )*
+            }
+        }
+
+        $(
+            $crate::__impl_flags_option! {
+                $opts $repr $BitFlags: $T
+            }
+        )*
+    };
+    (
+        $(#[$($outer:tt)*])*
+        $vis:vis struct $BitFlags:ident: $T:ty {
+            $($body:tt)*
+        }
+
+        $($t:tt)*
+    ) => {
+        $crate::__flags! {
+            @outer [] [] []
+            $(#[$($outer)*])*
+            $vis struct $BitFlags: $T {
+                $($body)*
+            }
+        }
+
+        $crate::__flags! {
+            $($t)*
+        }
+    };
+    () => {};
+}
+
+// Expands to whether the `strict` option is among the given options.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __flags_strict {
+    (strict $($rest:ident)*) => { true };
+    ($opt:ident $($rest:ident)*) => { $crate::__flags_strict!($($rest)*) };
+    () => { false };
+}
+
+// Concatenates the lines of the doc comments among the given attributes.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __flags_doc {
+    (#[doc = $doc:expr] $($rest:tt)*) => {
+        $crate::flags::__private::core::concat!($doc, "\n", $crate::__flags_doc!($($rest)*))
+    };
+    (#[$attr:ident $($args:tt)*] $($rest:tt)*) => {
+        $crate::__flags_doc!($($rest)*)
+    };
+    () => { "" };
+}
+
+// Implements the methods and traits that bitflags does not provide.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags {
+    (
+        $strict:expr, $BitFlags:ident: $T:ty {
+            $(
+                $(#[$attr:ident $($args:tt)*])*
+                $Flag:ident;
+            )*
+        }
+    ) => {
+        #[allow(dead_code)]
+        impl $BitFlags {
+            /// The names of all flag constants, including composite and
+            /// zero-valued constants, in declaration order.
+            pub const NAMES: &'static [&'static str] = &$crate::flags::__private::names::<
+                $BitFlags,
+                { <$BitFlags as $crate::flags::Flags>::FLAGS.len() },
+            >();
+
+            /// The names and values of all flag constants, including
+            /// composite and zero-valued constants, in declaration order.
+            pub const ENTRIES: &'static [(&'static str, Self)] = &$crate::flags::__private::entries::<
+                $BitFlags,
+                { <$BitFlags as $crate::flags::Flags>::FLAGS.len() },
+            >($BitFlags::empty());
+
+            /// The doc comments of the flag constants, by name.
+            const __DOCS: &'static [(&'static str, &'static str)] = &[
+                $(
+                    (
+                        $crate::flags::__private::core::stringify!($Flag),
+                        $crate::__flags_doc!($(#[$attr $($args)*])*),
+                    ),
+                )*
+            ];
+
+            /// Convert from underlying bit representation, unless that
+            /// representation contains bits that do not correspond to a flag.
+            /// The error reports those bits.
+            pub fn from_bits_checked(bits: $T) -> $crate::flags::__private::core::result::Result<Self, $crate::flags::UnknownBitsError<$T>> {
+                $crate::flags::__private::from_bits_checked(bits)
+            }
+
+            /// Convert from underlying bit representation, preserving all
+            /// bits (even those not corresponding to a defined flag).
+            ///
+            /// # Safety
+            ///
+            /// This function is safe to call; it is `unsafe` for compatibility.
+            #[deprecated = "use `from_bits_retain`"]
+            pub const unsafe fn from_bits_unchecked(bits: $T) -> Self {
+                Self::from_bits_retain(bits)
+            }
+
+            /// Returns a mutable reference to the raw value of the flags currently stored.
+            pub fn bits_mut(&mut self) -> &mut $T {
+                &mut self.0
+            }
+
+            /// Removes all bits in-place.
+            pub fn clear(&mut self) {
+                self.0 = 0;
+            }
+
+            /// Returns the number of single-bit flags currently stored.
+            pub fn len(&self) -> u32 {
+                $crate::flags::__private::len(*self)
+            }
+
+            /// Returns `true` if any of the flags in `other` is contained
+            /// within `self`. Same as `intersects`.
+            pub const fn contains_any(&self, other: Self) -> bool {
+                self.intersects(other)
+            }
+
+            /// Returns `true` if all of the flags in `other` are contained
+            /// within `self`. Same as `contains`.
+            pub const fn contains_all(&self, other: Self) -> bool {
+                self.contains(other)
+            }
+
+            /// Inserts the specified flags in-place, returning `true`
+            /// only if the stored bits changed.
+            pub fn insert_checked(&mut self, other: Self) -> bool {
+                let bits = self.0;
+                self.insert(other);
+                self.0 != bits
+            }
+
+            /// Removes the specified flags in-place, returning `true`
+            /// only if the stored bits changed.
+            pub fn remove_checked(&mut self, other: Self) -> bool {
+                let bits = self.0;
+                self.remove(other);
+                self.0 != bits
+            }
+
+            /// Inserts the specified flags in-place if `value` is `true`.
+            pub fn insert_if(&mut self, other: Self, value: bool) {
+                if value {
+                    self.insert(other);
+                }
+            }
+
+            /// Retains only the single-bit flags for which the predicate
+            /// returns `true`, calling it in ascending bit order.
+            pub fn retain<F: $crate::flags::__private::core::ops::FnMut(Self) -> bool>(&mut self, f: F) {
+                $crate::flags::__private::retain(self, f)
+            }
+
+            /// Joins the names of the single-bit flags contained in `self`,
+            /// in ascending bit order, with `sep`.
+            pub fn to_names_string(self, sep: &str) -> ::std::string::String {
+                $crate::flags::__private::to_names_string(self, sep)
+            }
+
+            /// Returns the doc comment of the flag constant equal to `self`,
+            /// or `None` if it has none.
+            pub fn description(&self) -> $crate::flags::__private::core::option::Option<::std::string::String> {
+                $crate::flags::__private::description(self, Self::__DOCS)
+            }
+        }
+
+        impl $crate::flags::__private::core::fmt::Debug for $BitFlags {
+            fn fmt(&self, f: &mut $crate::flags::__private::core::fmt::Formatter) -> $crate::flags::__private::core::fmt::Result {
+                $crate::flags::__private::fmt_debug(*self, f)
+            }
+        }
+
+        impl $crate::flags::__private::core::str::FromStr for $BitFlags {
+            type Err = $crate::flags::ParseError;
+
+            fn from_str(s: &str) -> $crate::flags::__private::core::result::Result<Self, $crate::flags::ParseError> {
+                $crate::flags::__private::parse(s, Self::NAMES, $strict)
+            }
+        }
+
+        impl<'a> $crate::flags::__private::core::iter::IntoIterator for &'a $BitFlags {
+            type Item = $BitFlags;
+            type IntoIter = $crate::flags::bitflags::iter::Iter<$BitFlags>;
+
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+    };
+}
+
+// Expands a `#[flags(...)]` option.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_option {
+    (arbitrary $repr:tt $BitFlags:ident: $T:ty) => {
+        $crate::__impl_flags_arbitrary! { $BitFlags: $T }
+    };
+    (bytemuck [transparent] $BitFlags:ident: $T:ty) => {
+        $crate::__impl_flags_bytemuck! { $BitFlags: $T }
+    };
+    (bytemuck [] $BitFlags:ident: $T:ty) => {
+        $crate::flags::__private::core::compile_error!($crate::flags::__private::core::concat!(
+            "the `bytemuck` flags option requires `",
+            $crate::flags::__private::core::stringify!($BitFlags),
+            "` to be `#[repr(transparent)]`"
+        ));
+    };
+    (serde $repr:tt $BitFlags:ident: $T:ty) => {
+        $crate::__impl_flags_serde! { $BitFlags: $T }
+    };
+    (schema $repr:tt $BitFlags:ident: $T:ty) => {
+        $crate::__impl_flags_schema! { $BitFlags: $T }
+    };
+    // Handled by `__flags_strict`.
+    (strict $repr:tt $BitFlags:ident: $T:ty) => {};
+    ($opt:ident $repr:tt $BitFlags:ident: $T:ty) => {
+        $crate::flags::__private::core::compile_error!($crate::flags::__private::core::concat!(
+            "unknown flags option `", $crate::flags::__private::core::stringify!($opt), "`"
+        ));
+    };
+}
+
+#[cfg(feature = "arbitrary")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_arbitrary {
+    ($BitFlags:ident: $T:ty) => {
+        impl $crate::flags::__private::proptest::arbitrary::Arbitrary for $BitFlags {
+            type Parameters = ();
+            type Strategy = $crate::flags::__private::proptest::strategy::Map<
+                $crate::flags::__private::proptest::arbitrary::StrategyFor<$T>,
+                fn($T) -> Self,
+            >;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                use $crate::flags::__private::proptest::strategy::Strategy as _;
+                $crate::flags::__private::proptest::arbitrary::any::<$T>()
+                    .prop_map(Self::from_bits_truncate as fn($T) -> Self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "arbitrary"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_arbitrary {
+    ($BitFlags:ident: $T:ty) => {
+        $crate::flags::__private::core::compile_error!("the `arbitrary` flags option requires the `arbitrary` feature of rialight_util");
+    };
+}
+
+#[cfg(feature = "bytemuck")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_bytemuck {
+    ($BitFlags:ident: $T:ty) => {
+        const _: () = {
+            // The struct is `#[repr(transparent)]` over `$T`,
+            // so it is `Pod` as long as `$T` is.
+            const fn assert_pod<T: $crate::flags::__private::bytemuck::Pod>() {}
+            assert_pod::<$T>();
+        };
+
+        // SAFETY: `$BitFlags` is `#[repr(transparent)]` over a `Pod` integer.
+        unsafe impl $crate::flags::__private::bytemuck::Zeroable for $BitFlags {}
+
+        // SAFETY: `$BitFlags` is `#[repr(transparent)]` over a `Pod` integer.
+        unsafe impl $crate::flags::__private::bytemuck::Pod for $BitFlags {}
+    };
+}
+
+#[cfg(not(feature = "bytemuck"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_bytemuck {
+    ($BitFlags:ident: $T:ty) => {
+        $crate::flags::__private::core::compile_error!("the `bytemuck` flags option requires the `bytemuck` feature of rialight_util");
+    };
+}
+
+#[cfg(feature = "serde")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_serde {
+    ($BitFlags:ident: $T:ty) => {
+        impl $crate::flags::__private::serde::Serialize for $BitFlags {
+            fn serialize<S>(&self, serializer: S) -> $crate::flags::__private::core::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::flags::__private::serde::Serializer,
+            {
+                use $crate::flags::__private::serde::ser::{Error as _, SerializeSeq as _};
+                let (names, remaining) = $crate::flags::__private::names_of(*self);
+                if remaining != 0 {
+                    return $crate::flags::__private::core::result::Result::Err(S::Error::custom($crate::flags::__private::core::format_args!(
+                        "bits {:#x} do not correspond to a flag of {}",
+                        remaining,
+                        $crate::flags::__private::core::stringify!($BitFlags),
+                    )));
+                }
+                let mut seq = serializer.serialize_seq($crate::flags::__private::core::option::Option::Some(names.len()))?;
+                for name in names {
+                    seq.serialize_element(name)?;
+                }
+                seq.end()
+            }
+        }
+
+        impl<'de> $crate::flags::__private::serde::Deserialize<'de> for $BitFlags {
+            fn deserialize<D>(deserializer: D) -> $crate::flags::__private::core::result::Result<Self, D::Error>
+            where
+                D: $crate::flags::__private::serde::Deserializer<'de>,
+            {
+                struct Visitor;
+
+                impl<'de> $crate::flags::__private::serde::de::Visitor<'de> for Visitor {
+                    type Value = $BitFlags;
+
+                    fn expecting(&self, f: &mut $crate::flags::__private::core::fmt::Formatter) -> $crate::flags::__private::core::fmt::Result {
+                        f.write_str($crate::flags::__private::core::concat!(
+                            "a list of ", $crate::flags::__private::core::stringify!($BitFlags), " flag names"
+                        ))
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> $crate::flags::__private::core::result::Result<$BitFlags, A::Error>
+                    where
+                        A: $crate::flags::__private::serde::de::SeqAccess<'de>,
+                    {
+                        use $crate::flags::__private::serde::de::Error as _;
+                        let mut r = $BitFlags::empty();
+                        while let $crate::flags::__private::core::option::Option::Some(name) = seq.next_element::<::std::string::String>()? {
+                            match $BitFlags::from_name(&name) {
+                                $crate::flags::__private::core::option::Option::Some(flag) => r.insert(flag),
+                                $crate::flags::__private::core::option::Option::None => {
+                                    return $crate::flags::__private::core::result::Result::Err(A::Error::unknown_variant(&name, $BitFlags::NAMES));
+                                },
+                            }
+                        }
+                        $crate::flags::__private::core::result::Result::Ok(r)
+                    }
+                }
+
+                deserializer.deserialize_seq(Visitor)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_serde {
+    ($BitFlags:ident: $T:ty) => {
+        $crate::flags::__private::core::compile_error!("the `serde` flags option requires the `serde` feature of rialight_util");
+    };
+}
+
+#[cfg(feature = "schema")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_schema {
+    ($BitFlags:ident: $T:ty) => {
+        impl $BitFlags {
+            /// Describes the flag constants of this type, with their names,
+            /// bits and doc comments, in declaration order.
+            pub fn schema() -> $crate::flags::FlagsSchema<$T> {
+                $crate::flags::__private::schema::<Self>($crate::flags::__private::core::stringify!($BitFlags), Self::__DOCS)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "schema"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_flags_schema {
+    ($($t:tt)*) => {
+        $crate::flags::__private::core::compile_error!("the `schema` flags option requires the `schema` feature of rialight_util");
+    };
+}
@@ -10,7 +10,15 @@ pub mod lazy_static;
 pub mod flags;
 pub mod regex;
 
-/// Creates a `HashMap` object from a list of key-value pairs.
+#[cfg(feature = "indexmap")]
+#[doc(hidden)]
+pub mod __private {
+    pub use indexmap;
+}
+
+/// Creates a `HashMap` object from a list of key-value pairs, allocated
+/// with the capacity for the given number of pairs. A different capacity
+/// may be given before the pairs, as in `hashmap!(cap: 128; "a" => 1)`.
 ///
 /// ## Example
 ///
@@ -24,10 +32,20 @@ pub mod regex;
 /// assert_eq!(map["b"], "bar");
 /// ```
 pub macro hashmap {
+    (cap: $capacity:expr; $($key:expr => $value:expr),* $(,)?) => {
+        {
+            #[allow(unused_mut)]
+            let mut r_map = ::std::collections::HashMap::with_capacity($capacity);
+            $(
+                let _ = r_map.insert($key, $value);
+            )*
+            r_map
+        }
+    },
     ($($key:expr => $value:expr,)+) => {
         {
             #[allow(unused_mut)]
-            let mut r_map = ::std::collections::HashMap::new();
+            let mut r_map = ::std::collections::HashMap::with_capacity(<[&str]>::len(&[$(::std::stringify!($key)),*]));
             $(
                 let _ = r_map.insert($key, $value);
             )*
@@ -37,7 +55,7 @@ pub macro hashmap {
     ($($key:expr => $value:expr),*) => {
         {
             #[allow(unused_mut)]
-            let mut r_map = ::std::collections::HashMap::new();
+            let mut r_map = ::std::collections::HashMap::with_capacity(<[&str]>::len(&[$(::std::stringify!($key)),*]));
             $(
                 let _ = r_map.insert($key, $value);
             )*
@@ -46,7 +64,8 @@ pub macro hashmap {
     }
 }
 
-/// Creates a `BTreeMap` object from a list of key-value pairs.
+/// Creates a `BTreeMap` object from a list of key-value pairs,
+/// whose entries are iterated in key order.
 ///
 /// ## Example
 ///
@@ -82,7 +101,47 @@ pub macro btreemap {
     }
 }
 
-/// Creates a `HashSet` object from a list of values.
+/// Creates an `IndexMap` object from a list of key-value pairs,
+/// whose entries are iterated in the order in which they are given.
+/// Requires the `indexmap` feature.
+///
+/// ## Example
+///
+/// ```
+/// use rialight::util::indexmap;
+/// let map = indexmap!{
+///     "b" => "bar",
+///     "a" => "foo",
+/// };
+/// assert_eq!(map.keys().collect::<Vec<_>>(), [&"b", &"a"]);
+/// ```
+#[cfg(feature = "indexmap")]
+pub macro indexmap {
+    ($($key:expr => $value:expr,)+) => {
+        {
+            #[allow(unused_mut)]
+            let mut r_map = $crate::__private::indexmap::IndexMap::with_capacity(<[&str]>::len(&[$(::std::stringify!($key)),*]));
+            $(
+                let _ = r_map.insert($key, $value);
+            )*
+            r_map
+        }
+    },
+    ($($key:expr => $value:expr),*) => {
+        {
+            #[allow(unused_mut)]
+            let mut r_map = $crate::__private::indexmap::IndexMap::with_capacity(<[&str]>::len(&[$(::std::stringify!($key)),*]));
+            $(
+                let _ = r_map.insert($key, $value);
+            )*
+            r_map
+        }
+    }
+}
+
+/// Creates a `HashSet` object from a list of values, allocated
+/// with the capacity for the given number of values. Duplicate
+/// values are inserted once.
 ///
 /// ## Example
 ///
@@ -94,7 +153,7 @@ pub macro hashset {
     ($($value:expr,)+) => {
         {
             #[allow(unused_mut)]
-            let mut r_set = ::std::collections::HashSet::new();
+            let mut r_set = ::std::collections::HashSet::with_capacity(<[&str]>::len(&[$(::std::stringify!($value)),*]));
             $(
                 let _ = r_set.insert($value);
             )*
@@ -104,7 +163,7 @@ pub macro hashset {
     ($($value:expr),*) => {
         {
             #[allow(unused_mut)]
-            let mut r_set = ::std::collections::HashSet::new();
+            let mut r_set = ::std::collections::HashSet::with_capacity(<[&str]>::len(&[$(::std::stringify!($value)),*]));
             $(
                 let _ = r_set.insert($value);
             )*
@@ -149,9 +208,26 @@ mod test {
     use super::{
         template_string,
         hashmap, hashset, btreemap, btreeset, lazy_static::lazy_static,
+        flags::flags,
     };
     use std::collections::HashMap;
 
+    flags! {
+        struct Flags: u32 {
+            const A = 0b00000001;
+            const B = 0b00000010;
+            const C = 0b00000100;
+            const ABC = Self::A.bits() | Self::B.bits() | Self::C.bits();
+        }
+
+        struct CfgFlags: u32 {
+            #[cfg(unix)]
+            const CFG_A = 0b01;
+            #[cfg(windows)]
+            const CFG_B = 0b01;
+        }
+    }
+
     #[test]
     fn lazy_static_utility() {
         lazy_static! {
@@ -168,15 +244,445 @@ mod test {
         let map = hashmap!{"a" => "foo", "b" => "bar"};
         assert_eq!(map["a"], "foo");
         assert_eq!(map["b"], "bar");
+        assert!(map.capacity() >= 2);
+        let map = hashmap!(cap: 128; "a" => 1, "b" => 2,);
+        assert_eq!(map["b"], 2);
+        assert!(map.capacity() >= 128);
+        let map: HashMap<&str, i32> = hashmap!(cap: 16;);
+        assert!(map.is_empty() && map.capacity() >= 16);
         let map = btreemap!{"a" => "foo", "b" => "bar"};
         assert_eq!(map["a"], "foo");
         assert_eq!(map["b"], "bar");
+        let map = btreemap!{"b" => 2, "a" => 1,};
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![("a", 1), ("b", 2)]);
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn indexmap_literal() {
+        let map = super::indexmap!{"b" => 2, "a" => 1, "c" => 3,};
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![("b", 2), ("a", 1), ("c", 3)]);
+        let map = super::indexmap!{"b" => 2, "a" => 1, "b" => 4};
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![("b", 4), ("a", 1)]);
     }
 
     #[test]
     fn set_literal() {
         assert!(hashset!{"foo"}.contains("foo"));
         assert!(btreeset!{"foo"}.contains("foo"));
+        let set = hashset!{"a", "b", "a",};
+        assert_eq!(set.len(), 2);
+        assert!(set.capacity() >= 3);
+        let set: std::collections::HashSet<i32> = hashset!{};
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn flags() {
+        let e1 = Flags::A | Flags::C;
+        let e2 = Flags::B | Flags::C;
+        assert_eq!(e1 | e2, Flags::ABC);
+        assert_eq!(e1 & e2, Flags::C);
+        assert_eq!(e1 - e2, Flags::A);
+        assert_eq!(!e2, Flags::A);
+        assert_eq!(format!("{:?}", Flags::A | Flags::B), "A | B");
+        assert_eq!(CfgFlags::all().bits(), 0b01);
+    }
+
+    #[test]
+    fn flags_const() {
+        const MASK: Flags = Flags::A.union(Flags::B);
+        const TABLE: [Flags; 4] = [
+            MASK.intersection(Flags::B),
+            MASK.difference(Flags::A),
+            MASK.complement(),
+            MASK.symmetric_difference(Flags::ABC),
+        ];
+        assert_eq!(MASK, Flags::A | Flags::B);
+        assert_eq!(TABLE, [MASK & Flags::B, MASK - Flags::A, !MASK, MASK ^ Flags::ABC]);
+    }
+
+    #[test]
+    fn flags_iter() {
+        flags! {
+            struct Unordered: u8 {
+                const C = 0b100;
+                const NONE = 0;
+                const A = 0b001;
+                const AC = Self::A.bits() | Self::C.bits();
+                const ALIAS = 0b001;
+            }
+        }
+        assert_eq!((Flags::A | Flags::C).iter().collect::<Vec<_>>(), vec![Flags::A, Flags::C]);
+        assert_eq!(Flags::ABC.iter().collect::<Vec<_>>(), vec![Flags::A, Flags::B, Flags::C]);
+        assert_eq!(Flags::empty().iter().count(), 0);
+        assert_eq!(Unordered::all().iter().collect::<Vec<_>>(), vec![Unordered::C, Unordered::A]);
+        assert_eq!(Unordered::all().iter_names().collect::<Vec<_>>(), vec![("C", Unordered::C), ("A", Unordered::A)]);
+        let extra = Flags::from_bits_retain(0b1010);
+        assert_eq!(extra.iter_names().collect::<Vec<_>>(), vec![("B", Flags::B)]);
+
+        assert_eq!(extra.iter().collect::<Vec<_>>(), vec![Flags::B, Flags::from_bits_retain(0b1000)]);
+        let mut collected = vec![];
+        for flag in &Flags::ABC {
+            collected.push(flag);
+        }
+        assert_eq!(collected, Flags::ABC.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn flags_debug() {
+        flags! {
+            struct Unordered: u8 {
+                const C = 0b0100;
+                const A = 0b0001;
+                const AC = Self::A.bits() | Self::C.bits();
+                const HIGH = 0b1000_0000;
+                const WIDE = 0b0011_0000;
+            }
+        }
+        assert_eq!(format!("{:?}", Unordered::AC), "A | C");
+        assert_eq!(format!("{:?}", Unordered::HIGH | Unordered::C), "C | HIGH");
+        assert_eq!(format!("{:?}", Unordered::WIDE | Unordered::A), "A | WIDE");
+        assert_eq!(format!("{:?}", Unordered::WIDE), "WIDE");
+        assert_eq!(format!("{:?}", Unordered::all()), "A | C | HIGH | WIDE");
+        assert_eq!(format!("{:?}", Unordered::from_bits_retain(0b0001_0000)), "0x10");
+        assert_eq!(format!("{:?}", Unordered::empty()), "(empty)");
+        let extra = Flags::from_bits_retain(0b1010);
+        assert_eq!(format!("{:?}", extra), "B | 0x8");
+        assert_eq!(format!("{:?}", Flags::ABC), "A | B | C");
+    }
+
+    #[test]
+    fn flags_u128() {
+        flags! {
+            struct Caps: u128 {
+                const LOW = 1;
+                const HIGH = 1 << 100;
+                const TOP = 1 << 127;
+            }
+        }
+        assert_eq!(Caps::HIGH.bits(), 1 << 100);
+        assert_eq!(Caps::from_bits(1 << 100), Some(Caps::HIGH));
+        assert_eq!(Caps::from_bits((1 << 100) | 1), Some(Caps::LOW | Caps::HIGH));
+        assert_eq!(Caps::from_bits(1 << 101), None);
+        assert_eq!(Caps::from_bits_truncate((1 << 101) | (1 << 127)), Caps::TOP);
+        assert_eq!(Caps::all().len(), 3);
+        assert_eq!(Caps::all().iter().collect::<Vec<_>>(), vec![Caps::LOW, Caps::HIGH, Caps::TOP]);
+        assert_eq!(format!("{:?}", Caps::HIGH | Caps::TOP), "HIGH | TOP");
+        let extra = Caps::from_bits_retain((1 << 100) | (1 << 120));
+        assert_eq!(format!("{:?}", extra), "HIGH | 0x1000000000000000000000000000000");
+        assert_eq!(Caps::from_bits_checked(1 << 120).unwrap_err().unknown_bits(), 1 << 120);
+        assert_eq!("HIGH | TOP".parse::<Caps>().unwrap(), Caps::HIGH | Caps::TOP);
+    }
+
+    #[test]
+    fn flags_from_name() {
+        assert_eq!(Flags::from_name("A"), Some(Flags::A));
+        assert_eq!(Flags::from_name("ABC"), Some(Flags::ABC));
+        assert_eq!(Flags::from_name("a"), None);
+        assert_eq!(Flags::from_name("D"), None);
+        assert_eq!(CfgFlags::from_name("CFG_A").is_some(), cfg!(unix));
+    }
+
+    #[test]
+    fn flags_names() {
+        assert_eq!(Flags::NAMES, &["A", "B", "C", "ABC"]);
+        assert_eq!(CfgFlags::NAMES.len(), 1);
+    }
+
+    #[test]
+    fn flags_entries() {
+        assert_eq!(Flags::ENTRIES, &[("A", Flags::A), ("B", Flags::B), ("C", Flags::C), ("ABC", Flags::ABC)]);
+        assert_eq!(CfgFlags::ENTRIES.len(), 1);
+        let reverse = Flags::ENTRIES.iter().map(|&(name, flag)| (flag, name)).collect::<HashMap<_, _>>();
+        assert_eq!(reverse[&Flags::ABC], "ABC");
+    }
+
+    #[test]
+    fn flags_len() {
+        assert_eq!(Flags::empty().len(), 0);
+        assert_eq!((Flags::A | Flags::C).len(), 2);
+        assert_eq!(Flags::ABC.len(), 3);
+        let extra = Flags::from_bits_retain(0b1001);
+        assert_eq!(extra.len(), 1);
+    }
+
+    #[test]
+    fn flags_from_bits_checked() {
+        assert_eq!(Flags::from_bits_checked(0b101), Ok(Flags::A | Flags::C));
+        let error = Flags::from_bits_checked(0b11001).unwrap_err();
+        assert_eq!(error.unknown_bits(), 0b11000);
+        assert_eq!(error.to_string(), "bits 0x18 do not correspond to a defined flag");
+    }
+
+    #[test]
+    fn flags_from_bits_retain() {
+        let extra = Flags::from_bits_retain(0b11001);
+        assert_eq!(extra.bits(), 0b11001);
+        assert_eq!(Flags::from_bits(extra.bits()), None);
+        assert_eq!(Flags::from_bits_truncate(extra.bits()), Flags::A);
+        #[allow(deprecated)]
+        let unchecked = unsafe { Flags::from_bits_unchecked(0b11001) };
+        assert_eq!(unchecked, extra);
+    }
+
+    flags! {
+        #[flags(strict)]
+        struct StrictFlags: u8 {
+            const A = 0b01;
+            const B = 0b10;
+        }
+    }
+
+    #[test]
+    fn flags_strict() {
+        assert_eq!(!StrictFlags::A, StrictFlags::B);
+        assert_eq!(StrictFlags::empty().complement(), StrictFlags::all());
+        let mut flags = StrictFlags::from_bits_retain(0b01);
+        flags.insert(StrictFlags::B);
+        assert!(flags.is_all());
+    }
+
+    #[test]
+    fn flags_strict_undefined_bits() {
+        assert_eq!("0x4".parse::<StrictFlags>().unwrap_err().token(), "0x4");
+        assert_eq!("A | 0x2".parse::<StrictFlags>().unwrap(), StrictFlags::all());
+        assert_eq!("0x4".parse::<Flags>().unwrap(), Flags::C);
+    }
+
+    #[test]
+    fn flags_contains_any_all() {
+        let flags = Flags::A | Flags::B;
+        assert!(flags.contains_any(Flags::B | Flags::C));
+        assert!(!flags.contains_all(Flags::B | Flags::C));
+        assert!(flags.contains_all(Flags::A | Flags::B));
+        assert!(!flags.contains_any(Flags::C));
+        assert!(!flags.contains_any(Flags::empty()));
+        assert!(flags.contains_all(Flags::empty()));
+    }
+
+    #[test]
+    fn flags_description() {
+        flags! {
+            struct Video: u8 {
+                /// Enables vsync
+                const VSYNC = 0b01;
+                ///
+                /// Renders at the native resolution.
+                ///
+                /// Costly on older devices.
+                ///
+                const NATIVE = 0b10;
+                const BOTH = Self::VSYNC.bits() | Self::NATIVE.bits();
+            }
+        }
+        assert_eq!(Video::VSYNC.description(), Some("Enables vsync".to_owned()));
+        assert_eq!(Video::NATIVE.description(), Some("Renders at the native resolution.\n\nCostly on older devices.".to_owned()));
+        assert_eq!(Video::BOTH.description(), None);
+        assert_eq!(Video::empty().description(), None);
+    }
+
+    #[test]
+    fn flags_clear_and_bits_mut() {
+        let mut flags = Flags::A | Flags::C;
+        *flags.bits_mut() &= !Flags::A.bits();
+        assert_eq!(flags, Flags::C);
+        *flags.bits_mut() |= 0b1000;
+        assert_eq!(flags.bits(), 0b1100);
+        flags.clear();
+        assert!(flags.is_empty());
+        assert_eq!(flags.bits(), 0);
+    }
+
+    #[test]
+    fn flags_insert_if() {
+        let mut flags = Flags::A;
+        flags.insert_if(Flags::B, true);
+        flags.insert_if(Flags::C, false);
+        assert_eq!(flags, Flags::A | Flags::B);
+        flags.insert_if(Flags::A, false);
+        assert_eq!(flags, Flags::A | Flags::B);
+    }
+
+    #[test]
+    fn flags_is_all() {
+        flags! {
+            struct Caps: u8 {
+                const NONE = 0;
+                const A = 0b001;
+                const B = 0b010;
+                const C = 0b100;
+                const ABC = Self::A.bits() | Self::B.bits() | Self::C.bits();
+            }
+        }
+        assert_eq!(Caps::all().bits(), 0b111);
+        assert!(Caps::all().is_all());
+        assert!(Caps::ABC.is_all());
+        assert!((Caps::A | Caps::B | Caps::C | Caps::NONE).is_all());
+        assert!(!(Caps::A | Caps::B).is_all());
+        assert!(!Caps::NONE.is_all());
+        assert!(Caps::from_bits_retain(0b1111).is_all());
+    }
+
+    #[test]
+    fn flags_checked_operations() {
+        let mut flags = Flags::A;
+        assert!(flags.insert_checked(Flags::B));
+        assert!(!flags.insert_checked(Flags::A));
+        assert!(!flags.insert_checked(Flags::empty()));
+        assert!(flags.remove_checked(Flags::A | Flags::C));
+        assert!(!flags.remove_checked(Flags::C));
+        assert_eq!(flags, Flags::B);
+    }
+
+    #[test]
+    fn flags_retain() {
+        let mut flags = Flags::ABC;
+        let mut visited = vec![];
+        flags.retain(|flag| {
+            visited.push(flag);
+            flag != Flags::B
+        });
+        assert_eq!(flags, Flags::A | Flags::C);
+        assert_eq!(visited, vec![Flags::A, Flags::B, Flags::C]);
+
+        let mut extra = Flags::from_bits_retain(0b1001);
+        extra.retain(|_| false);
+        assert_eq!(extra.bits(), 0b1000);
+    }
+
+    #[test]
+    fn flags_to_names_string() {
+        assert_eq!((Flags::C | Flags::A).to_names_string(", "), "A, C");
+        assert_eq!(Flags::ABC.to_names_string(" + "), "A + B + C");
+        assert_eq!(Flags::empty().to_names_string(", "), "");
+    }
+
+    #[test]
+    fn flags_from_str() {
+        assert_eq!("A | C".parse::<Flags>().unwrap(), Flags::A | Flags::C);
+        assert_eq!("A|B".parse::<Flags>().unwrap(), Flags::A | Flags::B);
+        assert_eq!(" ABC ".parse::<Flags>().unwrap(), Flags::ABC);
+        assert_eq!("".parse::<Flags>().unwrap(), Flags::empty());
+        let error = "A | D".parse::<Flags>().unwrap_err();
+        assert_eq!(error.token(), "D");
+        assert_eq!(error.valid_names(), &["A", "B", "C", "ABC"]);
+        assert_eq!("A |".parse::<Flags>().unwrap_err().token(), "");
+        assert_eq!("(empty)".parse::<Flags>().unwrap(), Flags::empty());
+        assert_eq!("0x5".parse::<Flags>().unwrap(), Flags::A | Flags::C);
+        assert_eq!("0xg".parse::<Flags>().unwrap_err().token(), "0xg");
+
+        flags! {
+            struct CompositeFlags: u32 {
+                const A = 0b0001;
+                const HI = 0b1100;
+            }
+        }
+        for flags in [Flags::empty(), Flags::A | Flags::C, Flags::from_bits_retain(0x11)] {
+            assert_eq!(format!("{:?}", flags).parse::<Flags>().unwrap(), flags);
+        }
+        for flags in [CompositeFlags::empty(), CompositeFlags::HI, CompositeFlags::all(), CompositeFlags::from_bits_retain(0x14)] {
+            assert_eq!(format!("{:?}", flags).parse::<CompositeFlags>().unwrap(), flags);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn flags_serde() {
+        flags! {
+            #[flags(serde)]
+            struct SerdeFlags: u32 {
+                const A = 0b00000001;
+                const B = 0b00000010;
+                const C = 0b00000100;
+                const ABC = Self::A.bits() | Self::B.bits() | Self::C.bits();
+            }
+        }
+        assert_eq!(serde_json::to_string(&(SerdeFlags::A | SerdeFlags::C)).unwrap(), r#"["A","C"]"#);
+        assert_eq!(serde_json::to_string(&SerdeFlags::empty()).unwrap(), "[]");
+        assert_eq!(serde_json::from_str::<SerdeFlags>(r#"["A","C"]"#).unwrap(), SerdeFlags::A | SerdeFlags::C);
+        assert_eq!(serde_json::from_str::<SerdeFlags>(r#"["ABC"]"#).unwrap(), SerdeFlags::all());
+        assert_eq!(serde_json::from_str::<SerdeFlags>("[]").unwrap(), SerdeFlags::empty());
+        let error = serde_json::from_str::<SerdeFlags>(r#"["A","D"]"#).unwrap_err();
+        assert!(error.to_string().contains("`D`"));
+
+        flags! {
+            #[flags(serde)]
+            struct CompositeFlags: u32 {
+                const A = 0b0001;
+                const HI = 0b1100;
+            }
+        }
+        assert_eq!(serde_json::to_string(&CompositeFlags::HI).unwrap(), r#"["HI"]"#);
+        assert_eq!(serde_json::to_string(&CompositeFlags::all()).unwrap(), r#"["A","HI"]"#);
+        assert_eq!(serde_json::from_str::<CompositeFlags>(r#"["A","HI"]"#).unwrap(), CompositeFlags::all());
+        assert!(serde_json::to_string(&CompositeFlags::from_bits_retain(0b0100)).is_err());
+        assert!(serde_json::to_string(&CompositeFlags::from_bits_retain(0b10001)).is_err());
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn flags_schema() {
+        flags! {
+            #[flags(schema)]
+            struct SchemaFlags: u32 {
+                /// Collides with the world.
+                const WORLD = 0b01;
+                /// Collides with players.
+                ///
+                /// Includes spectators.
+                #[allow(dead_code)]
+                const PLAYERS = 0b10;
+                #[cfg(any())]
+                const REMOVED = 0b100;
+                const ALL = Self::WORLD.bits() | Self::PLAYERS.bits();
+            }
+        }
+        let schema = SchemaFlags::schema();
+        assert_eq!(schema.name(), "SchemaFlags");
+        let flags: Vec<_> = schema.flags().iter().map(|flag| (flag.name(), flag.bits(), flag.doc())).collect();
+        assert_eq!(flags, vec![
+            ("WORLD", 0b01, "Collides with the world."),
+            ("PLAYERS", 0b10, "Collides with players.\n\nIncludes spectators."),
+            ("ALL", 0b11, ""),
+        ]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn flags_arbitrary() {
+        use proptest::{arbitrary::any, test_runner::TestRunner};
+        flags! {
+            #[flags(arbitrary)]
+            struct ArbitraryFlags: u8 {
+                const A = 0b00000001;
+                const C = 0b00000100;
+                const AC = Self::A.bits() | Self::C.bits();
+            }
+        }
+        TestRunner::default().run(&any::<ArbitraryFlags>(), |flags| {
+            assert!(ArbitraryFlags::all().contains(flags));
+            assert_eq!(ArbitraryFlags::from_bits(flags.bits()), Some(flags));
+            Ok(())
+        }).unwrap();
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn flags_bytemuck() {
+        flags! {
+            #[flags(bytemuck)]
+            #[repr(transparent)]
+            struct PodFlags: u32 {
+                const A = 0b00000001;
+                const B = 0b00000010;
+            }
+        }
+        let flags = [PodFlags::A, PodFlags::A | PodFlags::B];
+        let bytes: &[u8] = bytemuck::cast_slice(&flags);
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(bytemuck::cast_slice::<u8, u32>(bytes), &[1, 3]);
+        assert_eq!(<PodFlags as bytemuck::Zeroable>::zeroed(), PodFlags::empty());
     }
 
     #[test]